@@ -0,0 +1,39 @@
+//! Room assignment core of the rooms scheduler service.
+//!
+//! The library has no HTTP dependencies, so the algorithm can be used
+//! directly from other Rust projects:
+//!
+//! ```
+//! use rooms_scheduler_core::{run_scheduler, Activity, Room, SchedulerOptions};
+//! use serde_json::json;
+//!
+//! let rooms: Vec<Room> = serde_json::from_value(json!([
+//!     { "id": 1, "name": "A-101", "capacity": 40 },
+//! ])).unwrap();
+//!
+//! let activities: Vec<Activity> = serde_json::from_value(json!([{
+//!     "id": 1,
+//!     "subject": "Calculus",
+//!     "room": rooms[0],
+//!     "time_slots": [0, 1],
+//!     "students_count": 35,
+//! }])).unwrap();
+//!
+//! let schedule = run_scheduler(activities, rooms, &SchedulerOptions::default()).unwrap();
+//!
+//! assert_eq!(schedule.scheduled_activities[0].room.name, "A-101");
+//! assert!(schedule.unscheduled_activities.is_empty());
+//! ```
+
+pub mod models;
+pub mod scheduler;
+
+pub use models::{Activity, ActivitiesRequest, AnnealingParams, AssignmentReason, BatchScenario, Building, CalendarMapping, CalendarRequest, CapacityPolicy, ChangeKind, DaySchedule, FeasibilityRequest, Instructor, RankedSchedule, Room, RoomBlackout, RoomTimeline, Schedule, ScheduleChange, SchedulerOptions, SchedulingProgress, ScoringWeights, SlotGrid, Strategy, TimelineSlot, UnscheduledActivity, UnscheduledReason, Weekday};
+pub use scheduler::algorithm::{feasible_rooms, run_scheduler, run_scheduler_with_progress};
+pub use scheduler::calendar::schedule_to_ics;
+pub use scheduler::incremental::schedule_changes;
+pub use scheduler::export::{schedule_to_csv, schedule_to_xlsx};
+pub use scheduler::error::{PinConflict, PinConflictKind, SchedulerError, SlotShortfall, ValidationError};
+pub use scheduler::scoring::{RoomScore, schedule_score, score_room, score_room_after};
+pub use scheduler::time_slots::resolve_time_slots;
+pub use scheduler::validation::{validate_activity, validate_request, validate_room};
@@ -0,0 +1,717 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Room {
+    pub id: u32,
+    pub name: String,
+    pub capacity: u32,
+    /// Name of one of the request's `buildings`, or just a label when the
+    /// request has none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub building: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    /// Most slots the room may be booked for in a single day.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_daily_slots: Option<u32>,
+    /// Subjects the room is reserved for; `None` opens it to every activity.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_subjects: Option<Vec<String>>,
+    #[serde(default)]
+    pub wheelchair_accessible: bool,
+    /// Ids of the rooms this one is made of when their partition walls are
+    /// opened; booking it blocks them, and booking any of them blocks it.
+    /// Greedy strategy only.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub combines: Vec<u32>,
+    /// Slots the room is closed in (cleaning, maintenance, other
+    /// departments), on every day.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unavailable_slots: Vec<u32>,
+    /// Closures of the room; the HTTP layer adds the ones stored through
+    /// `/api/v1/rooms/{room_id}/blackouts` before scheduling.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blackouts: Vec<RoomBlackout>,
+}
+
+impl Room {
+    /// Whether the room is unavailable or blacked out in the slot of the day.
+    pub fn is_closed(&self, day: u8, slot: u32) -> bool {
+        self.unavailable_slots.contains(&slot)
+            || self.blackouts.iter().any(|blackout| {
+                blackout.day.is_none_or(|blackout_day| blackout_day == day) && blackout.slots.contains(&slot)
+            })
+    }
+}
+
+/// A period a room is closed in, e.g. for maintenance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoomBlackout {
+    /// Day the room is closed on; every day when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub day: Option<u8>,
+    pub slots: Vec<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// A building rooms can belong to, by name, placed on a campus plan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Building {
+    pub name: String,
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Building {
+    pub fn distance_to(&self, other: &Building) -> f64 {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Instructor {
+    pub id: u32,
+    pub name: String,
+    /// Slots the instructor cannot teach in, on every day.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unavailable_slots: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Activity {
+    pub id: u32,
+    pub subject: String,
+    pub room: Room,
+    #[serde(default)]
+    pub time_slots: Vec<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_minute: Option<u32>,
+    /// Start of the activity as an actual time, instead of `time_slots` or
+    /// `start_minute`; needs the request's `grid_start`. Set on the output
+    /// to the start of the slots the activity runs in whenever the request
+    /// has `grid_start`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<DateTime<Utc>>,
+    /// End of the last slot of the activity; output only, alongside `start`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_minutes: Option<u32>,
+    pub students_count: u32,
+    #[serde(default)]
+    pub min_capacity: Option<u32>,
+    /// Day index of the week (or longer period) being scheduled. Accepts a
+    /// weekday name as well, counting from `monday` as day 0.
+    #[serde(default, deserialize_with = "deserialize_day")]
+    pub day: u8,
+    #[serde(default)]
+    pub priority: Option<u8>,
+    /// Id of a room to use whenever it is free and fits; not mandatory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preferred_room: Option<u32>,
+    /// Id of the room the activity must take, booked before anything else
+    /// is scheduled; capacity and preferences are not checked. Pins sharing
+    /// a room or an instructor, or landing where the instructor is
+    /// unavailable, fail the run with a `PinConflict`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned_room: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preferred_building: Option<String>,
+    /// Id of an activity of the same day whose building this activity must
+    /// share, e.g. the class right before it; greedy strategy only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub same_building_as: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub preferred_features: Vec<String>,
+    /// Features every assigned room must offer, unlike `preferred_features`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_features: Vec<String>,
+    /// Only wheelchair accessible rooms may be assigned.
+    #[serde(default)]
+    pub requires_accessibility: bool,
+    #[serde(default)]
+    pub allow_split: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub split_rooms: Vec<Room>,
+    /// Windows tried in order when `time_slots` cannot be accommodated.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternative_time_slots: Vec<Vec<u32>>,
+    /// Index into `alternative_time_slots` of the window the activity was
+    /// placed in; absent when it runs in its primary `time_slots`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chosen_alternative: Option<usize>,
+    /// Group of students attending the activity; back-to-back activities of
+    /// a cohort are kept in the same room when possible.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cohort_id: Option<String>,
+    /// Cross-listed sections meeting together share a group id; they are
+    /// scheduled as one activity for all their students and get the same
+    /// room and slots.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<String>,
+    /// Id of the request's instructor teaching the activity.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructor_id: Option<u32>,
+    /// Caller data passed through untouched; never read by the scheduler.
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub metadata: serde_json::Value,
+    /// Deciding factor behind the room the scheduler picked; output only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignment_reason: Option<AssignmentReason>,
+}
+
+pub const DEFAULT_ACTIVITY_PRIORITY: u8 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    const ALL: [Weekday; 7] = [
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+        Weekday::Saturday,
+        Weekday::Sunday,
+    ];
+
+    /// Weekday of a day index, day 0 being a Monday.
+    pub fn of_day(day: u8) -> Weekday {
+        Self::ALL[day as usize % 7]
+    }
+
+    /// Day index of the weekday in the first week.
+    pub fn day(self) -> u8 {
+        self as u8
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DayInput {
+    Index(u8),
+    Weekday(Weekday),
+}
+
+fn deserialize_day<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+    let day = DayInput::deserialize(deserializer)
+        .map_err(|_| serde::de::Error::custom("expected a day index or a weekday name such as \"monday\""))?;
+
+    Ok(match day {
+        DayInput::Index(day) => day,
+        DayInput::Weekday(weekday) => weekday.day(),
+    })
+}
+
+impl Activity {
+    /// Higher values are scheduled first when rooms are contested.
+    pub fn priority(&self) -> u8 {
+        self.priority.unwrap_or(DEFAULT_ACTIVITY_PRIORITY)
+    }
+
+    /// Every room held by the activity, including overflow rooms of a split.
+    pub fn assigned_rooms(&self) -> Vec<Room> {
+        if self.split_rooms.is_empty() {
+            vec![self.room.clone()]
+        } else {
+            self.split_rooms.clone()
+        }
+    }
+
+    /// Moves the activity to its alternative window at `index`.
+    pub fn use_alternative(&mut self, index: usize) {
+        self.time_slots = self.alternative_time_slots[index].clone();
+        self.chosen_alternative = Some(index);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivitiesRequest {
+    #[serde(default)]
+    pub activities: Vec<Activity>,
+    /// Ids of stored activities, appended to `activities` by the HTTP layer
+    /// before scheduling.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub activity_ids: Vec<u32>,
+    #[serde(default)]
+    pub rooms: Vec<Room>,
+    /// Ids of rooms from the service's stored inventory, appended to `rooms`
+    /// by the HTTP layer before scheduling.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub room_ids: Vec<u32>,
+    #[serde(default)]
+    pub blackout_slots: Vec<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub instructors: Vec<Instructor>,
+    /// Coordinates of the buildings rooms refer to, for the
+    /// `building_distance` weight.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub buildings: Vec<Building>,
+    #[serde(default)]
+    pub slot_length_minutes: Option<u32>,
+    /// Start of slot 0 on day 0; slots of `slot_length_minutes` follow it
+    /// and each day starts 24 hours after the previous one. Lets activities
+    /// use `start` and gives actual times back.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grid_start: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub overcapacity_tolerance: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capacity_policy: Option<CapacityPolicy>,
+    #[serde(default)]
+    pub scoring_weights: ScoringWeights,
+    #[serde(default)]
+    pub allow_partial: bool,
+    #[serde(default)]
+    pub strategy: Strategy,
+    /// Reject activities whose slots are not consecutive and ascending.
+    #[serde(default)]
+    pub require_contiguous_slots: bool,
+    #[serde(default)]
+    pub fairness_seed: Option<u64>,
+    /// Seed of the strategies that make random choices (annealing); runs of
+    /// the same request and seed give the same schedule. Defaults to 0.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Tuning of the annealing strategy; ignored by the others.
+    #[serde(default)]
+    pub annealing: AnnealingParams,
+    /// Number of distinct schedules to return in `alternatives`, best
+    /// first; 0 or 1 returns a single schedule.
+    #[serde(default)]
+    pub alternatives: u8,
+    /// Schedule this request revises; changes against it are reported in
+    /// `Schedule::changes`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_schedule: Option<Schedule>,
+    /// Keep each activity in its `previous_schedule` room whenever that room
+    /// still fits and is free, and only move the rest.
+    #[serde(default)]
+    pub minimize_changes: bool,
+    /// URL notified when an async run (`Prefer: respond-async`) finishes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub callback_url: Option<String>,
+}
+
+impl ActivitiesRequest {
+    /// Orders rooms, activities and blackout slots canonically, so requests
+    /// that only differ in ordering schedule (and hash) identically.
+    pub fn normalized(mut self) -> Self {
+        self.rooms.sort_by_key(|r| r.id);
+        self.activities.sort_by_key(|a| a.id);
+        self.blackout_slots.sort_unstable();
+        self.blackout_slots.dedup();
+        self.instructors.sort_by_key(|i| i.id);
+        self
+    }
+
+    pub fn scheduler_options(&self) -> SchedulerOptions {
+        SchedulerOptions {
+            blackout_slots: self.blackout_slots.clone(),
+            instructors: self.instructors.clone(),
+            buildings: self.buildings.clone(),
+            overcapacity_tolerance: self.overcapacity_tolerance,
+            capacity_policy: self.capacity_policy,
+            scoring_weights: self.scoring_weights.clone(),
+            allow_partial: self.allow_partial,
+            strategy: self.strategy,
+            fairness_seed: self.fairness_seed,
+            seed: self.seed,
+            annealing: self.annealing.clone(),
+            alternatives: self.alternatives,
+            previous_schedule: self.previous_schedule.clone(),
+            minimize_changes: self.minimize_changes,
+            slot_grid: self.slot_grid(),
+        }
+    }
+
+    /// The slot grid of `grid_start`, when the slot length is known too.
+    pub fn slot_grid(&self) -> Option<SlotGrid> {
+        match (self.grid_start, self.slot_length_minutes) {
+            (Some(start), Some(slot_length_minutes)) if slot_length_minutes > 0 => Some(SlotGrid { start, slot_length_minutes }),
+            _ => None,
+        }
+    }
+}
+
+/// Maps slot indices of every day to actual times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotGrid {
+    pub start: DateTime<Utc>,
+    pub slot_length_minutes: u32,
+}
+
+/// One what-if scenario of a batch request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchScenario {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(flatten)]
+    pub request: ActivitiesRequest,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeasibilityRequest {
+    pub activity: Activity,
+    pub rooms: Vec<Room>,
+    #[serde(default)]
+    pub overcapacity_tolerance: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capacity_policy: Option<CapacityPolicy>,
+}
+
+impl FeasibilityRequest {
+    pub fn scheduler_options(&self) -> SchedulerOptions {
+        SchedulerOptions {
+            overcapacity_tolerance: self.overcapacity_tolerance,
+            capacity_policy: self.capacity_policy,
+            ..SchedulerOptions::default()
+        }
+    }
+}
+
+/// Maps slot indices to wall-clock times for calendar exports.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarMapping {
+    /// Date of day 0, as `YYYY-MM-DD`; day `n` falls `n` days later.
+    pub start_date: String,
+    /// Start time of slot 0, as `HH:MM`; later slots follow back to back.
+    /// Not needed with `slot_times`.
+    #[serde(default)]
+    pub day_start: String,
+    /// Defaults to the request's `slot_length_minutes`, then 60.
+    #[serde(default)]
+    pub slot_length_minutes: Option<u32>,
+    /// Start time of each slot, as `HH:MM`, for timetables with breaks
+    /// between slots; overrides `day_start`.
+    #[serde(default)]
+    pub slot_times: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarRequest {
+    #[serde(flatten)]
+    pub request: ActivitiesRequest,
+    pub calendar: CalendarMapping,
+}
+
+/// How many seats a room needs for a given headcount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CapacityPolicy {
+    /// Rooms must hold the headcount plus this share of it, e.g. for
+    /// safety regulations.
+    BufferPercent(u32),
+    /// Rooms may take this share more students than their capacity.
+    AllowOverbookingPercent(u32),
+}
+
+impl CapacityPolicy {
+    pub fn admits(&self, capacity: u32, students_count: u32) -> bool {
+        // Neither side overflows: u32 × (100 + u32) stays within u128
+        let (capacity, students_count) = (capacity as u128, students_count as u128);
+
+        match *self {
+            CapacityPolicy::BufferPercent(percent) => capacity * 100 >= students_count * (100 + percent as u128),
+            CapacityPolicy::AllowOverbookingPercent(percent) => capacity * (100 + percent as u128) >= students_count * 100,
+        }
+    }
+}
+
+/// Request-level knobs that tune how `run_scheduler` places activities.
+#[derive(Debug, Clone, Default)]
+pub struct SchedulerOptions {
+    pub blackout_slots: Vec<u32>,
+    /// Instructors referenced by `Activity::instructor_id`; none of them may
+    /// teach two activities at once.
+    pub instructors: Vec<Instructor>,
+    pub buildings: Vec<Building>,
+    /// Seats a room may fall short of the headcount and still be used.
+    pub overcapacity_tolerance: u32,
+    /// Margin between capacity and headcount, applied on top of
+    /// `overcapacity_tolerance`; rooms only need the headcount when unset.
+    pub capacity_policy: Option<CapacityPolicy>,
+    pub scoring_weights: ScoringWeights,
+    /// Schedule what fits instead of failing when some slot has more
+    /// activities than rooms.
+    pub allow_partial: bool,
+    pub strategy: Strategy,
+    /// Shuffles the order of activities that tie on priority and size, so
+    /// different seeds drop different activities; the same seed always
+    /// reproduces the same schedule. Ties keep input order when unset.
+    pub fairness_seed: Option<u64>,
+    /// See `ActivitiesRequest::seed`.
+    pub seed: Option<u64>,
+    pub annealing: AnnealingParams,
+    /// Distinct schedules wanted, see `ActivitiesRequest::alternatives`.
+    pub alternatives: u8,
+    pub previous_schedule: Option<Schedule>,
+    pub minimize_changes: bool,
+    /// Stamps `start` and `end` on the activities of the schedule.
+    pub slot_grid: Option<SlotGrid>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    /// Slot-by-slot placement of the largest, most important activities first.
+    #[default]
+    Greedy,
+    /// Optimal number of scheduled activities; requires the `exact` feature.
+    Exact,
+    /// Greedy order, but earlier room choices are revisited whenever that
+    /// lets the next activity in.
+    Backtracking,
+    /// Integer program with the fewest unscheduled activities, then the
+    /// fewest wasted seats; requires the `ilp` feature.
+    Ilp,
+    /// Simulated annealing from the greedy placement, for instances too
+    /// large for the exact strategies.
+    Annealing,
+}
+
+/// Parameters of the annealing strategy. Each day stops after `iterations`
+/// moves or `time_budget_ms`, whichever comes first; the budget is capped at
+/// ten seconds. Runs that hit the budget may differ even with one seed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnnealingParams {
+    pub iterations: u32,
+    /// Starting temperature, in the objective's unit (seats).
+    pub initial_temperature: f64,
+    /// Factor the temperature is multiplied by after every move, in `(0, 1)`.
+    pub cooling_rate: f64,
+    pub time_budget_ms: u64,
+}
+
+impl Default for AnnealingParams {
+    fn default() -> Self {
+        AnnealingParams {
+            iterations: 50_000,
+            initial_temperature: 20.0,
+            cooling_rate: 0.9995,
+            time_budget_ms: 2_000,
+        }
+    }
+}
+
+/// Weights of the soft preferences used to rank the feasible rooms of an
+/// activity. The defaults only penalize wasted seats, which is the greedy
+/// closest-capacity choice.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoringWeights {
+    pub wasted_seats: f64,
+    pub preferred_building: f64,
+    pub preferred_features: f64,
+    /// Cost of sending a cohort or instructor to a different building than
+    /// their previous activity of the day.
+    pub building_change: f64,
+    /// Cost per unit of distance between the preferred building and the
+    /// room's building.
+    pub building_distance: f64,
+}
+
+impl Default for ScoringWeights {
+    fn default() -> Self {
+        ScoringWeights {
+            wasted_seats: 1.0,
+            preferred_building: 0.0,
+            preferred_features: 0.0,
+            building_change: 0.0,
+            building_distance: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnscheduledReason {
+    /// Some rooms are large enough and equipped, but none admits the
+    /// activity's subject; the other reasons are more specific.
+    NoAvailableRoom,
+    /// No room is large enough for the activity, even when every room is free.
+    NoRoomLargeEnough,
+    /// Rooms are large enough, but none of them is wheelchair accessible.
+    NoAccessibleRoom,
+    /// Rooms are large enough, but none offers every required feature.
+    MissingFeature,
+    /// Suitable rooms exist, but all of them are taken in the activity's slots.
+    AllRoomsBusy,
+    /// Every suitable room is closed in one of the activity's slots.
+    RoomUnavailable,
+    /// The activity requires a slot reserved campus-wide.
+    BlackoutSlot,
+    /// No fitting room stays free for every slot of the activity.
+    RoomContinuity,
+    /// Every fitting room already reached its `max_daily_slots` for the day.
+    RoomDailyCap,
+    /// Fitting rooms are free, but none in the building of the activity's
+    /// `same_building_as` link.
+    BuildingLink,
+    /// The instructor is unavailable in one of the activity's slots.
+    InstructorUnavailable,
+    /// The instructor already teaches another activity in an overlapping slot.
+    InstructorConflict,
+    /// Lost its room to a higher-priority activity, and no other room was
+    /// free for all of its slots.
+    BumpedByPriority,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssignmentReason {
+    /// The request pinned the activity to this room.
+    PinnedRoom,
+    /// No room was free, so lower-priority activities holding this one were
+    /// moved elsewhere or left unscheduled.
+    BumpedLowerPriority,
+    /// Kept from the previous schedule under `minimize_changes`.
+    PreviousRoom,
+    /// The activity's `preferred_room` was free and fit.
+    PreferredRoom,
+    /// The room the activity's cohort vacated in the previous slot.
+    CohortRoom,
+    /// The only free room that fit the activity.
+    OnlyFittingRoom,
+    /// Highest score among the fitting rooms (by default the closest capacity).
+    BestScore,
+    /// No single room fit, so the activity was spread over several.
+    Split,
+    /// Chosen by the exact strategy to maximize the scheduled activities.
+    OptimalSearch,
+    /// Chosen by the backtracking strategy while fitting every higher-ranked activity.
+    BacktrackingSearch,
+    /// Taken from the optimal solution of the ILP strategy.
+    IlpSolution,
+    /// Held by the activity in the best state the annealing strategy reached.
+    AnnealingSearch,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnscheduledActivity {
+    #[serde(flatten)]
+    pub activity: Activity,
+    pub reason: UnscheduledReason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelineSlot {
+    pub day: u8,
+    pub slot: u32,
+    pub activity_id: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoomTimeline {
+    pub room_id: u32,
+    pub room_name: String,
+    pub slots: Vec<TimelineSlot>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaySchedule {
+    pub day: u8,
+    pub weekday: Weekday,
+    pub scheduled_activity_ids: Vec<u32>,
+    pub unscheduled_activity_ids: Vec<u32>,
+}
+
+/// Output of a scheduling run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Schedule {
+    pub scheduled_activities: Vec<Activity>,
+    #[serde(default)]
+    pub unscheduled_activities: Vec<UnscheduledActivity>,
+    #[serde(default)]
+    pub room_timelines: Vec<RoomTimeline>,
+    /// Activity ids of every day in the request, in day order.
+    #[serde(default)]
+    pub days: Vec<DaySchedule>,
+    /// Distinct schedules ranked best first when the request asked for
+    /// alternatives; the first one is also the schedule above.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<RankedSchedule>,
+    /// Differences from the request's `previous_schedule`, when it had one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changes: Option<Vec<ScheduleChange>>,
+}
+
+/// How an activity's outcome differs from the previous schedule.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleChange {
+    pub activity_id: u32,
+    pub kind: ChangeKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_room_id: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub room_id: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// Scheduled in another room than before.
+    Moved,
+    /// Scheduled now, but not in the previous schedule.
+    Added,
+    /// Scheduled before, left unscheduled now.
+    Unscheduled,
+    /// Scheduled before, no longer part of the request.
+    Removed,
+}
+
+/// One of the alternative schedules of a run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankedSchedule {
+    /// 1 for the best schedule.
+    pub rank: usize,
+    /// Sum of the room scores of the scheduled activities; ranks break
+    /// ties on the unscheduled count with it.
+    pub score: f64,
+    #[serde(flatten)]
+    pub schedule: Schedule,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SchedulingProgress {
+    pub day: u8,
+    pub slot: u32,
+    pub scheduled_so_far: usize,
+    pub unscheduled_so_far: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub exp: usize,
+    pub user_id: i64,
+    pub rol: String,
+    #[serde(rename = "type")]
+    pub token_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<Audience>,
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Audience {
+    Single(String),
+    Multiple(Vec<String>),
+}
@@ -0,0 +1,1880 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::models::{Room, Activity, AssignmentReason, Instructor, Schedule, SchedulerOptions, Strategy, SchedulingProgress, UnscheduledActivity, UnscheduledReason};
+use crate::scheduler::error::{PinConflict, PinConflictKind, SchedulerError, SlotShortfall};
+use crate::scheduler::groups::{combine_groups, expand_groups};
+use crate::scheduler::incremental::{keep_previous_rooms, schedule_changes};
+use crate::scheduler::scoring::score_room_after;
+use crate::scheduler::time_slots::stamp_times;
+use crate::scheduler::timeline::{build_room_timelines, group_by_day};
+
+
+/// Lists every `(day, slot)` where more activities overlap than there are
+/// rooms open. Activities with alternative windows may move away, so they
+/// are not counted.
+fn oversubscribed_slots(activities: &[Activity], rooms: &[Room]) -> Vec<SlotShortfall> {
+    let mut activities_per_time_slot: BTreeMap<(u8, u32), usize> = BTreeMap::new();
+
+    for activity in activities.iter().filter(|a| a.alternative_time_slots.is_empty()) {
+        for time_slot in &activity.time_slots {
+            *activities_per_time_slot.entry((activity.day, *time_slot)).or_insert(0) += 1;
+        }
+    }
+
+    let open_rooms = |day: u8, slot: u32| rooms.iter().filter(|r| !r.is_closed(day, slot)).count();
+
+    activities_per_time_slot
+        .into_iter()
+        .filter_map(|((day, slot), count)| {
+            let rooms_count = open_rooms(day, slot);
+
+            (count > rooms_count).then(|| SlotShortfall {
+                day,
+                slot,
+                activities: count,
+                rooms: rooms_count,
+                shortfall: count - rooms_count,
+            })
+        })
+        .collect()
+}
+
+
+fn sort_activities(activities: Vec<Activity>, fairness_seed: Option<u64>) -> Vec<Activity> {
+    let mut sorted_activities = activities;
+
+    match fairness_seed {
+        Some(seed) => sorted_activities.sort_by_key(|a| (a.priority(), a.students_count, fairness_rank(seed, a.id))),
+        None => sorted_activities.sort_by_key(|a| (a.priority(), a.students_count)),
+    }
+
+    sorted_activities
+}
+
+
+/// Seeded position of an activity among its ties (splitmix64), stable
+/// across platforms and releases so a seed always reproduces its outcome.
+fn fairness_rank(seed: u64, activity_id: u32) -> u64 {
+    let mut z = seed ^ (activity_id as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+
+/// Order between rooms that score the same: by name, then by id, so the
+/// choice never depends on the order rooms come in.
+pub(crate) fn room_tie_break(a: &Room, b: &Room) -> std::cmp::Ordering {
+    a.name.cmp(&b.name).then(a.id.cmp(&b.id))
+}
+
+
+/// Picks the room the activity's cohort just vacated when it is a candidate,
+/// otherwise the highest-scoring room; ties go to `room_tie_break`.
+fn get_best_room(
+    activity: &Activity,
+    rooms: Vec<Room>,
+    options: &SchedulerOptions,
+    context: &PlacementContext,
+) -> (Room, AssignmentReason) {
+    if let Some(vacated_room) = context.vacated_room
+        && let Some(room) = rooms.iter().find(|r| r.id == vacated_room)
+    {
+        return (room.clone(), AssignmentReason::CohortRoom);
+    }
+
+    let reason = if rooms.len() == 1 { AssignmentReason::OnlyFittingRoom } else { AssignmentReason::BestScore };
+    let previous_building = context.previous_building.as_deref();
+
+    let room = rooms.into_iter()
+        .map(|r| (score_room_after(&r, activity, &options.scoring_weights, &options.buildings, previous_building).total, r))
+        .min_by(|(a, room_a), (b, room_b)| b.total_cmp(a).then(room_tie_break(room_a, room_b)))
+        .map(|(_, r)| r)
+        .unwrap();
+
+    (room, reason)
+}
+
+
+/// Uses the activity's preferred room when it is among the candidates,
+/// falling back to the best-scoring one otherwise.
+fn select_room(
+    activity: &Activity,
+    rooms: Vec<Room>,
+    options: &SchedulerOptions,
+    context: &PlacementContext,
+) -> (Room, AssignmentReason) {
+    if let Some(preferred_room) = activity.preferred_room
+        && let Some(room) = rooms.iter().find(|r| r.id == preferred_room)
+    {
+        return (room.clone(), AssignmentReason::PreferredRoom);
+    }
+
+    get_best_room(activity, rooms, options, context)
+}
+
+
+/// Who moves between activities: a cohort of students or an instructor.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Attendee {
+    Cohort(String),
+    Instructor(u32),
+}
+
+
+fn attendees(activity: &Activity) -> Vec<Attendee> {
+    activity.cohort_id.iter().cloned().map(Attendee::Cohort)
+        .chain(activity.instructor_id.map(Attendee::Instructor))
+        .collect()
+}
+
+
+struct RecentPlacement {
+    room_id: u32,
+    building: Option<String>,
+    last_slot: u32,
+}
+
+
+/// Latest placed activity of each attendee during the day being scheduled.
+type RecentPlacements = HashMap<Attendee, RecentPlacement>;
+
+
+/// What the attendees' earlier activities imply for picking a room.
+struct PlacementContext {
+    /// Room the activity's cohort leaves in the slot right before it starts.
+    vacated_room: Option<u32>,
+    /// Building of the attendees' latest activity that ended before this one.
+    previous_building: Option<String>,
+}
+
+
+fn placement_context(activity: &Activity, recent_placements: &RecentPlacements) -> PlacementContext {
+    let Some(first_slot) = activity.time_slots.iter().min() else {
+        return PlacementContext { vacated_room: None, previous_building: None };
+    };
+
+    let vacated_room = activity.cohort_id
+        .as_ref()
+        .and_then(|cohort_id| recent_placements.get(&Attendee::Cohort(cohort_id.clone())))
+        .filter(|placement| placement.last_slot + 1 == *first_slot)
+        .map(|placement| placement.room_id);
+
+    let previous_building = attendees(activity)
+        .iter()
+        .filter_map(|attendee| recent_placements.get(attendee))
+        .filter(|placement| placement.last_slot < *first_slot)
+        .max_by_key(|placement| placement.last_slot)
+        .and_then(|placement| placement.building.clone());
+
+    PlacementContext { vacated_room, previous_building }
+}
+
+
+fn record_placement(activity: &Activity, recent_placements: &mut RecentPlacements) {
+    let Some(last_slot) = activity.time_slots.iter().max() else {
+        return;
+    };
+
+    for attendee in attendees(activity) {
+        recent_placements.insert(attendee, RecentPlacement {
+            room_id: activity.room.id,
+            building: activity.room.building.clone(),
+            last_slot: *last_slot,
+        });
+    }
+}
+
+
+/// Whether `capacity` seats hold the activity under the request's capacity
+/// policy and overcapacity tolerance.
+fn has_enough_seats(capacity: u32, activity: &Activity, options: &SchedulerOptions) -> bool {
+    let capacity = capacity.saturating_add(options.overcapacity_tolerance);
+
+    match &options.capacity_policy {
+        Some(policy) => policy.admits(capacity, activity.students_count),
+        None => capacity >= activity.students_count,
+    }
+}
+
+
+fn meets_min_capacity(room: &Room, activity: &Activity) -> bool {
+    activity.min_capacity.is_none_or(|min_capacity| room.capacity >= min_capacity)
+}
+
+
+fn admits_subject(room: &Room, activity: &Activity) -> bool {
+    room.allowed_subjects
+        .as_ref()
+        .is_none_or(|subjects| subjects.contains(&activity.subject))
+}
+
+
+fn has_required_features(room: &Room, activity: &Activity) -> bool {
+    activity.required_features
+        .iter()
+        .all(|feature| room.features.contains(feature))
+}
+
+
+fn is_accessible_enough(room: &Room, activity: &Activity) -> bool {
+    room.wheelchair_accessible || !activity.requires_accessibility
+}
+
+
+fn is_open(room: &Room, activity: &Activity) -> bool {
+    !activity.time_slots.iter().any(|slot| room.is_closed(activity.day, *slot))
+}
+
+
+/// Activities tied to each activity through `same_building_as`, in either
+/// direction.
+type BuildingLinks = HashMap<u32, Vec<u32>>;
+
+
+fn building_links(activities: &[Activity]) -> BuildingLinks {
+    let mut links = BuildingLinks::new();
+
+    for activity in activities {
+        if let Some(linked_id) = activity.same_building_as {
+            links.entry(activity.id).or_default().push(linked_id);
+            links.entry(linked_id).or_default().push(activity.id);
+        }
+    }
+
+    links
+}
+
+
+/// Building the activity must be in because a linked activity already holds
+/// a room there; `None` when no linked activity is placed yet.
+fn linked_building(activity: &Activity, links: &BuildingLinks, rooms: &[Room], occupancy: &RoomOccupancy) -> Option<Option<String>> {
+    let linked = links.get(&activity.id)?;
+
+    occupancy
+        .iter()
+        .find(|(_, activity_id)| linked.contains(activity_id))
+        .and_then(|((room_id, _), _)| rooms.iter().find(|r| r.id == *room_id))
+        .map(|room| room.building.clone())
+}
+
+
+fn in_building(room: &Room, building: Option<&Option<String>>) -> bool {
+    building.is_none_or(|building| room.building == *building)
+}
+
+
+/// Whether the room can hold the activity on its own, regardless of occupancy.
+pub fn room_fits_activity(room: &Room, activity: &Activity, options: &SchedulerOptions) -> bool {
+    has_enough_seats(room.capacity, activity, options)
+        && meets_min_capacity(room, activity)
+        && admits_subject(room, activity)
+        && is_accessible_enough(room, activity)
+        && has_required_features(room, activity)
+        && is_open(room, activity)
+}
+
+
+/// Narrows a `NoAvailableRoom` outcome down to why no room could be used:
+/// none is large enough, none of those is accessible when it must be or
+/// has the required features, the
+/// suitable ones are closed, or they were all taken.
+fn explain_no_room(activity: &Activity, rooms: &[Room], options: &SchedulerOptions) -> UnscheduledReason {
+    let large_enough: Vec<&Room> = rooms
+        .iter()
+        .filter(|r| has_enough_seats(r.capacity, activity, options) && meets_min_capacity(r, activity))
+        .collect();
+
+    if large_enough.is_empty() {
+        return UnscheduledReason::NoRoomLargeEnough;
+    }
+
+    let accessible: Vec<&Room> = large_enough.into_iter().filter(|r| is_accessible_enough(r, activity)).collect();
+
+    if accessible.is_empty() {
+        return UnscheduledReason::NoAccessibleRoom;
+    }
+
+    let equipped: Vec<&Room> = accessible.into_iter().filter(|r| has_required_features(r, activity)).collect();
+
+    if equipped.is_empty() {
+        return UnscheduledReason::MissingFeature;
+    }
+
+    let suitable: Vec<&Room> = equipped.into_iter().filter(|r| admits_subject(r, activity)).collect();
+
+    if suitable.is_empty() {
+        UnscheduledReason::NoAvailableRoom
+    } else if suitable.into_iter().any(|r| is_open(r, activity)) {
+        UnscheduledReason::AllRoomsBusy
+    } else {
+        UnscheduledReason::RoomUnavailable
+    }
+}
+
+
+/// Rooms that could host the activity on their own, ignoring every other activity.
+pub fn feasible_rooms(activity: &Activity, rooms: &[Room], options: &SchedulerOptions) -> Vec<Room> {
+    rooms
+        .iter()
+        .filter(|r| room_fits_activity(r, activity, options))
+        .cloned()
+        .collect()
+}
+
+
+/// `room_fits_activity` checks per day above which they run on several threads.
+const PARALLEL_FIT_CHECKS: usize = 20_000;
+
+
+/// Threads the fit checks of a day with this many activities and rooms are
+/// spread over.
+fn fit_check_workers(activities: usize, rooms: usize) -> usize {
+    if activities.saturating_mul(rooms) < PARALLEL_FIT_CHECKS {
+        return 1;
+    }
+
+    std::thread::available_parallelism().map_or(1, |workers| workers.get())
+}
+
+
+/// Ids of the rooms each activity fits on its own in its current slots,
+/// computed on `workers` threads. Activities are independent here, so the
+/// result is the same for any number of workers.
+fn fitting_room_ids(activities: &[Activity], rooms: &[Room], options: &SchedulerOptions, workers: usize) -> HashMap<u32, HashSet<u32>> {
+    let fits = |activity: &Activity| {
+        let room_ids = rooms.iter().filter(|r| room_fits_activity(r, activity, options)).map(|r| r.id).collect();
+        (activity.id, room_ids)
+    };
+
+    if workers <= 1 || activities.len() < 2 {
+        return activities.iter().map(fits).collect();
+    }
+
+    let fits = &fits;
+    let chunk_size = activities.len().div_ceil(workers);
+
+    std::thread::scope(|scope| {
+        let chunks: Vec<_> = activities
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(fits).collect::<Vec<_>>()))
+            .collect();
+
+        chunks.into_iter().flat_map(|chunk| chunk.join().unwrap()).collect()
+    })
+}
+
+
+/// Picks the largest free rooms until their combined capacity covers the activity.
+fn get_split_rooms(activity: &Activity, rooms: Vec<Room>, options: &SchedulerOptions) -> Option<Vec<Room>> {
+    let mut candidates = rooms;
+    candidates.sort_by(|a, b| b.capacity.cmp(&a.capacity).then(room_tie_break(a, b)));
+
+    let mut split_rooms = Vec::new();
+    let mut covered_capacity = 0;
+
+    for room in candidates {
+        if has_enough_seats(covered_capacity, activity, options) {
+            break;
+        }
+        if split_rooms.iter().any(|chosen| shares_parts(chosen, &room)) {
+            continue;
+        }
+        covered_capacity = covered_capacity.saturating_add(room.capacity);
+        split_rooms.push(room);
+    }
+
+    if !has_enough_seats(covered_capacity, activity, options) || split_rooms.len() < 2 {
+        return None;
+    }
+
+    Some(split_rooms)
+}
+
+fn pop_activity(activities: &mut Vec<Activity>) -> Result<Activity, SchedulerError> {
+    activities
+        .pop()
+        .ok_or_else(|| SchedulerError::Internal("Failed to pop activity from the list.".to_string()))
+}
+
+
+/// Maps `(room_id, time_slot)` to the id of the activity holding the room.
+pub(crate) type RoomOccupancy = HashMap<(u32, u32), u32>;
+
+
+fn keeps_room_continuity(room: &Room, activity: &Activity, occupancy: &RoomOccupancy) -> bool {
+    activity.time_slots
+        .iter()
+        .all(|slot| occupancy.get(&(room.id, *slot)).is_none_or(|id| *id == activity.id))
+}
+
+
+/// Whether booking the activity keeps the room within its `max_daily_slots`.
+/// The occupancy map only spans one day, so every entry counts towards the cap.
+fn within_daily_cap(room: &Room, activity: &Activity, occupancy: &RoomOccupancy) -> bool {
+    room.max_daily_slots.is_none_or(|cap| {
+        let used = occupancy.keys().filter(|(room_id, _)| *room_id == room.id).count();
+        used + activity.time_slots.len() <= cap as usize
+    })
+}
+
+
+/// Maps `(instructor_id, time_slot)` to the id of the activity they teach.
+pub(crate) type InstructorOccupancy = HashMap<(u32, u32), u32>;
+
+
+/// Why the activity's instructor cannot teach it in its current slots, if at all.
+pub(crate) fn instructor_conflict(activity: &Activity, options: &SchedulerOptions, occupancy: &InstructorOccupancy) -> Option<UnscheduledReason> {
+    let instructor = options.instructors.iter().find(|i| Some(i.id) == activity.instructor_id)?;
+
+    if activity.time_slots.iter().any(|slot| instructor.unavailable_slots.contains(slot)) {
+        return Some(UnscheduledReason::InstructorUnavailable);
+    }
+
+    activity.time_slots
+        .iter()
+        .any(|slot| occupancy.get(&(instructor.id, *slot)).is_some_and(|id| *id != activity.id))
+        .then_some(UnscheduledReason::InstructorConflict)
+}
+
+
+pub(crate) fn book_instructor(activity: &Activity, occupancy: &mut InstructorOccupancy) {
+    if let Some(instructor_id) = activity.instructor_id {
+        for slot in &activity.time_slots {
+            occupancy.insert((instructor_id, *slot), activity.id);
+        }
+    }
+}
+
+
+/// Frees one of the `candidates` for the activity by evicting the lower
+/// priority activities holding it in the activity's slots, picking the room
+/// whose evicted activities rank lowest and are fewest students. Split
+/// activities are never evicted. Returns the freed room and the evicted
+/// activities, no longer booked anywhere.
+fn bump_lower_priority(
+    activity: &Activity,
+    candidates: Vec<Room>,
+    room_occupancy: &mut RoomOccupancy,
+    instructor_occupancy: &mut InstructorOccupancy,
+    scheduled_activities: &mut Vec<Activity>,
+) -> Option<(Room, Vec<Activity>)> {
+    let mut best: Option<((u8, u32), Room, Vec<u32>)> = None;
+
+    for room in candidates {
+        let mut occupants: Vec<u32> = activity.time_slots
+            .iter()
+            .filter_map(|slot| room_occupancy.get(&(room.id, *slot)).copied())
+            .collect();
+        occupants.sort_unstable();
+        occupants.dedup();
+
+        let evicted: Option<Vec<&Activity>> = occupants
+            .iter()
+            .map(|id| scheduled_activities.iter().find(|a| a.id == *id))
+            .collect();
+
+        let Some(evicted) = evicted else {
+            continue;
+        };
+
+        if evicted.is_empty() || evicted.iter().any(|a| a.priority() >= activity.priority() || !a.split_rooms.is_empty()) {
+            continue;
+        }
+
+        let fits_cap = room.max_daily_slots.is_none_or(|cap| {
+            let used = room_occupancy.keys().filter(|(room_id, _)| *room_id == room.id).count();
+            let freed: usize = evicted.iter().map(|a| a.time_slots.len()).sum();
+            used - freed + activity.time_slots.len() <= cap as usize
+        });
+
+        if !fits_cap {
+            continue;
+        }
+
+        let cost = (
+            evicted.iter().map(|a| a.priority()).max().unwrap_or_default(),
+            evicted.iter().map(|a| a.students_count).sum(),
+        );
+
+        let is_better = best.as_ref().is_none_or(|(best_cost, best_room, _)| {
+            cost.cmp(best_cost).then(room_tie_break(&room, best_room)).is_lt()
+        });
+
+        if is_better {
+            best = Some((cost, room, occupants));
+        }
+    }
+
+    let (_, room, occupants) = best?;
+
+    room_occupancy.retain(|_, id| !occupants.contains(id));
+    instructor_occupancy.retain(|_, id| !occupants.contains(id));
+
+    let (evicted, kept): (Vec<Activity>, Vec<Activity>) = std::mem::take(scheduled_activities)
+        .into_iter()
+        .partition(|a| occupants.contains(&a.id));
+    *scheduled_activities = kept;
+
+    Some((room, evicted))
+}
+
+
+/// Rooms and instructors booked by the pinned activities of a day, which
+/// every strategy starts from.
+#[derive(Default)]
+pub(crate) struct Reservations {
+    pub(crate) rooms: RoomOccupancy,
+    pub(crate) instructors: InstructorOccupancy,
+}
+
+impl Reservations {
+    fn book(&mut self, activity: &Activity, rooms: &[Room]) {
+        occupy_room(&activity.room, activity, &mut self.rooms, rooms);
+        book_instructor(activity, &mut self.instructors);
+    }
+
+    /// Whether a pinned activity holds the room in one of the activity's slots.
+    pub(crate) fn blocks(&self, room: &Room, activity: &Activity) -> bool {
+        !keeps_room_continuity(room, activity, &self.rooms)
+    }
+
+    /// Slots of the room's daily cap already used by pinned activities.
+    pub(crate) fn booked_slots(&self, room: &Room) -> usize {
+        self.rooms.keys().filter(|(room_id, _)| *room_id == room.id).count()
+    }
+}
+
+
+/// Every `(day, slot, room)` that more than one activity is pinned to, then
+/// every `(day, slot, instructor)` that more than one pinned activity needs
+/// or that the instructor is unavailable in. Rooms combining others collide
+/// through their parts, which are reported instead.
+fn pin_conflicts(activities: &[Activity], rooms: &[Room], instructors: &[Instructor]) -> Vec<PinConflict> {
+    let mut pins: BTreeMap<(u8, u32, u32), Vec<u32>> = BTreeMap::new();
+    let mut teaching: BTreeMap<(u8, u32, u32), Vec<u32>> = BTreeMap::new();
+
+    for activity in activities {
+        let Some(room) = activity.pinned_room.and_then(|room_id| rooms.iter().find(|r| r.id == room_id)) else {
+            continue;
+        };
+
+        for part in room_parts(room) {
+            for slot in &activity.time_slots {
+                pins.entry((activity.day, *slot, part)).or_default().push(activity.id);
+            }
+        }
+
+        if let Some(instructor_id) = activity.instructor_id {
+            for slot in &activity.time_slots {
+                teaching.entry((activity.day, *slot, instructor_id)).or_default().push(activity.id);
+            }
+        }
+    }
+
+    let room_conflicts = pins.into_iter()
+        .filter(|(_, activity_ids)| activity_ids.len() > 1)
+        .map(|((day, slot, room_id), activity_ids)| PinConflict {
+            day,
+            slot,
+            kind: PinConflictKind::Room,
+            room_id: Some(room_id),
+            instructor_id: None,
+            activity_ids,
+        });
+
+    let instructor_conflicts = teaching.into_iter().flat_map(|((day, slot, instructor_id), activity_ids)| {
+        let unavailable = instructors
+            .iter()
+            .any(|instructor| instructor.id == instructor_id && instructor.unavailable_slots.contains(&slot));
+
+        let kind = match (activity_ids.len() > 1, unavailable) {
+            (true, _) => Some(PinConflictKind::Instructor),
+            (false, true) => Some(PinConflictKind::InstructorUnavailable),
+            (false, false) => None,
+        };
+
+        kind.map(|kind| PinConflict { day, slot, kind, room_id: None, instructor_id: Some(instructor_id), activity_ids })
+    });
+
+    room_conflicts.chain(instructor_conflicts).collect()
+}
+
+
+/// Books the pinned activities of a day in their rooms, returning them with
+/// the reservations the rest of the day is scheduled around.
+fn place_pinned_activities(pinned: Vec<Activity>, rooms: &[Room]) -> Result<(Vec<Activity>, Reservations), SchedulerError> {
+    let mut reservations = Reservations::default();
+    let mut placed = Vec::new();
+
+    for mut activity in pinned {
+        let room_id = activity.pinned_room.unwrap_or_default();
+        let room = rooms.iter().find(|room| room.id == room_id).ok_or_else(|| {
+            SchedulerError::InvalidInput(format!("Activity {} is pinned to unknown room {}.", activity.id, room_id))
+        })?;
+
+        tracing::debug!("Assigning pinned activity {} to room {}.", activity.subject, room.name);
+        activity.room = room.clone();
+        activity.assignment_reason = Some(AssignmentReason::PinnedRoom);
+        reservations.book(&activity, rooms);
+        placed.push(activity);
+    }
+
+    Ok((placed, reservations))
+}
+
+
+/// Rooms made of the room's parts: its own id, or the rooms it combines.
+fn room_parts(room: &Room) -> Vec<u32> {
+    if room.combines.is_empty() { vec![room.id] } else { room.combines.clone() }
+}
+
+
+/// Whether using one of the rooms blocks the other, because they share a
+/// part (or are the same room).
+pub(crate) fn shares_parts(a: &Room, b: &Room) -> bool {
+    let parts_of_b = room_parts(b);
+    room_parts(a).iter().any(|part| parts_of_b.contains(part))
+}
+
+
+/// Books the room in the activity's slots, along with every room sharing
+/// one of its parts: a combined room blocks its parts and a part blocks the
+/// combined rooms it belongs to.
+fn occupy_room(room: &Room, activity: &Activity, occupancy: &mut RoomOccupancy, rooms: &[Room]) {
+    for blocked in rooms.iter().filter(|r| r.id == room.id || shares_parts(r, room)) {
+        for slot in &activity.time_slots {
+            occupancy.insert((blocked.id, *slot), activity.id);
+        }
+    }
+}
+
+
+fn assert_room_continuity(scheduled_activities: &[Activity], occupancy: &RoomOccupancy) {
+    for activity in scheduled_activities {
+        debug_assert!(
+            activity.time_slots.iter().all(|slot| occupancy.get(&(activity.room.id, *slot)) == Some(&activity.id)),
+            "activity {} does not hold room {} across all of its time slots",
+            activity.id, activity.room.id
+        );
+    }
+}
+
+
+fn group_activities_by_day(activities: Vec<Activity>) -> BTreeMap<u8, Vec<Activity>> {
+    let mut activities_by_day: BTreeMap<u8, Vec<Activity>> = BTreeMap::new();
+
+    for activity in activities {
+        activities_by_day.entry(activity.day).or_default().push(activity);
+    }
+
+    activities_by_day
+}
+
+
+fn touches_blackout(time_slots: &[u32], blackout_slots: &[u32]) -> bool {
+    time_slots.iter().any(|slot| blackout_slots.contains(slot))
+}
+
+
+/// Moves activities whose primary window hits a blackout slot to their first
+/// clean alternative, then separates the ones left without any.
+fn split_blackout_activities(activities: Vec<Activity>, blackout_slots: &[u32]) -> (Vec<Activity>, Vec<Activity>) {
+    activities
+        .into_iter()
+        .map(|mut activity| {
+            if touches_blackout(&activity.time_slots, blackout_slots)
+                && let Some(index) = activity.alternative_time_slots
+                    .iter()
+                    .position(|window| !touches_blackout(window, blackout_slots))
+            {
+                activity.use_alternative(index);
+            }
+            activity
+        })
+        .partition(|a| !touches_blackout(&a.time_slots, blackout_slots))
+}
+
+
+/// Tries the alternative windows of an activity that could not be placed in
+/// its current one, booking the first window where a fitting room is free
+/// across every slot. Rooms are checked against the occupancy map alone, so
+/// the window may lie before or after the slot being swept.
+fn place_in_alternative_window(
+    activity: &mut Activity,
+    rooms: &[Room],
+    building: Option<&Option<String>>,
+    occupancy: &mut RoomOccupancy,
+    recent_placements: &RecentPlacements,
+    instructor_occupancy: &mut InstructorOccupancy,
+    options: &SchedulerOptions,
+) -> bool {
+    let first_alternative = activity.chosen_alternative.map_or(0, |index| index + 1);
+
+    for index in first_alternative..activity.alternative_time_slots.len() {
+        let window = &activity.alternative_time_slots[index];
+
+        if touches_blackout(window, &options.blackout_slots) {
+            continue;
+        }
+
+        let mut candidate = activity.clone();
+        candidate.use_alternative(index);
+
+        if instructor_conflict(&candidate, options, instructor_occupancy).is_some() {
+            continue;
+        }
+
+        let continuous_rooms: Vec<Room> = rooms
+            .iter()
+            .filter(|r| room_fits_activity(r, &candidate, options) && in_building(r, building))
+            .filter(|r| keeps_room_continuity(r, &candidate, occupancy))
+            .filter(|r| within_daily_cap(r, &candidate, occupancy))
+            .cloned()
+            .collect();
+
+        if continuous_rooms.is_empty() {
+            continue;
+        }
+
+        let (best_room, reason) = select_room(&candidate, continuous_rooms, options, &placement_context(&candidate, recent_placements));
+        occupy_room(&best_room, &candidate, occupancy, rooms);
+        book_instructor(&candidate, instructor_occupancy);
+
+        candidate.room = best_room;
+        candidate.assignment_reason = Some(reason);
+        *activity = candidate;
+        return true;
+    }
+
+    false
+}
+
+
+pub fn run_scheduler(activities: Vec<Activity>, rooms: Vec<Room>, options: &SchedulerOptions) -> Result<Schedule, SchedulerError> {
+    run_scheduler_with_progress(activities, rooms, options, &mut |_| {})
+}
+
+
+/// Same as `run_scheduler`, reporting the running totals after every processed slot.
+pub fn run_scheduler_with_progress(
+    mut activities: Vec<Activity>,
+    rooms: Vec<Room>,
+    options: &SchedulerOptions,
+    on_progress: &mut dyn FnMut(SchedulingProgress),
+) -> Result<Schedule, SchedulerError> {
+    let span = tracing::info_span!(
+        "run_scheduler",
+        activities = activities.len(),
+        rooms = rooms.len(),
+        strategy = ?options.strategy,
+    );
+    let _entered = span.enter();
+
+    tracing::info!("Running the scheduling algorithm");
+
+    if options.strategy == Strategy::Exact && !cfg!(feature = "exact") {
+        return Err(SchedulerError::InvalidInput(
+            "The exact strategy is not enabled in this build.".to_string()
+        ));
+    }
+
+    if options.strategy == Strategy::Ilp && !cfg!(feature = "ilp") {
+        return Err(SchedulerError::InvalidInput(
+            "The ilp strategy is not enabled in this build.".to_string()
+        ));
+    }
+
+    if options.strategy != Strategy::Greedy && activities.iter().any(|a| a.same_building_as.is_some()) {
+        return Err(SchedulerError::InvalidInput(
+            "same_building_as is only supported by the greedy strategy.".to_string()
+        ));
+    }
+
+    if options.strategy != Strategy::Greedy && rooms.iter().any(|r| !r.combines.is_empty()) {
+        return Err(SchedulerError::InvalidInput(
+            "Combined rooms are only supported by the greedy strategy.".to_string()
+        ));
+    }
+
+    let alternative_inputs = (options.alternatives > 1).then(|| (activities.clone(), rooms.clone()));
+
+    let (combined, group_members) = combine_groups(activities)?;
+    activities = combined;
+
+    let mut unscheduled_activities: Vec<UnscheduledActivity> = Vec::new();
+
+    let (schedulable, blacked_out) = split_blackout_activities(activities, &options.blackout_slots);
+    activities = schedulable;
+
+    for activity in blacked_out {
+        tracing::debug!("Activity {} requires a blackout time slot.", activity.subject);
+        unscheduled_activities.push(UnscheduledActivity { activity, reason: UnscheduledReason::BlackoutSlot });
+    }
+
+    let conflicts = pin_conflicts(&activities, &rooms, &options.instructors);
+
+    if !conflicts.is_empty() {
+        tracing::debug!("Some pinned activities collide.");
+        return Err(SchedulerError::PinConflict(conflicts));
+    }
+
+    // Sort activities by priority and then by number of students (asending),
+    // so the most important and largest activities are popped first in each slot
+    activities = sort_activities(activities, options.fairness_seed);
+
+    let kept: HashSet<u32> = match &options.previous_schedule {
+        Some(previous) if options.minimize_changes => keep_previous_rooms(&mut activities, &rooms, previous, options),
+        _ => HashSet::new(),
+    };
+
+    let shortfalls = oversubscribed_slots(&activities, &rooms);
+
+    if !shortfalls.is_empty() && !options.allow_partial {
+        tracing::debug!("Not enough rooms to schedule all activities.");
+        return Err(SchedulerError::NotEnoughRooms(shortfalls));
+    }
+
+    let mut scheduled_activities: Vec<Activity> = Vec::new();
+
+    for (day, day_activities) in group_activities_by_day(activities) {
+        let (pinned, day_activities): (Vec<Activity>, Vec<Activity>) = day_activities
+            .into_iter()
+            .partition(|activity| activity.pinned_room.is_some());
+
+        let (pinned, reservations) = place_pinned_activities(pinned, &rooms)?;
+        scheduled_activities.extend(pinned);
+
+        let scheduled_before = scheduled_activities.len();
+        let unscheduled_before = unscheduled_activities.len();
+        let last_slot = day_activities.iter().flat_map(|activity| activity.time_slots.iter().copied()).max().unwrap_or(0);
+
+        let mut on_slot_done = |slot, scheduled, unscheduled| {
+            on_progress(SchedulingProgress {
+                day,
+                slot,
+                scheduled_so_far: scheduled_before + scheduled,
+                unscheduled_so_far: unscheduled_before + unscheduled,
+            })
+        };
+
+        let (day_scheduled, day_unscheduled) = match options.strategy {
+            Strategy::Greedy => {
+                let workers = fit_check_workers(day_activities.len(), rooms.len());
+                schedule_day(day_activities, rooms.clone(), options, &reservations, workers, &mut on_slot_done)?
+            }
+            Strategy::Backtracking => crate::scheduler::backtracking::schedule_day_backtracking(day_activities, &rooms, options, &reservations),
+            Strategy::Annealing => crate::scheduler::annealing::schedule_day_annealing(day_activities, &rooms, options, &reservations),
+            #[cfg(feature = "exact")]
+            Strategy::Exact => crate::scheduler::exact::schedule_day_exact(day_activities, &rooms, options, &reservations),
+            #[cfg(not(feature = "exact"))]
+            Strategy::Exact => unreachable!("rejected above"),
+            #[cfg(feature = "ilp")]
+            Strategy::Ilp => crate::scheduler::ilp::schedule_day_ilp(day_activities, &rooms, options, &reservations),
+            #[cfg(not(feature = "ilp"))]
+            Strategy::Ilp => unreachable!("rejected above"),
+        };
+
+        // Only the greedy pass goes slot by slot; the others report the day as a whole
+        if options.strategy != Strategy::Greedy {
+            on_slot_done(last_slot, day_scheduled.len(), day_unscheduled.len());
+        }
+
+        scheduled_activities.extend(day_scheduled);
+        unscheduled_activities.extend(day_unscheduled.into_iter().map(|mut unscheduled| {
+            if unscheduled.reason == UnscheduledReason::NoAvailableRoom {
+                unscheduled.reason = explain_no_room(&unscheduled.activity, &rooms, options);
+            }
+            unscheduled
+        }));
+    }
+
+    // Previous rooms were kept through pins the request never asked for
+    for activity in scheduled_activities.iter_mut().filter(|activity| kept.contains(&activity.id)) {
+        activity.pinned_room = None;
+        activity.assignment_reason = Some(AssignmentReason::PreviousRoom);
+    }
+
+    let (mut scheduled_activities, mut unscheduled_activities) =
+        expand_groups(scheduled_activities, unscheduled_activities, group_members);
+
+    let room_timelines = build_room_timelines(
+        &rooms,
+        &scheduled_activities,
+        scheduled_activities.iter().chain(unscheduled_activities.iter().map(|u| &u.activity)),
+    );
+
+    if let Some(grid) = &options.slot_grid {
+        stamp_times(
+            scheduled_activities.iter_mut().chain(unscheduled_activities.iter_mut().map(|u| &mut u.activity)),
+            grid,
+        )?;
+    }
+
+    let days = group_by_day(&scheduled_activities, &unscheduled_activities);
+
+    let mut schedule = Schedule {
+        scheduled_activities,
+        unscheduled_activities,
+        room_timelines,
+        days,
+        alternatives: Vec::new(),
+        changes: None,
+    };
+
+    schedule.changes = options.previous_schedule.as_ref().map(|previous| schedule_changes(previous, &schedule));
+
+    match alternative_inputs {
+        Some((activities, rooms)) => crate::scheduler::alternatives::with_alternatives(activities, rooms, options, schedule),
+        None => Ok(schedule),
+    }
+}
+
+
+/// Runs the slot-by-slot greedy pass over the activities of a single day.
+/// Every day starts with all the rooms free but those of pinned activities.
+/// The rooms each activity fits are worked out up front on `workers`
+/// threads; the slots themselves are swept in order.
+fn schedule_day(
+    mut activities: Vec<Activity>,
+    rooms: Vec<Room>,
+    options: &SchedulerOptions,
+    reservations: &Reservations,
+    workers: usize,
+    on_slot_done: &mut dyn FnMut(u32, usize, usize),
+) -> Result<(Vec<Activity>, Vec<UnscheduledActivity>), SchedulerError> {
+    let mut scheduled_activities: Vec<Activity> = Vec::new();
+    let mut unscheduled_activities: Vec<UnscheduledActivity> = Vec::new();
+
+    // Pinned activities hold their rooms and instructors from the start
+    let mut room_occupancy = reservations.rooms.clone();
+    let mut recent_placements = RecentPlacements::new();
+    let mut instructor_occupancy = reservations.instructors.clone();
+
+    let building_links = building_links(&activities);
+
+    let fitting_rooms_of = fitting_room_ids(&activities, &rooms, options, workers);
+    let fits = |room: &Room, activity: &Activity| {
+        fitting_rooms_of.get(&activity.id).map_or_else(|| room_fits_activity(room, activity, options), |room_ids| room_ids.contains(&room.id))
+    };
+
+    let mut current_time_slot = 0;
+
+    while !activities.is_empty() {
+        let mut activities_start_in_time_slot: Vec<Activity> = activities
+            .iter()
+            .filter(|a| a.time_slots[0] == current_time_slot)
+            .cloned()
+            .collect();
+
+        if activities_start_in_time_slot.is_empty() {
+            // Jump straight to the next slot where something starts
+            current_time_slot = activities
+                .iter()
+                .map(|a| a.time_slots[0])
+                .filter(|slot| *slot > current_time_slot)
+                .min()
+                .unwrap_or(current_time_slot + 1);
+            continue;
+        }
+
+        while !activities_start_in_time_slot.is_empty() {
+            let mut activity = pop_activity(&mut activities_start_in_time_slot)?;
+            let building = linked_building(&activity, &building_links, &rooms, &room_occupancy);
+
+            if let Some(reason) = instructor_conflict(&activity, options, &instructor_occupancy) {
+                if place_in_alternative_window(&mut activity, &rooms, building.as_ref(), &mut room_occupancy, &recent_placements, &mut instructor_occupancy, options) {
+                    tracing::debug!(
+                        "Moving activity {} to alternative time slots {:?} to avoid an instructor clash.",
+                        activity.subject, activity.time_slots
+                    );
+                    activities.retain(|a| a.id != activity.id);
+                    record_placement(&activity, &mut recent_placements);
+                    scheduled_activities.push(activity);
+                    continue;
+                }
+
+                tracing::debug!("The instructor of activity {} cannot teach it in its time slots.", activity.subject);
+                activities.retain(|a| a.id != activity.id);
+                unscheduled_activities.push(UnscheduledActivity { activity, reason });
+                continue;
+            }
+
+            // Rooms are free per slot, so a gap in another activity's slots frees its room
+            let free_rooms: Vec<Room> = rooms
+                .iter()
+                .filter(|r| !room_occupancy.contains_key(&(r.id, current_time_slot)))
+                .cloned()
+                .collect();
+
+            let unlinked_rooms: Vec<Room> = free_rooms
+                .iter()
+                .filter(|r| fits(r, &activity))
+                .filter(|r| !reservations.blocks(r, &activity))
+                .cloned()
+                .collect();
+
+            let fitting_rooms: Vec<Room> = unlinked_rooms
+                .iter()
+                .filter(|r| in_building(r, building.as_ref()))
+                .cloned()
+                .collect();
+
+            let outside_building = fitting_rooms.is_empty() && !unlinked_rooms.is_empty();
+
+            let reached_daily_cap = !fitting_rooms.is_empty();
+
+            let available_rooms: Vec<Room> = fitting_rooms
+                .into_iter()
+                .filter(|r| within_daily_cap(r, &activity, &room_occupancy))
+                .collect();
+
+            if available_rooms.is_empty() && activity.allow_split {
+                let continuous_free_rooms: Vec<Room> = free_rooms
+                    .iter()
+                    .filter(|r| meets_min_capacity(r, &activity) && admits_subject(r, &activity))
+                    .filter(|r| is_accessible_enough(r, &activity) && has_required_features(r, &activity))
+                    .filter(|r| is_open(r, &activity))
+                    .filter(|r| in_building(r, building.as_ref()))
+                    .filter(|r| keeps_room_continuity(r, &activity, &room_occupancy))
+                    .filter(|r| within_daily_cap(r, &activity, &room_occupancy))
+                    .cloned()
+                    .collect();
+
+                if let Some(split_rooms) = get_split_rooms(&activity, continuous_free_rooms, options) {
+                    tracing::debug!(
+                        "Splitting activity {} across {} rooms in time slot {}.",
+                        activity.subject, split_rooms.len(), current_time_slot
+                    );
+
+                    for room in &split_rooms {
+                        occupy_room(room, &activity, &mut room_occupancy, &rooms);
+                    }
+                    book_instructor(&activity, &mut instructor_occupancy);
+
+                    activity.room = split_rooms[0].clone();
+                    activity.split_rooms = split_rooms;
+                    activity.assignment_reason = Some(AssignmentReason::Split);
+                    activities.retain(|a| a.id != activity.id);
+                    record_placement(&activity, &mut recent_placements);
+                    scheduled_activities.push(activity);
+                    continue;
+                }
+            }
+
+            if (available_rooms.is_empty() || !available_rooms.iter().any(|r| keeps_room_continuity(r, &activity, &room_occupancy)))
+                && place_in_alternative_window(&mut activity, &rooms, building.as_ref(), &mut room_occupancy, &recent_placements, &mut instructor_occupancy, options)
+            {
+                tracing::debug!(
+                    "Moving activity {} to alternative time slots {:?} in room {}.",
+                    activity.subject, activity.time_slots, activity.room.name
+                );
+                activities.retain(|a| a.id != activity.id);
+                record_placement(&activity, &mut recent_placements);
+                scheduled_activities.push(activity);
+                continue;
+            }
+
+            if available_rooms.is_empty() || !available_rooms.iter().any(|r| keeps_room_continuity(r, &activity, &room_occupancy)) {
+                let candidates: Vec<Room> = rooms
+                    .iter()
+                    .filter(|r| fits(r, &activity) && !reservations.blocks(r, &activity))
+                    .filter(|r| in_building(r, building.as_ref()))
+                    .cloned()
+                    .collect();
+
+                if let Some((room, evicted)) = bump_lower_priority(&activity, candidates, &mut room_occupancy, &mut instructor_occupancy, &mut scheduled_activities) {
+                    tracing::debug!(
+                        "Assigning activity {} to room {} in time slot {}, bumping {} lower-priority activities.",
+                        activity.subject, room.name, current_time_slot, evicted.len()
+                    );
+
+                    occupy_room(&room, &activity, &mut room_occupancy, &rooms);
+                    book_instructor(&activity, &mut instructor_occupancy);
+
+                    activity.room = room;
+                    activity.assignment_reason = Some(AssignmentReason::BumpedLowerPriority);
+                    activities.retain(|a| a.id != activity.id);
+                    record_placement(&activity, &mut recent_placements);
+                    scheduled_activities.push(activity);
+
+                    // Bumped activities take any other room still free for all their slots
+                    for mut bumped in evicted {
+                        let building = linked_building(&bumped, &building_links, &rooms, &room_occupancy);
+                        let free_rooms: Vec<Room> = rooms
+                            .iter()
+                            .filter(|r| room_fits_activity(r, &bumped, options) && !reservations.blocks(r, &bumped))
+                            .filter(|r| in_building(r, building.as_ref()))
+                            .filter(|r| keeps_room_continuity(r, &bumped, &room_occupancy) && within_daily_cap(r, &bumped, &room_occupancy))
+                            .cloned()
+                            .collect();
+
+                        if free_rooms.is_empty() || instructor_conflict(&bumped, options, &instructor_occupancy).is_some() {
+                            tracing::debug!("Activity {} lost its room to a higher-priority activity.", bumped.subject);
+                            unscheduled_activities.push(UnscheduledActivity { activity: bumped, reason: UnscheduledReason::BumpedByPriority });
+                            continue;
+                        }
+
+                        let context = placement_context(&bumped, &recent_placements);
+                        let (room, reason) = select_room(&bumped, free_rooms, options, &context);
+
+                        tracing::debug!("Moving bumped activity {} to room {}.", bumped.subject, room.name);
+                        occupy_room(&room, &bumped, &mut room_occupancy, &rooms);
+                        book_instructor(&bumped, &mut instructor_occupancy);
+
+                        bumped.room = room;
+                        bumped.assignment_reason = Some(reason);
+                        scheduled_activities.push(bumped);
+                    }
+                    continue;
+                }
+            }
+
+            if available_rooms.is_empty() {
+                let reason = if outside_building {
+                    tracing::debug!("No room is free in the building activity {} has to share.", activity.subject);
+                    UnscheduledReason::BuildingLink
+                } else if reached_daily_cap {
+                    tracing::debug!("Every fitting room for activity {} reached its daily cap.", activity.subject);
+                    UnscheduledReason::RoomDailyCap
+                } else {
+                    tracing::debug!(
+                        "No available rooms for activity {} in time slot {}.",
+                        activity.subject, current_time_slot
+                    );
+                    UnscheduledReason::NoAvailableRoom
+                };
+
+                activities.retain(|a| a.id != activity.id);
+                unscheduled_activities.push(UnscheduledActivity { activity, reason });
+                continue;
+            }
+
+            let continuous_rooms: Vec<Room> = available_rooms
+                .into_iter()
+                .filter(|r| keeps_room_continuity(r, &activity, &room_occupancy))
+                .collect();
+
+            if continuous_rooms.is_empty() {
+                tracing::debug!(
+                    "No room stays free across all time slots of activity {}.",
+                    activity.subject
+                );
+                activities.retain(|a| a.id != activity.id);
+                unscheduled_activities.push(UnscheduledActivity { activity, reason: UnscheduledReason::RoomContinuity });
+                continue;
+            }
+
+            let context = placement_context(&activity, &recent_placements);
+            let (best_room, reason) = select_room(&activity, continuous_rooms, options, &context);
+
+            tracing::debug!("Assigning activity {} to room {} in time slot {}.", activity.subject, best_room.name, current_time_slot);
+
+            occupy_room(&best_room, &activity, &mut room_occupancy, &rooms);
+            book_instructor(&activity, &mut instructor_occupancy);
+
+            activity.room = best_room;
+            activity.assignment_reason = Some(reason);
+            activities.retain(|a| a.id != activity.id);
+            record_placement(&activity, &mut recent_placements);
+            scheduled_activities.push(activity);
+
+        }
+
+        on_slot_done(current_time_slot, scheduled_activities.len(), unscheduled_activities.len());
+
+        current_time_slot += 1;
+    }
+
+    assert_room_continuity(&scheduled_activities, &room_occupancy);
+
+    Ok((scheduled_activities, unscheduled_activities))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ScoringWeights;
+    use crate::scheduler::test_support::{activity, room, room_of};
+
+    fn schedule(activities: Vec<Activity>, rooms: Vec<Room>) -> Schedule {
+        run_scheduler(activities, rooms, &SchedulerOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn rooms_sharing_a_name_are_told_apart_by_id() {
+        let rooms = vec![room(1, "Lab", 30), room(2, "Lab", 30)];
+        let result = schedule(vec![activity(1, 20, &[0]), activity(2, 25, &[0])], rooms);
+
+        assert!(result.unscheduled_activities.is_empty());
+
+        let mut room_ids = vec![room_of(&result, 1), room_of(&result, 2)];
+        room_ids.sort();
+        assert_eq!(room_ids, vec![1, 2]);
+    }
+
+    fn partial() -> SchedulerOptions {
+        SchedulerOptions { allow_partial: true, ..SchedulerOptions::default() }
+    }
+
+    #[test]
+    fn higher_priority_activity_wins_the_last_room() {
+        let mut large = activity(1, 40, &[0]);
+        large.priority = Some(1);
+        let mut urgent = activity(2, 10, &[0]);
+        urgent.priority = Some(9);
+
+        let result = run_scheduler(vec![large, urgent], vec![room(1, "A-101", 50)], &partial()).unwrap();
+
+        assert_eq!(room_of(&result, 2), 1);
+        assert_eq!(result.unscheduled_activities.len(), 1);
+        assert_eq!(result.unscheduled_activities[0].activity.id, 1);
+    }
+
+    #[test]
+    fn activity_spanning_a_blackout_slot_is_left_out() {
+        let options = SchedulerOptions { blackout_slots: vec![2], ..SchedulerOptions::default() };
+        let activities = vec![activity(1, 20, &[1, 2]), activity(2, 20, &[3, 4])];
+
+        let result = run_scheduler(activities, vec![room(1, "A-101", 30)], &options).unwrap();
+
+        assert_eq!(room_of(&result, 2), 1);
+        assert_eq!(result.unscheduled_activities.len(), 1);
+        assert_eq!(result.unscheduled_activities[0].activity.id, 1);
+        assert_eq!(result.unscheduled_activities[0].reason, UnscheduledReason::BlackoutSlot);
+    }
+
+    #[test]
+    fn multi_slot_activity_keeps_one_room_across_its_slots() {
+        let rooms = vec![room(1, "A-101", 30), room(2, "B-201", 30)];
+        let result = schedule(vec![activity(1, 25, &[0, 1, 2]), activity(2, 20, &[1])], rooms);
+
+        let room_id = room_of(&result, 1);
+        let timeline = result.room_timelines.iter().find(|t| t.room_id == room_id).unwrap();
+        let held: Vec<u32> = timeline.slots.iter().filter(|s| s.activity_id == Some(1)).map(|s| s.slot).collect();
+
+        assert_eq!(held, vec![0, 1, 2]);
+        assert_ne!(room_of(&result, 2), room_id);
+        assert!(result.room_timelines.iter().filter(|t| t.room_id != room_id).all(|t| t.slots.iter().all(|s| s.activity_id != Some(1))));
+    }
+
+    #[test]
+    fn activity_without_a_room_free_for_every_slot_is_not_moved_midway() {
+        // Activity 1 holds the only room in slots 0 and 2, so activity 2 gets slot 1 but not 2
+        let activities = vec![activity(1, 20, &[0, 2]), activity(2, 20, &[1, 2])];
+
+        let result = run_scheduler(activities, vec![room(1, "A-101", 30)], &partial()).unwrap();
+
+        assert_eq!(room_of(&result, 1), 1);
+        assert_eq!(result.unscheduled_activities.len(), 1);
+        assert_eq!(result.unscheduled_activities[0].activity.id, 2);
+        assert_eq!(result.unscheduled_activities[0].reason, UnscheduledReason::RoomContinuity);
+    }
+
+    #[test]
+    fn oversized_activity_is_split_across_rooms_when_allowed() {
+        let mut lecture = activity(1, 70, &[0]);
+        lecture.allow_split = true;
+
+        let result = schedule(vec![lecture], vec![room(1, "A-101", 40), room(2, "B-201", 40), room(3, "C-301", 20)]);
+
+        let split: Vec<u32> = result.scheduled_activities[0].split_rooms.iter().map(|r| r.id).collect();
+        assert_eq!(split, vec![1, 2]);
+        assert_eq!(result.scheduled_activities[0].assignment_reason, Some(AssignmentReason::Split));
+    }
+
+    #[test]
+    fn activity_too_large_even_when_split_is_unscheduled() {
+        let mut lecture = activity(1, 100, &[0]);
+        lecture.allow_split = true;
+
+        let result = schedule(vec![lecture], vec![room(1, "A-101", 40), room(2, "B-201", 40)]);
+
+        assert!(result.scheduled_activities.is_empty());
+        assert_eq!(result.unscheduled_activities[0].reason, UnscheduledReason::NoRoomLargeEnough);
+    }
+
+    /// A faculty-sized day: `activities` activities of one or two slots over
+    /// 40 slots, and `rooms` rooms of mixed sizes, some reserved or equipped.
+    fn generated_day(activities: u32, rooms: u32) -> (Vec<Activity>, Vec<Room>) {
+        use rand::{Rng, SeedableRng, rngs::StdRng};
+
+        let mut rng = StdRng::seed_from_u64(291);
+
+        let rooms = (1..=rooms)
+            .map(|id| {
+                let mut r = room(id, &format!("R-{:03}", id), rng.gen_range(10..=200));
+                if id % 7 == 0 {
+                    r.features = vec!["lab".to_string()];
+                }
+                if id % 11 == 0 {
+                    r.allowed_subjects = Some(vec!["Subject 3".to_string()]);
+                }
+                r
+            })
+            .collect();
+
+        let activities = (1..=activities)
+            .map(|id| {
+                let start = rng.gen_range(0..40);
+                let slots: Vec<u32> = (start..start + rng.gen_range(1..=2)).collect();
+                let mut a = activity(id, rng.gen_range(5..=180), &slots);
+                a.priority = Some(rng.gen_range(1..=9));
+                if id % 13 == 0 {
+                    a.required_features = vec!["lab".to_string()];
+                }
+                a
+            })
+            .collect();
+
+        (activities, rooms)
+    }
+
+    fn schedule_day_on(workers: usize, activities: Vec<Activity>, rooms: Vec<Room>) -> String {
+        let activities = sort_activities(activities, None);
+        let (scheduled, unscheduled) =
+            schedule_day(activities, rooms, &partial(), &Reservations::default(), workers, &mut |_, _, _| {}).unwrap();
+
+        serde_json::to_string(&(scheduled, unscheduled)).unwrap()
+    }
+
+    #[test]
+    fn parallel_fit_checks_match_the_serial_ones() {
+        let (activities, rooms) = generated_day(2_000, 150);
+
+        let serial = fitting_room_ids(&activities, &rooms, &partial(), 1);
+
+        for workers in [2, 3, 8] {
+            assert_eq!(fitting_room_ids(&activities, &rooms, &partial(), workers), serial);
+        }
+    }
+
+    #[test]
+    fn parallel_day_schedule_matches_the_serial_one() {
+        let (activities, rooms) = generated_day(2_000, 150);
+
+        let serial = schedule_day_on(1, activities.clone(), rooms.clone());
+
+        assert_eq!(schedule_day_on(4, activities, rooms), serial);
+    }
+
+    #[test]
+    #[ignore = "benchmark; run with `cargo test --release -- --ignored --nocapture`"]
+    fn benchmark_parallel_fit_checks() {
+        let (activities, rooms) = generated_day(20_000, 600);
+        let workers = fit_check_workers(activities.len(), rooms.len());
+
+        for (label, workers) in [("serial", 1), ("parallel", workers)] {
+            let started_at = std::time::Instant::now();
+            let fits = fitting_room_ids(&activities, &rooms, &partial(), workers);
+            let fit_checks = started_at.elapsed();
+
+            let started_at = std::time::Instant::now();
+            schedule_day_on(workers, activities.clone(), rooms.clone());
+            let day = started_at.elapsed();
+
+            println!(
+                "{label} ({workers} threads): fit checks {:?} ({:.0} activities/s), whole day {:?}, {} activities",
+                fit_checks,
+                fits.len() as f64 / fit_checks.as_secs_f64(),
+                day,
+                activities.len(),
+            );
+        }
+    }
+
+    #[test]
+    fn same_slot_on_different_days_shares_a_room() {
+        let monday = activity(1, 20, &[0, 1]);
+        let mut tuesday = activity(2, 20, &[0, 1]);
+        tuesday.day = 1;
+
+        let result = schedule(vec![monday, tuesday], vec![room(1, "A-101", 30)]);
+
+        assert!(result.unscheduled_activities.is_empty());
+        assert_eq!(room_of(&result, 1), 1);
+        assert_eq!(room_of(&result, 2), 1);
+    }
+
+    #[test]
+    fn same_slot_on_the_same_day_needs_two_rooms() {
+        let result = run_scheduler(vec![activity(1, 20, &[0]), activity(2, 20, &[0])], vec![room(1, "A-101", 30)], &partial()).unwrap();
+
+        assert_eq!(result.scheduled_activities.len(), 1);
+        assert_eq!(result.unscheduled_activities.len(), 1);
+    }
+
+    #[test]
+    fn room_below_the_minimum_capacity_is_excluded() {
+        let mut exam = activity(1, 20, &[0]);
+        exam.min_capacity = Some(40);
+
+        let result = schedule(vec![exam], vec![room(1, "A-101", 25), room(2, "B-201", 60)]);
+
+        assert_eq!(room_of(&result, 1), 2);
+    }
+
+    #[test]
+    fn activity_without_a_room_meeting_its_minimum_is_unscheduled() {
+        let mut exam = activity(1, 20, &[0]);
+        exam.min_capacity = Some(40);
+
+        let result = run_scheduler(vec![exam], vec![room(1, "A-101", 25)], &partial()).unwrap();
+
+        assert!(result.scheduled_activities.is_empty());
+        assert_eq!(result.unscheduled_activities[0].activity.id, 1);
+    }
+
+    #[test]
+    fn overcapacity_tolerance_admits_a_slightly_small_room() {
+        let rooms = vec![room(1, "A-101", 30)];
+        let strict = run_scheduler(vec![activity(1, 33, &[0])], rooms.clone(), &partial()).unwrap();
+        let tolerant = SchedulerOptions { overcapacity_tolerance: 5, ..partial() };
+        let tolerated = run_scheduler(vec![activity(1, 33, &[0])], rooms, &tolerant).unwrap();
+
+        assert_eq!(strict.unscheduled_activities.len(), 1);
+        assert_eq!(room_of(&tolerated, 1), 1);
+    }
+
+    #[test]
+    fn tolerance_still_prefers_the_least_wasteful_room() {
+        let options = SchedulerOptions { overcapacity_tolerance: 5, ..SchedulerOptions::default() };
+        let rooms = vec![room(1, "Hall", 100), room(2, "A-101", 30)];
+
+        let result = run_scheduler(vec![activity(1, 33, &[0])], rooms, &options).unwrap();
+
+        assert_eq!(room_of(&result, 1), 2);
+    }
+
+    #[test]
+    fn huge_tolerance_and_capacities_do_not_overflow() {
+        let options = SchedulerOptions { overcapacity_tolerance: u32::MAX, ..SchedulerOptions::default() };
+        let result = run_scheduler(vec![activity(1, u32::MAX, &[0])], vec![room(1, "Hall", u32::MAX)], &options).unwrap();
+        assert_eq!(room_of(&result, 1), 1);
+
+        let mut lecture = activity(1, u32::MAX, &[0]);
+        lecture.allow_split = true;
+        let rooms = vec![room(1, "Hall", u32::MAX - 1), room(2, "Annex", u32::MAX - 1)];
+        let result = run_scheduler(vec![lecture], rooms, &SchedulerOptions::default()).unwrap();
+        assert!(result.unscheduled_activities.is_empty());
+    }
+
+    #[test]
+    fn building_weight_flips_the_chosen_room() {
+        let tight = room(1, "B-101", 30);
+        let mut preferred = room(2, "A-101", 45);
+        preferred.building = Some("A".to_string());
+        let rooms = vec![tight, preferred];
+
+        let mut seminar = activity(1, 30, &[0]);
+        seminar.preferred_building = Some("A".to_string());
+
+        let by_waste = schedule(vec![seminar.clone()], rooms.clone());
+        let weights = ScoringWeights { preferred_building: 100.0, ..ScoringWeights::default() };
+        let options = SchedulerOptions { scoring_weights: weights, ..SchedulerOptions::default() };
+        let by_building = run_scheduler(vec![seminar], rooms, &options).unwrap();
+
+        assert_eq!(room_of(&by_waste, 1), 1);
+        assert_eq!(room_of(&by_building, 1), 2);
+    }
+
+    #[test]
+    fn not_enough_rooms_names_the_oversubscribed_slot() {
+        let mut activities: Vec<Activity> = (1..=3).map(|id| activity(id, 10, &[1, 2])).collect();
+        activities.push(activity(4, 10, &[0]));
+        let mut tuesday = activity(5, 10, &[1]);
+        tuesday.day = 1;
+        activities.push(tuesday);
+
+        let result = run_scheduler(activities, vec![room(1, "A-101", 30), room(2, "A-102", 30)], &SchedulerOptions::default());
+
+        let Err(SchedulerError::NotEnoughRooms(shortfalls)) = result else {
+            panic!("expected NotEnoughRooms");
+        };
+
+        let found: Vec<(u8, u32, usize, usize, usize)> = shortfalls
+            .iter()
+            .map(|shortfall| (shortfall.day, shortfall.slot, shortfall.activities, shortfall.rooms, shortfall.shortfall))
+            .collect();
+
+        assert_eq!(found, vec![(0, 1, 3, 2, 1), (0, 2, 3, 2, 1)]);
+    }
+
+    #[test]
+    fn closed_rooms_do_not_count_towards_a_slot() {
+        let mut closed = room(2, "A-102", 30);
+        closed.unavailable_slots = vec![0];
+        let activities = vec![activity(1, 10, &[0]), activity(2, 10, &[0])];
+
+        let result = run_scheduler(activities, vec![room(1, "A-101", 30), closed], &SchedulerOptions::default());
+
+        let Err(SchedulerError::NotEnoughRooms(shortfalls)) = result else {
+            panic!("expected NotEnoughRooms");
+        };
+        assert_eq!((shortfalls[0].slot, shortfalls[0].rooms, shortfalls[0].shortfall), (0, 1, 1));
+    }
+
+    fn preferring(id: u32, students_count: u32, slots: &[u32], room_id: u32) -> Activity {
+        let mut activity = activity(id, students_count, slots);
+        activity.preferred_room = Some(room_id);
+        activity
+    }
+
+    #[test]
+    fn free_preferred_room_is_used() {
+        let rooms = vec![room(1, "A-101", 30), room(2, "Hall", 120)];
+
+        let result = schedule(vec![preferring(1, 25, &[0], 2)], rooms);
+
+        assert_eq!(room_of(&result, 1), 2);
+        assert_eq!(result.scheduled_activities[0].assignment_reason, Some(AssignmentReason::PreferredRoom));
+    }
+
+    #[test]
+    fn taken_preferred_room_falls_back_to_the_best_room() {
+        let mut pinned = activity(1, 100, &[0]);
+        pinned.pinned_room = Some(2);
+        let rooms = vec![room(1, "A-101", 30), room(2, "Hall", 120)];
+
+        let result = schedule(vec![pinned, preferring(2, 25, &[0], 2)], rooms);
+
+        assert_eq!(room_of(&result, 2), 1);
+        assert_ne!(result.scheduled_activities.iter().find(|a| a.id == 2).unwrap().assignment_reason, Some(AssignmentReason::PreferredRoom));
+    }
+
+    #[test]
+    fn too_small_preferred_room_falls_back_to_the_best_room() {
+        let rooms = vec![room(1, "A-101", 30), room(2, "Hall", 120)];
+
+        let result = schedule(vec![preferring(1, 50, &[0], 1)], rooms);
+
+        assert_eq!(room_of(&result, 1), 2);
+        assert!(result.unscheduled_activities.is_empty());
+    }
+
+    #[test]
+    fn feasible_rooms_are_those_that_fit_on_their_own() {
+        let mut lab = room(3, "Lab", 40);
+        lab.features = vec!["projector".to_string()];
+        let rooms = vec![room(1, "A-101", 20), room(2, "Hall", 120), lab];
+
+        let mut seminar = activity(1, 30, &[0]);
+        let ids = |rooms: Vec<Room>| rooms.into_iter().map(|r| r.id).collect::<Vec<_>>();
+        assert_eq!(ids(feasible_rooms(&seminar, &rooms, &SchedulerOptions::default())), vec![2, 3]);
+
+        seminar.required_features = vec!["projector".to_string()];
+        assert_eq!(ids(feasible_rooms(&seminar, &rooms, &SchedulerOptions::default())), vec![3]);
+
+        seminar.min_capacity = Some(60);
+        assert!(feasible_rooms(&seminar, &rooms, &SchedulerOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn partial_mode_schedules_what_strict_mode_rejects() {
+        let oversubscribed = || vec![activity(1, 10, &[0]), activity(2, 20, &[0]), activity(3, 10, &[1])];
+        let rooms = vec![room(1, "A-101", 30)];
+
+        let strict = run_scheduler(oversubscribed(), rooms.clone(), &SchedulerOptions::default());
+        let partial = run_scheduler(oversubscribed(), rooms, &partial()).unwrap();
+
+        assert!(matches!(strict, Err(SchedulerError::NotEnoughRooms(_))));
+        assert_eq!(partial.scheduled_activities.len(), 2);
+        assert_eq!(room_of(&partial, 3), 1);
+        assert_eq!(partial.unscheduled_activities.len(), 1);
+        assert_eq!(partial.unscheduled_activities[0].reason, UnscheduledReason::AllRoomsBusy);
+    }
+
+    /// Greedy puts activity 3 in the larger room it holds through slot 2,
+    /// where activity 2 needs it; every activity fits when 1 takes that room
+    /// first and 3 the smaller one.
+    fn greedy_trap() -> (Vec<Activity>, Vec<Room>) {
+        let activities = vec![activity(1, 20, &[0, 1]), activity(2, 30, &[2]), activity(3, 10, &[1, 2])];
+        (activities, vec![room(1, "A-101", 20), room(2, "B-201", 30)])
+    }
+
+    #[test]
+    fn greedy_leaves_the_trap_partly_unscheduled() {
+        let (activities, rooms) = greedy_trap();
+
+        let result = run_scheduler(activities, rooms, &partial()).unwrap();
+
+        assert_eq!(room_of(&result, 3), 2);
+        assert_eq!(result.unscheduled_activities.len(), 1);
+        assert_eq!(result.unscheduled_activities[0].activity.id, 2);
+    }
+
+    #[cfg(feature = "exact")]
+    #[test]
+    fn exact_strategy_schedules_what_greedy_leaves_out() {
+        let (activities, rooms) = greedy_trap();
+        let options = SchedulerOptions { strategy: Strategy::Exact, ..partial() };
+
+        let result = run_scheduler(activities, rooms, &options).unwrap();
+
+        assert!(result.unscheduled_activities.is_empty());
+        assert_eq!((room_of(&result, 1), room_of(&result, 2), room_of(&result, 3)), (2, 2, 1));
+        assert!(result.scheduled_activities.iter().all(|a| a.assignment_reason == Some(AssignmentReason::OptimalSearch)));
+    }
+
+    #[test]
+    fn alternative_window_is_used_when_the_primary_is_taken() {
+        let mut flexible = activity(2, 20, &[0]);
+        flexible.alternative_time_slots = vec![vec![0, 1], vec![3]];
+        let mut busy = activity(3, 20, &[1]);
+        busy.pinned_room = Some(1);
+
+        let result = schedule(vec![activity(1, 25, &[0]), flexible, busy], vec![room(1, "A-101", 30)]);
+
+        let moved = result.scheduled_activities.iter().find(|a| a.id == 2).unwrap();
+        assert_eq!(moved.chosen_alternative, Some(1));
+        assert_eq!(moved.time_slots, vec![3]);
+        assert!(result.unscheduled_activities.is_empty());
+    }
+
+    #[test]
+    fn activity_is_unscheduled_when_every_window_fails() {
+        let mut flexible = activity(2, 20, &[0]);
+        flexible.alternative_time_slots = vec![vec![1]];
+
+        let rooms = vec![room(1, "A-101", 30)];
+        let result = run_scheduler(vec![activity(1, 25, &[0, 1]), flexible], rooms, &partial()).unwrap();
+
+        assert_eq!(room_of(&result, 1), 1);
+        assert_eq!(result.unscheduled_activities.len(), 1);
+        assert_eq!(result.unscheduled_activities[0].activity.id, 2);
+        assert_eq!(result.unscheduled_activities[0].activity.chosen_alternative, None);
+    }
+
+    fn of_cohort(mut activity: Activity, cohort_id: Option<&str>) -> Activity {
+        activity.cohort_id = cohort_id.map(str::to_string);
+        activity
+    }
+
+    #[test]
+    fn cohort_keeps_the_room_it_just_vacated() {
+        let rooms = vec![room(1, "Hall", 60), room(2, "A-101", 25), room(3, "A-102", 16)];
+        let back_to_back = |cohort_id| vec![of_cohort(activity(1, 20, &[0]), cohort_id), of_cohort(activity(2, 15, &[1]), cohort_id)];
+
+        let cohort = schedule(back_to_back(Some("1A")), rooms.clone());
+        let strangers = schedule(back_to_back(None), rooms);
+
+        assert_eq!((room_of(&cohort, 1), room_of(&cohort, 2)), (2, 2));
+        assert_eq!(cohort.scheduled_activities.iter().find(|a| a.id == 2).unwrap().assignment_reason, Some(AssignmentReason::CohortRoom));
+        assert_eq!(room_of(&strangers, 2), 3);
+    }
+
+    #[test]
+    fn cohort_moves_when_its_room_is_too_small() {
+        let rooms = vec![room(1, "Hall", 60), room(2, "A-101", 25)];
+        let activities = vec![of_cohort(activity(1, 20, &[0]), Some("1A")), of_cohort(activity(2, 40, &[1]), Some("1A"))];
+
+        let result = schedule(activities, rooms);
+
+        assert_eq!((room_of(&result, 1), room_of(&result, 2)), (2, 1));
+    }
+
+    fn capped(id: u32, name: &str, capacity: u32, max_daily_slots: u32) -> Room {
+        Room { max_daily_slots: Some(max_daily_slots), ..room(id, name, capacity) }
+    }
+
+    #[test]
+    fn room_at_its_daily_cap_sends_the_rest_elsewhere() {
+        let rooms = vec![capped(1, "A-101", 30, 2), room(2, "Hall", 60)];
+        let activities = vec![activity(1, 20, &[0]), activity(2, 20, &[1]), activity(3, 20, &[2])];
+
+        let result = schedule(activities, rooms);
+        let in_capped = (1..=3).filter(|id| room_of(&result, *id) == 1).count();
+
+        assert!(result.unscheduled_activities.is_empty());
+        assert_eq!(in_capped, 2);
+    }
+
+    #[test]
+    fn room_past_its_daily_cap_leaves_the_activity_unscheduled() {
+        let activities = vec![activity(1, 20, &[0]), activity(2, 20, &[1]), activity(3, 20, &[2])];
+
+        let result = run_scheduler(activities, vec![capped(1, "A-101", 30, 2)], &partial()).unwrap();
+
+        assert_eq!(result.scheduled_activities.len(), 2);
+        assert_eq!(result.unscheduled_activities.len(), 1);
+        assert_eq!(result.unscheduled_activities[0].reason, UnscheduledReason::RoomDailyCap);
+    }
+
+    #[test]
+    fn daily_cap_starts_over_on_the_next_day() {
+        let mut tuesday = activity(3, 20, &[0]);
+        tuesday.day = 1;
+        let activities = vec![activity(1, 20, &[0]), activity(2, 20, &[1]), tuesday];
+
+        let result = schedule(activities, vec![capped(1, "A-101", 30, 2)]);
+
+        assert!(result.unscheduled_activities.is_empty());
+        assert_eq!(room_of(&result, 3), 1);
+    }
+
+    #[test]
+    fn metadata_passes_through_unchanged() {
+        let metadata = serde_json::json!({ "course": "CS-101", "color": "#ff8800", "tags": ["core", 1, null], "nested": { "x": 1.5 } });
+        let mut tagged = activity(1, 20, &[0]);
+        tagged.metadata = metadata.clone();
+        tagged.priority = Some(9);
+        let mut dropped = activity(2, 20, &[0]);
+        dropped.metadata = serde_json::json!("left out");
+
+        let result = run_scheduler(vec![tagged, dropped], vec![room(1, "A-101", 30)], &partial()).unwrap();
+
+        assert_eq!(result.scheduled_activities[0].metadata, metadata);
+        assert_eq!(result.unscheduled_activities[0].activity.metadata, serde_json::json!("left out"));
+    }
+
+    /// Id of the one activity dropped when two equal activities compete for one room.
+    fn dropped_with(fairness_seed: u64) -> u32 {
+        let options = SchedulerOptions { fairness_seed: Some(fairness_seed), ..partial() };
+        let result = run_scheduler(vec![activity(1, 20, &[0]), activity(2, 20, &[0])], vec![room(1, "A-101", 30)], &options).unwrap();
+
+        assert_eq!(result.unscheduled_activities.len(), 1);
+        result.unscheduled_activities[0].activity.id
+    }
+
+    #[test]
+    fn different_seeds_drop_different_activities() {
+        let dropped: HashSet<u32> = (0..16).map(dropped_with).collect();
+
+        assert_eq!(dropped, HashSet::from([1, 2]));
+    }
+
+    #[test]
+    fn a_seed_always_drops_the_same_activity() {
+        for seed in 0..16 {
+            assert_eq!(dropped_with(seed), dropped_with(seed));
+        }
+    }
+
+    fn chemistry_lab() -> Room {
+        Room { allowed_subjects: Some(vec!["Chemistry".to_string()]), ..room(1, "Lab", 30) }
+    }
+
+    fn about(subject: &str, activity: Activity) -> Activity {
+        Activity { subject: subject.to_string(), ..activity }
+    }
+
+    #[test]
+    fn restricted_room_is_used_for_a_matching_subject() {
+        let result = schedule(vec![about("Chemistry", activity(1, 20, &[0]))], vec![chemistry_lab(), room(2, "Hall", 80)]);
+
+        assert_eq!(room_of(&result, 1), 1);
+    }
+
+    #[test]
+    fn restricted_room_is_excluded_for_other_subjects() {
+        let result = schedule(vec![about("History", activity(1, 20, &[0]))], vec![chemistry_lab(), room(2, "Hall", 80)]);
+
+        assert_eq!(room_of(&result, 1), 2);
+    }
+
+    #[test]
+    fn activity_with_only_restricted_rooms_is_unscheduled() {
+        let result = run_scheduler(vec![about("History", activity(1, 20, &[0]))], vec![chemistry_lab()], &partial()).unwrap();
+
+        assert!(result.scheduled_activities.is_empty());
+        assert_eq!(result.unscheduled_activities[0].reason, UnscheduledReason::NoAvailableRoom);
+    }
+
+    fn reason_of(schedule: &Schedule, activity_id: u32) -> Option<AssignmentReason> {
+        schedule.scheduled_activities.iter().find(|a| a.id == activity_id).unwrap().assignment_reason
+    }
+
+    #[test]
+    fn closest_capacity_pick_is_explained_by_its_score() {
+        let result = schedule(vec![activity(1, 20, &[0])], vec![room(1, "A-101", 25), room(2, "Hall", 80)]);
+
+        assert_eq!(reason_of(&result, 1), Some(AssignmentReason::BestScore));
+    }
+
+    #[test]
+    fn single_fitting_room_is_explained_as_such() {
+        let result = schedule(vec![activity(1, 50, &[0])], vec![room(1, "A-101", 25), room(2, "Hall", 80)]);
+
+        assert_eq!(reason_of(&result, 1), Some(AssignmentReason::OnlyFittingRoom));
+    }
+
+    #[test]
+    fn pinned_pick_is_explained_by_the_pin() {
+        let pinned = Activity { pinned_room: Some(2), ..activity(1, 20, &[0]) };
+
+        let result = schedule(vec![pinned], vec![room(1, "A-101", 25), room(2, "Hall", 80)]);
+
+        assert_eq!(room_of(&result, 1), 2);
+        assert_eq!(reason_of(&result, 1), Some(AssignmentReason::PinnedRoom));
+    }
+
+    #[test]
+    fn preferred_pick_is_explained_by_the_preference() {
+        let result = schedule(vec![preferring(1, 20, &[0], 2)], vec![room(1, "A-101", 25), room(2, "Hall", 80)]);
+
+        assert_eq!(room_of(&result, 1), 2);
+        assert_eq!(reason_of(&result, 1), Some(AssignmentReason::PreferredRoom));
+    }
+
+    fn pinned(id: u32, slots: &[u32], room_id: u32, instructor_id: Option<u32>) -> Activity {
+        Activity { pinned_room: Some(room_id), instructor_id, ..activity(id, 20, slots) }
+    }
+
+    fn pin_conflicts_of(activities: Vec<Activity>, unavailable_slots: Vec<u32>) -> Vec<PinConflict> {
+        let instructor = Instructor { id: 7, name: "Dr. Rivas".to_string(), unavailable_slots };
+        let options = SchedulerOptions { instructors: vec![instructor], ..SchedulerOptions::default() };
+
+        match run_scheduler(activities, vec![room(1, "A-101", 30), room(2, "A-102", 30)], &options) {
+            Err(SchedulerError::PinConflict(conflicts)) => conflicts,
+            other => panic!("expected a pin conflict, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn activities_pinned_to_the_same_room_collide() {
+        let conflicts = pin_conflicts_of(vec![pinned(1, &[0, 1], 1, None), pinned(2, &[1], 1, None)], Vec::new());
+
+        assert_eq!(conflicts, vec![PinConflict {
+            day: 0,
+            slot: 1,
+            kind: PinConflictKind::Room,
+            room_id: Some(1),
+            instructor_id: None,
+            activity_ids: vec![1, 2],
+        }]);
+    }
+
+    #[test]
+    fn pinned_activities_sharing_an_instructor_collide() {
+        let conflicts = pin_conflicts_of(vec![pinned(1, &[0, 1], 1, Some(7)), pinned(2, &[1, 2], 2, Some(7))], Vec::new());
+
+        assert_eq!(conflicts, vec![PinConflict {
+            day: 0,
+            slot: 1,
+            kind: PinConflictKind::Instructor,
+            room_id: None,
+            instructor_id: Some(7),
+            activity_ids: vec![1, 2],
+        }]);
+    }
+
+    #[test]
+    fn pin_in_a_slot_the_instructor_is_unavailable_collides() {
+        let conflicts = pin_conflicts_of(vec![pinned(1, &[2, 3], 1, Some(7))], vec![3]);
+
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!((conflicts[0].kind, conflicts[0].slot, conflicts[0].instructor_id), (PinConflictKind::InstructorUnavailable, 3, Some(7)));
+    }
+
+    #[test]
+    fn pinned_activities_of_one_instructor_on_different_days_do_not_collide() {
+        let mut tuesday = pinned(2, &[0], 2, Some(7));
+        tuesday.day = 1;
+        let instructor = Instructor { id: 7, name: "Dr. Rivas".to_string(), unavailable_slots: Vec::new() };
+        let options = SchedulerOptions { instructors: vec![instructor], ..SchedulerOptions::default() };
+
+        let result = run_scheduler(vec![pinned(1, &[0], 1, Some(7)), tuesday], vec![room(1, "A-101", 30), room(2, "A-102", 30)], &options).unwrap();
+
+        assert_eq!(result.scheduled_activities.len(), 2);
+    }
+}
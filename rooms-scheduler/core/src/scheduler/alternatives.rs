@@ -0,0 +1,79 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::models::{Activity, RankedSchedule, Room, Schedule, SchedulerOptions};
+use crate::scheduler::algorithm::run_scheduler;
+use crate::scheduler::error::SchedulerError;
+use crate::scheduler::scoring::schedule_score;
+
+/// Upper bound on `ActivitiesRequest::alternatives`.
+pub const MAX_ALTERNATIVES: u8 = 10;
+
+/// Reseeded runs tried per requested alternative before giving up on
+/// finding more distinct schedules.
+const ATTEMPTS_PER_ALTERNATIVE: u64 = 4;
+
+/// Which rooms every scheduled activity got; two schedules are the same
+/// alternative when these match.
+fn placement_signature(schedule: &Schedule) -> BTreeMap<u32, Vec<u32>> {
+    schedule
+        .scheduled_activities
+        .iter()
+        .map(|activity| (activity.id, activity.assigned_rooms().iter().map(|room| room.id).collect()))
+        .collect()
+}
+
+/// Reruns the scheduler with other tie-break and random seeds until
+/// `options.alternatives` distinct schedules are found (or the attempts run
+/// out), then returns the best one with every alternative ranked in it:
+/// fewest unscheduled activities first, then highest `schedule_score`.
+pub(crate) fn with_alternatives(
+    activities: Vec<Activity>,
+    rooms: Vec<Room>,
+    options: &SchedulerOptions,
+    first: Schedule,
+) -> Result<Schedule, SchedulerError> {
+    let wanted = options.alternatives as usize;
+
+    let mut seen = HashSet::from([placement_signature(&first)]);
+    let mut schedules = vec![first];
+
+    for attempt in 1..=ATTEMPTS_PER_ALTERNATIVE * wanted as u64 {
+        if schedules.len() == wanted {
+            break;
+        }
+
+        let mut variant = options.clone();
+        variant.alternatives = 0;
+        variant.fairness_seed = Some(options.fairness_seed.unwrap_or(0).wrapping_add(attempt));
+        variant.seed = Some(options.seed.unwrap_or(0).wrapping_add(attempt));
+
+        let schedule = run_scheduler(activities.clone(), rooms.clone(), &variant)?;
+
+        if seen.insert(placement_signature(&schedule)) {
+            schedules.push(schedule);
+        }
+    }
+
+    let mut ranked: Vec<(f64, Schedule)> = schedules
+        .into_iter()
+        .map(|schedule| (schedule_score(&schedule, &options.scoring_weights, &options.buildings), schedule))
+        .collect();
+
+    ranked.sort_by(|(score_a, a), (score_b, b)| {
+        a.unscheduled_activities
+            .len()
+            .cmp(&b.unscheduled_activities.len())
+            .then(score_b.total_cmp(score_a))
+    });
+
+    let alternatives: Vec<RankedSchedule> = ranked
+        .into_iter()
+        .enumerate()
+        .map(|(index, (score, schedule))| RankedSchedule { rank: index + 1, score, schedule })
+        .collect();
+
+    let mut best = alternatives[0].schedule.clone();
+    best.alternatives = alternatives;
+
+    Ok(best)
+}
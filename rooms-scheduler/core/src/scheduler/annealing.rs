@@ -0,0 +1,288 @@
+//! Annealing strategy: simulated annealing over activity-to-room
+//! assignments, starting from a greedy placement. A move puts one activity
+//! into another fitting room, evicting whatever holds that room (or its
+//! instructor) in the same slots, and then re-seats the evicted activities
+//! in any free fitting room. The cost is the same as the ILP strategy's:
+//! unscheduled activities first, wasted seats second.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::models::{Activity, AssignmentReason, Room, SchedulerOptions, UnscheduledActivity, UnscheduledReason};
+use crate::scheduler::algorithm::{Reservations, book_instructor, instructor_conflict, room_fits_activity, room_tie_break};
+
+/// Longest a request may let the annealing strategy run per day.
+const MAX_TIME_BUDGET_MS: u64 = 10_000;
+
+/// Moves between two looks at the clock.
+const CLOCK_CHECK_INTERVAL: u32 = 256;
+
+struct State<'a> {
+    activities: &'a [Activity],
+    /// Fitting room indexes of each activity, fewest wasted seats first.
+    candidates: Vec<Vec<usize>>,
+    rooms: &'a [Room],
+    assignment: Vec<Option<usize>>,
+    occupancy: HashMap<(usize, u32), usize>,
+    teaching: HashMap<(u32, u32), usize>,
+    /// Slots still bookable per room under `max_daily_slots`.
+    remaining_slots: Vec<usize>,
+    /// Cost of leaving one activity out; outweighs any wasted seats.
+    unscheduled_cost: i64,
+    cost: i64,
+}
+
+impl State<'_> {
+    fn wasted_seats(&self, activity: usize, room: usize) -> i64 {
+        self.rooms[room].capacity.saturating_sub(self.activities[activity].students_count) as i64
+    }
+
+    fn is_free(&self, activity: usize, room: usize) -> bool {
+        let slots = &self.activities[activity].time_slots;
+
+        slots.len() <= self.remaining_slots[room]
+            && slots.iter().all(|slot| !self.occupancy.contains_key(&(room, *slot)))
+            && self.activities[activity].instructor_id.is_none_or(|instructor_id| {
+                slots.iter().all(|slot| !self.teaching.contains_key(&(instructor_id, *slot)))
+            })
+    }
+
+    fn place(&mut self, activity: usize, room: usize) {
+        let slots = &self.activities[activity].time_slots;
+
+        for slot in slots {
+            self.occupancy.insert((room, *slot), activity);
+        }
+        if let Some(instructor_id) = self.activities[activity].instructor_id {
+            for slot in slots {
+                self.teaching.insert((instructor_id, *slot), activity);
+            }
+        }
+
+        self.remaining_slots[room] -= slots.len();
+        self.assignment[activity] = Some(room);
+        self.cost -= self.unscheduled_cost - self.wasted_seats(activity, room);
+    }
+
+    fn remove(&mut self, activity: usize) {
+        let Some(room) = self.assignment[activity].take() else {
+            return;
+        };
+
+        let slots = &self.activities[activity].time_slots;
+
+        for slot in slots {
+            self.occupancy.remove(&(room, *slot));
+        }
+        if let Some(instructor_id) = self.activities[activity].instructor_id {
+            for slot in slots {
+                self.teaching.remove(&(instructor_id, *slot));
+            }
+        }
+
+        self.remaining_slots[room] += slots.len();
+        self.cost += self.unscheduled_cost - self.wasted_seats(activity, room);
+    }
+
+    /// Activities that would have to leave for `activity` to take `room`.
+    fn blockers(&self, activity: usize, room: usize) -> Vec<usize> {
+        let slots = &self.activities[activity].time_slots;
+        let mut blockers: Vec<usize> = slots
+            .iter()
+            .filter_map(|slot| self.occupancy.get(&(room, *slot)).copied())
+            .collect();
+
+        if let Some(instructor_id) = self.activities[activity].instructor_id {
+            blockers.extend(slots.iter().filter_map(|slot| self.teaching.get(&(instructor_id, *slot)).copied()));
+        }
+
+        blockers.sort_unstable();
+        blockers.dedup();
+        blockers.retain(|blocker| *blocker != activity);
+        blockers
+    }
+
+    /// Seats the activity in its first free fitting room, if any.
+    fn reseat(&mut self, activity: usize) {
+        if let Some(room) = self.candidates[activity].iter().copied().find(|room| self.is_free(activity, *room)) {
+            self.place(activity, room);
+        }
+    }
+
+    /// Moves `activity` into `room`, evicting and re-seating its blockers.
+    /// Returns the activities touched with their previous rooms, or `None`
+    /// (with nothing changed) when the room's daily cap rules it out.
+    fn apply_move(&mut self, activity: usize, room: usize) -> Option<Vec<(usize, Option<usize>)>> {
+        let mut touched = vec![(activity, self.assignment[activity])];
+        touched.extend(self.blockers(activity, room).into_iter().map(|blocker| (blocker, self.assignment[blocker])));
+
+        for (touched_activity, _) in &touched {
+            self.remove(*touched_activity);
+        }
+
+        if !self.is_free(activity, room) {
+            self.undo(&touched);
+            return None;
+        }
+
+        self.place(activity, room);
+
+        for (blocker, _) in &touched[1..] {
+            self.reseat(*blocker);
+        }
+
+        Some(touched)
+    }
+
+    fn undo(&mut self, touched: &[(usize, Option<usize>)]) {
+        for (activity, _) in touched {
+            self.remove(*activity);
+        }
+
+        for (activity, room) in touched {
+            if let Some(room) = room {
+                self.place(*activity, *room);
+            }
+        }
+    }
+}
+
+/// Schedules the activities of a single day by simulated annealing.
+pub(crate) fn schedule_day_annealing(
+    activities: Vec<Activity>,
+    rooms: &[Room],
+    options: &SchedulerOptions,
+    reservations: &Reservations,
+) -> (Vec<Activity>, Vec<UnscheduledActivity>) {
+    let params = &options.annealing;
+    let deadline = Instant::now() + Duration::from_millis(params.time_budget_ms.min(MAX_TIME_BUDGET_MS));
+
+    let mut unscheduled_activities = Vec::new();
+
+    let mut schedulable = Vec::new();
+
+    for activity in activities {
+        match instructor_conflict(&activity, options, &reservations.instructors) {
+            Some(reason) => unscheduled_activities.push(UnscheduledActivity { activity, reason }),
+            None => schedulable.push(activity),
+        }
+    }
+
+    let activities = schedulable;
+
+    let unscheduled_cost = rooms.iter().map(|room| room.capacity as i64).max().unwrap_or(0) + 1;
+
+    let candidates: Vec<Vec<usize>> = activities
+        .iter()
+        .map(|activity| {
+            let mut candidates: Vec<usize> = (0..rooms.len())
+                .filter(|room| room_fits_activity(&rooms[*room], activity, options) && !reservations.blocks(&rooms[*room], activity))
+                .collect();
+            candidates.sort_by(|a, b| {
+                let wasted_seats = |room: usize| rooms[room].capacity.saturating_sub(activity.students_count);
+                wasted_seats(*a).cmp(&wasted_seats(*b)).then(room_tie_break(&rooms[*a], &rooms[*b]))
+            });
+            candidates
+        })
+        .collect();
+
+    let mut state = State {
+        activities: &activities,
+        candidates,
+        rooms,
+        assignment: vec![None; activities.len()],
+        occupancy: HashMap::new(),
+        teaching: HashMap::new(),
+        remaining_slots: rooms
+            .iter()
+            .map(|room| {
+                room.max_daily_slots
+                    .map_or(usize::MAX, |cap| (cap as usize).saturating_sub(reservations.booked_slots(room)))
+            })
+            .collect(),
+        unscheduled_cost,
+        cost: unscheduled_cost * activities.len() as i64,
+    };
+
+    // Greedy start: the work list is popped from the back, highest rank first
+    for activity in (0..activities.len()).rev() {
+        state.reseat(activity);
+    }
+
+    let movable: Vec<usize> = (0..activities.len()).filter(|activity| !state.candidates[*activity].is_empty()).collect();
+
+    let mut best_assignment = state.assignment.clone();
+    let mut best_cost = state.cost;
+
+    let mut rng = StdRng::seed_from_u64(options.seed.unwrap_or(0));
+    let mut temperature = params.initial_temperature;
+
+    for iteration in 0..params.iterations {
+        if movable.is_empty() {
+            break;
+        }
+
+        if iteration % CLOCK_CHECK_INTERVAL == 0 && Instant::now() >= deadline {
+            tracing::info!("Annealing stopped at its time budget after {} moves.", iteration);
+            break;
+        }
+
+        let activity = movable[rng.gen_range(0..movable.len())];
+        let candidates = &state.candidates[activity];
+        let room = candidates[rng.gen_range(0..candidates.len())];
+
+        temperature *= params.cooling_rate;
+
+        if state.assignment[activity] == Some(room) {
+            continue;
+        }
+
+        let cost_before = state.cost;
+
+        let Some(touched) = state.apply_move(activity, room) else {
+            continue;
+        };
+
+        let delta = (state.cost - cost_before) as f64;
+
+        if delta > 0.0 && rng.r#gen::<f64>() >= (-delta / temperature).exp() {
+            state.undo(&touched);
+            continue;
+        }
+
+        if state.cost < best_cost {
+            best_cost = state.cost;
+            best_assignment = state.assignment.clone();
+        }
+    }
+
+    let mut scheduled_activities = Vec::new();
+    let mut skipped = Vec::new();
+    let mut instructor_occupancy = reservations.instructors.clone();
+
+    for (mut activity, room) in activities.iter().cloned().zip(best_assignment) {
+        match room {
+            Some(room) => {
+                tracing::debug!("Assigning activity {} to room {}.", activity.subject, rooms[room].name);
+                activity.room = rooms[room].clone();
+                activity.assignment_reason = Some(AssignmentReason::AnnealingSearch);
+                book_instructor(&activity, &mut instructor_occupancy);
+                scheduled_activities.push(activity);
+            }
+            None => skipped.push(activity),
+        }
+    }
+
+    // Activities left out while their instructor teaches something overlapping
+    for activity in skipped {
+        let reason = instructor_conflict(&activity, options, &instructor_occupancy)
+            .unwrap_or(UnscheduledReason::NoAvailableRoom);
+
+        unscheduled_activities.push(UnscheduledActivity { activity, reason });
+    }
+
+    (scheduled_activities, unscheduled_activities)
+}
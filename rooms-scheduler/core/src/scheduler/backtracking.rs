@@ -0,0 +1,278 @@
+//! Backtracking strategy: activities are admitted one at a time in the
+//! greedy pass's order (by start slot, higher ranks first), and each
+//! newcomer may move the rooms of the activities admitted before it that
+//! share one of its slots. An activity is only left out when no such
+//! rearrangement (within a search budget) fits it alongside every activity
+//! admitted earlier. Splits and alternative windows are not explored.
+
+use std::collections::HashSet;
+
+use crate::models::{Activity, AssignmentReason, Room, SchedulerOptions, UnscheduledActivity, UnscheduledReason};
+use crate::scheduler::algorithm::{Reservations, book_instructor, instructor_conflict, room_fits_activity, room_tie_break};
+use crate::scheduler::scoring::score_room;
+
+/// Search budget per admitted activity; past it the newcomer is left out
+/// and the previous arrangement is kept.
+const MAX_SEARCH_NODES: u64 = 2_000;
+
+struct Search<'a> {
+    activities: Vec<&'a Activity>,
+    candidates: Vec<Vec<usize>>,
+    occupied: HashSet<(usize, u32)>,
+    /// Slots still bookable per room under `max_daily_slots`.
+    remaining_slots: Vec<usize>,
+    assignment: Vec<usize>,
+    nodes: u64,
+}
+
+impl Search<'_> {
+    fn fits(&self, room: usize, activity: &Activity) -> bool {
+        activity.time_slots.len() <= self.remaining_slots[room]
+            && activity.time_slots.iter().all(|slot| !self.occupied.contains(&(room, *slot)))
+    }
+
+    fn set_occupied(&mut self, room: usize, activity: &Activity, occupied: bool) {
+        for slot in &activity.time_slots {
+            if occupied {
+                self.occupied.insert((room, *slot));
+            } else {
+                self.occupied.remove(&(room, *slot));
+            }
+        }
+
+        if occupied {
+            self.remaining_slots[room] -= activity.time_slots.len();
+        } else {
+            self.remaining_slots[room] += activity.time_slots.len();
+        }
+    }
+
+    /// Places activities `index..` without overlaps; `true` once all fit.
+    fn place(&mut self, index: usize) -> bool {
+        if index == self.activities.len() {
+            return true;
+        }
+
+        self.nodes += 1;
+        if self.nodes > MAX_SEARCH_NODES {
+            return false;
+        }
+
+        let activity = self.activities[index];
+
+        for choice in 0..self.candidates[index].len() {
+            let room = self.candidates[index][choice];
+
+            if !self.fits(room, activity) {
+                continue;
+            }
+
+            self.set_occupied(room, activity, true);
+            self.assignment[index] = room;
+
+            if self.place(index + 1) {
+                return true;
+            }
+
+            self.set_occupied(room, activity, false);
+        }
+
+        false
+    }
+}
+
+/// Fitting rooms of the activity that no pinned activity holds, the
+/// preferred room first, then by score.
+fn ranked_candidates(activity: &Activity, rooms: &[Room], options: &SchedulerOptions, reservations: &Reservations) -> Vec<usize> {
+    let mut candidates: Vec<(usize, f64)> = (0..rooms.len())
+        .filter(|r| room_fits_activity(&rooms[*r], activity, options) && !reservations.blocks(&rooms[*r], activity))
+        .map(|r| (r, score_room(&rooms[r], activity, &options.scoring_weights, &options.buildings).total))
+        .collect();
+
+    candidates.sort_by(|(a, score_a), (b, score_b)| {
+        let preferred = |r: usize| Some(rooms[r].id) != activity.preferred_room;
+
+        preferred(*a)
+            .cmp(&preferred(*b))
+            .then(score_b.total_cmp(score_a))
+            .then(room_tie_break(&rooms[*a], &rooms[*b]))
+    });
+
+    candidates.into_iter().map(|(room, _)| room).collect()
+}
+
+/// `candidates` with `current` moved to the front, so earlier arrangements
+/// are kept when possible.
+fn current_first(candidates: &[usize], current: usize) -> Vec<usize> {
+    let mut ordered = Vec::with_capacity(candidates.len());
+    ordered.push(current);
+    ordered.extend(candidates.iter().copied().filter(|room| *room != current));
+    ordered
+}
+
+fn overlaps(a: &Activity, b: &Activity) -> bool {
+    a.time_slots.iter().any(|slot| b.time_slots.contains(slot))
+}
+
+/// Tries to fit the `newcomer` activity among the `admitted` ones, moving
+/// only those that share a slot with it; returns the room index of every
+/// admitted activity, then the newcomer's, when it succeeds.
+fn arrange(
+    activities: &[Activity],
+    candidates: &[Vec<usize>],
+    admitted: &[(usize, usize)],
+    newcomer: usize,
+    rooms: &[Room],
+    reservations: &Reservations,
+) -> Option<Vec<usize>> {
+    let activity = &activities[newcomer];
+    let newcomer_candidates = &candidates[newcomer];
+
+    if newcomer_candidates.is_empty() {
+        return None;
+    }
+
+    let (movable, fixed): (Vec<usize>, Vec<usize>) = (0..admitted.len())
+        .partition(|position| overlaps(&activities[admitted[*position].0], activity));
+
+    // In some slot every room is taken, or the activities that can only use
+    // the newcomer's rooms already take all of them: nothing to search
+    let sharing = |positions: &[usize], slot: &u32| {
+        positions
+            .iter()
+            .filter(|position| activities[admitted[**position].0].time_slots.contains(slot))
+            .count()
+    };
+
+    let confined: Vec<usize> = movable
+        .iter()
+        .copied()
+        .filter(|position| {
+            candidates[admitted[*position].0]
+                .iter()
+                .all(|room| newcomer_candidates.contains(room))
+        })
+        .collect();
+
+    if activity.time_slots.iter().any(|slot| {
+        sharing(&movable, slot) >= rooms.len() || sharing(&confined, slot) >= newcomer_candidates.len()
+    }) {
+        return None;
+    }
+
+    let mut search = Search {
+        activities: Vec::new(),
+        candidates: Vec::new(),
+        occupied: HashSet::new(),
+        remaining_slots: rooms
+            .iter()
+            .map(|room| {
+                room.max_daily_slots
+                    .map_or(usize::MAX, |cap| (cap as usize).saturating_sub(reservations.booked_slots(room)))
+            })
+            .collect(),
+        assignment: Vec::new(),
+        nodes: 0,
+    };
+
+    for position in &fixed {
+        let (index, room) = admitted[*position];
+        search.set_occupied(room, &activities[index], true);
+    }
+
+    // The newcomer picks its room first, preferring the ones nobody has to
+    // leave; moved activities then go most constrained first and try their
+    // current room before any other
+    let mut movable = movable;
+    movable.sort_by_key(|position| candidates[admitted[*position].0].len());
+
+    let mut newcomer_candidates = newcomer_candidates.clone();
+    newcomer_candidates.sort_by_key(|room| movable.iter().any(|position| admitted[*position].1 == *room));
+
+    search.activities.push(activity);
+    search.candidates.push(newcomer_candidates);
+
+    for position in &movable {
+        let (index, room) = admitted[*position];
+        search.activities.push(&activities[index]);
+        search.candidates.push(current_first(&candidates[index], room));
+    }
+
+    search.assignment = vec![0; search.activities.len()];
+
+    if !search.place(0) {
+        return None;
+    }
+
+    let mut arrangement: Vec<usize> = admitted.iter().map(|(_, room)| *room).collect();
+    for (index, position) in movable.iter().enumerate() {
+        arrangement[*position] = search.assignment[index + 1];
+    }
+    arrangement.push(search.assignment[0]);
+
+    Some(arrangement)
+}
+
+/// Schedules the activities of a single day. `activities` comes sorted
+/// like the greedy work list, so the last one is admitted first.
+pub(crate) fn schedule_day_backtracking(
+    activities: Vec<Activity>,
+    rooms: &[Room],
+    options: &SchedulerOptions,
+    reservations: &Reservations,
+) -> (Vec<Activity>, Vec<UnscheduledActivity>) {
+    let candidates: Vec<Vec<usize>> = activities
+        .iter()
+        .map(|activity| ranked_candidates(activity, rooms, options, reservations))
+        .collect();
+
+    let mut admitted: Vec<(usize, usize)> = Vec::new();
+    let mut unscheduled_activities = Vec::new();
+
+    let mut instructor_occupancy = reservations.instructors.clone();
+
+    // Chronological admission like the greedy pass, higher ranks first
+    // among the activities starting in the same slot
+    let mut admission_order: Vec<usize> = (0..activities.len()).rev().collect();
+    admission_order.sort_by_key(|index| activities[*index].time_slots[0]);
+
+    for index in admission_order {
+        let activity = &activities[index];
+
+        if let Some(reason) = instructor_conflict(activity, options, &instructor_occupancy) {
+            unscheduled_activities.push(UnscheduledActivity { activity: activity.clone(), reason });
+            continue;
+        }
+
+        match arrange(&activities, &candidates, &admitted, index, rooms, reservations) {
+            Some(arrangement) => {
+                book_instructor(activity, &mut instructor_occupancy);
+                admitted.push((index, 0));
+
+                for ((_, room), arranged) in admitted.iter_mut().zip(arrangement) {
+                    *room = arranged;
+                }
+            }
+            None => {
+                tracing::debug!("No arrangement of the rooms fits activity {}.", activity.subject);
+                unscheduled_activities.push(UnscheduledActivity {
+                    activity: activity.clone(),
+                    reason: UnscheduledReason::NoAvailableRoom,
+                });
+            }
+        }
+    }
+
+    let scheduled_activities = admitted
+        .into_iter()
+        .map(|(index, room)| {
+            let mut activity = activities[index].clone();
+            tracing::debug!("Assigning activity {} to room {}.", activity.subject, rooms[room].name);
+            activity.room = rooms[room].clone();
+            activity.assignment_reason = Some(AssignmentReason::BacktrackingSearch);
+            activity
+        })
+        .collect();
+
+    (scheduled_activities, unscheduled_activities)
+}
@@ -0,0 +1,258 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+
+use crate::models::{Activity, CalendarMapping};
+use crate::scheduler::error::SchedulerError;
+
+const DEFAULT_SLOT_LENGTH_MINUTES: u32 = 60;
+
+/// Renders scheduled activities as an RFC 5545 calendar with one VEVENT
+/// per activity. Times are floating (no time zone), so calendar apps show
+/// them in the viewer's local time.
+pub fn schedule_to_ics(
+    scheduled_activities: &[Activity],
+    mapping: &CalendarMapping,
+    request_slot_length: Option<u32>,
+) -> Result<String, SchedulerError> {
+    let start_date = NaiveDate::parse_from_str(&mapping.start_date, "%Y-%m-%d").map_err(|_| {
+        SchedulerError::InvalidInput(format!(
+            "calendar.start_date `{}` must be formatted as YYYY-MM-DD.",
+            mapping.start_date
+        ))
+    })?;
+
+    let slot_times = mapping
+        .slot_times
+        .iter()
+        .enumerate()
+        .map(|(slot, time)| parse_time(time, &format!("calendar.slot_times[{}]", slot)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let day_start = match slot_times.is_empty() {
+        true => parse_time(&mapping.day_start, "calendar.day_start")?,
+        false => NaiveTime::MIN,
+    };
+
+    let slot_length = mapping
+        .slot_length_minutes
+        .or(request_slot_length)
+        .unwrap_or(DEFAULT_SLOT_LENGTH_MINUTES);
+
+    if slot_length == 0 {
+        return Err(SchedulerError::InvalidInput(
+            "calendar.slot_length_minutes must be greater than zero.".to_string(),
+        ));
+    }
+
+    let stamp = format_datetime(Utc::now().naive_utc()) + "Z";
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//rooms-scheduler//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    // Start of a slot, counted from midnight
+    let slot_start = |slot: u32| -> Result<Duration, SchedulerError> {
+        if slot_times.is_empty() {
+            return (slot as i64)
+                .checked_mul(slot_length as i64)
+                .and_then(Duration::try_minutes)
+                .map(|offset| day_start - NaiveTime::MIN + offset)
+                .ok_or_else(|| SchedulerError::InvalidInput(format!(
+                    "Slot {} of {} minutes starts too late to be represented.",
+                    slot, slot_length
+                )));
+        }
+
+        slot_times.get(slot as usize).map(|time| *time - NaiveTime::MIN).ok_or_else(|| {
+            SchedulerError::InvalidInput(format!(
+                "calendar.slot_times has no start time for slot {}.",
+                slot
+            ))
+        })
+    };
+
+    for activity in scheduled_activities {
+        let day = start_date.and_time(NaiveTime::MIN) + Duration::days(activity.day as i64);
+
+        let location = activity
+            .assigned_rooms()
+            .iter()
+            .map(|room| room.name.clone())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        // One event per run of consecutive slots, so gaps stay free in the calendar
+        for (part, (first, last)) in slot_runs(&activity.time_slots).into_iter().enumerate() {
+            let out_of_range = || SchedulerError::InvalidInput(format!(
+                "Activity {} ends past the last representable date.",
+                activity.id
+            ));
+
+            let starts_at = day.checked_add_signed(slot_start(first)?).ok_or_else(out_of_range)?;
+            let ends_at = slot_start(last)?
+                .checked_add(&Duration::minutes(slot_length as i64))
+                .and_then(|offset| day.checked_add_signed(offset))
+                .ok_or_else(out_of_range)?;
+
+            let uid = match part {
+                0 => format!("activity-{}-day-{}@rooms-scheduler", activity.id, activity.day),
+                _ => format!("activity-{}-day-{}-part-{}@rooms-scheduler", activity.id, activity.day, part + 1),
+            };
+
+            lines.push("BEGIN:VEVENT".to_string());
+            lines.push(format!("UID:{}", uid));
+            lines.push(format!("DTSTAMP:{}", stamp));
+            lines.push(format!("DTSTART:{}", format_datetime(starts_at)));
+            lines.push(format!("DTEND:{}", format_datetime(ends_at)));
+            lines.push(format!("SUMMARY:{}", escape_text(&activity.subject)));
+            lines.push(format!("LOCATION:{}", escape_text(&location)));
+            lines.push("END:VEVENT".to_string());
+        }
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    Ok(lines.iter().map(|line| fold_line(line) + "\r\n").collect())
+}
+
+/// First and last slot of every run of consecutive slots.
+fn slot_runs(time_slots: &[u32]) -> Vec<(u32, u32)> {
+    let mut slots = time_slots.to_vec();
+    slots.sort_unstable();
+
+    let mut runs: Vec<(u32, u32)> = Vec::new();
+
+    for slot in slots {
+        match runs.last_mut() {
+            Some((_, last)) if last.checked_add(1) == Some(slot) => *last = slot,
+            _ => runs.push((slot, slot)),
+        }
+    }
+
+    runs
+}
+
+fn parse_time(value: &str, field: &str) -> Result<NaiveTime, SchedulerError> {
+    NaiveTime::parse_from_str(value, "%H:%M").map_err(|_| {
+        SchedulerError::InvalidInput(format!("{} `{}` must be formatted as HH:MM.", field, value))
+    })
+}
+
+fn format_datetime(datetime: NaiveDateTime) -> String {
+    datetime.format("%Y%m%dT%H%M%S").to_string()
+}
+
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Splits content lines longer than 75 octets, as required by RFC 5545.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut line_octets = 0;
+
+    for ch in line.chars() {
+        if line_octets + ch.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            line_octets = 1;
+        }
+
+        folded.push(ch);
+        line_octets += ch.len_utf8();
+    }
+
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::scheduler::test_support::{activity, room};
+
+    fn mapping() -> CalendarMapping {
+        CalendarMapping {
+            start_date: "2025-03-03".to_string(),
+            day_start: "08:00".to_string(),
+            slot_length_minutes: None,
+            slot_times: Vec::new(),
+        }
+    }
+
+    fn scheduled(id: u32, day: u8, slots: &[u32], room_name: &str) -> Activity {
+        let mut activity = activity(id, 10, slots);
+        activity.day = day;
+        activity.room = room(id, room_name, 30);
+        activity
+    }
+
+    /// The properties of every VEVENT, with folded lines joined back.
+    fn events(ics: &str) -> Vec<HashMap<String, String>> {
+        let unfolded = ics.replace("\r\n ", "");
+
+        unfolded
+            .split("BEGIN:VEVENT\r\n")
+            .skip(1)
+            .map(|event| {
+                event
+                    .split("\r\n")
+                    .take_while(|line| *line != "END:VEVENT")
+                    .filter_map(|line| line.split_once(':'))
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_scheduled_activity_becomes_a_vevent() {
+        let activities = vec![scheduled(1, 0, &[0, 1], "A-101"), scheduled(2, 2, &[3], "Hall; East, 2nd floor")];
+
+        let ics = schedule_to_ics(&activities, &mapping(), Some(45)).unwrap();
+        let events = events(&ics);
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n") && ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(events.len(), 2);
+
+        assert_eq!(events[0]["SUMMARY"], "Subject 1");
+        assert_eq!(events[0]["LOCATION"], "A-101");
+        assert_eq!(events[0]["DTSTART"], "20250303T080000");
+        assert_eq!(events[0]["DTEND"], "20250303T093000");
+
+        assert_eq!(events[1]["LOCATION"], r"Hall\; East\, 2nd floor");
+        assert_eq!(events[1]["DTSTART"], "20250305T101500");
+        assert_eq!(events[1]["DTEND"], "20250305T110000");
+    }
+
+    #[test]
+    fn slot_times_override_the_day_start() {
+        let mapping = CalendarMapping { slot_times: vec!["08:00".to_string(), "10:30".to_string()], ..mapping() };
+
+        let events = events(&schedule_to_ics(&[scheduled(1, 0, &[1], "A-101")], &mapping, None).unwrap());
+
+        assert_eq!(events[0]["DTSTART"], "20250303T103000");
+        assert_eq!(events[0]["DTEND"], "20250303T113000");
+    }
+
+    #[test]
+    fn gaps_split_an_activity_into_several_events() {
+        let events = events(&schedule_to_ics(&[scheduled(1, 0, &[0, 2], "A-101")], &mapping(), None).unwrap());
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1]["UID"], "activity-1-day-0-part-2@rooms-scheduler");
+    }
+
+    #[test]
+    fn slots_past_the_representable_range_are_rejected() {
+        let mapping = CalendarMapping { slot_length_minutes: Some(u32::MAX), ..mapping() };
+
+        assert!(schedule_to_ics(&[scheduled(1, 0, &[u32::MAX], "A-101")], &mapping, None).is_err());
+    }
+}
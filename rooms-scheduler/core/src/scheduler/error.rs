@@ -0,0 +1,71 @@
+use std::fmt;
+
+use serde::Serialize;
+
+/// A `(day, slot)` whose simultaneous activities outnumber the rooms.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SlotShortfall {
+    pub day: u8,
+    pub slot: u32,
+    pub activities: usize,
+    pub rooms: usize,
+    pub shortfall: usize,
+}
+
+/// A `(day, slot)` in which pinned activities cannot all be held as pinned.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PinConflict {
+    pub day: u8,
+    pub slot: u32,
+    pub kind: PinConflictKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub room_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructor_id: Option<u32>,
+    pub activity_ids: Vec<u32>,
+}
+
+/// What the activities of a `PinConflict` clash over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PinConflictKind {
+    /// Several activities are pinned to the same room.
+    Room,
+    /// Several pinned activities are taught by the same instructor.
+    Instructor,
+    /// The activity is pinned to a slot its instructor is unavailable in.
+    InstructorUnavailable,
+}
+
+/// One problem found in a request, located by its JSON path.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationError {
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchedulerError {
+    NotEnoughRooms(Vec<SlotShortfall>),
+    /// Pinned activities that would share a room or an instructor in the
+    /// same slot, or that their instructor cannot teach.
+    PinConflict(Vec<PinConflict>),
+    /// Every violation found in the request, not just the first.
+    Validation(Vec<ValidationError>),
+    InvalidInput(String),
+    Internal(String),
+}
+
+impl fmt::Display for SchedulerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchedulerError::NotEnoughRooms(_) => write!(f, "Not enough rooms to schedule all activities."),
+            SchedulerError::PinConflict(_) => write!(f, "Some pinned activities clash over a room or an instructor."),
+            SchedulerError::Validation(errors) => write!(f, "The request has {} validation errors.", errors.len()),
+            SchedulerError::InvalidInput(message) => write!(f, "{}", message),
+            SchedulerError::Internal(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for SchedulerError {}
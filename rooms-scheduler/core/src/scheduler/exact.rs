@@ -0,0 +1,207 @@
+//! Exact strategy: a branch-and-bound search over activity-to-room
+//! assignments that maximizes the number of scheduled activities of a day,
+//! subject to room capacity, slot exclusivity and instructor availability.
+
+use std::collections::HashSet;
+
+use crate::models::{Activity, AssignmentReason, Room, SchedulerOptions, UnscheduledActivity, UnscheduledReason};
+use crate::scheduler::algorithm::{Reservations, instructor_conflict, room_fits_activity, room_tie_break};
+use crate::scheduler::scoring::score_room;
+
+/// Upper bound on explored nodes so pathological inputs still terminate;
+/// past it the best assignment found so far is returned.
+const MAX_SEARCH_NODES: u64 = 2_000_000;
+
+struct Search<'a> {
+    activities: Vec<&'a Activity>,
+    candidates: Vec<Vec<usize>>,
+    occupied: HashSet<(usize, u32)>,
+    /// `(instructor_id, slot)` pairs taken by the current assignment.
+    teaching: HashSet<(u32, u32)>,
+    /// Slots still bookable per room under `max_daily_slots`.
+    remaining_slots: Vec<usize>,
+    current: Vec<Option<usize>>,
+    best: Vec<Option<usize>>,
+    best_count: usize,
+    nodes: u64,
+}
+
+impl Search<'_> {
+    fn is_free(&self, room: usize, activity: &Activity) -> bool {
+        activity.time_slots.len() <= self.remaining_slots[room]
+            && activity.time_slots.iter().all(|slot| !self.occupied.contains(&(room, *slot)))
+    }
+
+    fn instructor_is_free(&self, activity: &Activity) -> bool {
+        activity.instructor_id.is_none_or(|instructor_id| {
+            activity.time_slots.iter().all(|slot| !self.teaching.contains(&(instructor_id, *slot)))
+        })
+    }
+
+    fn set_teaching(&mut self, activity: &Activity, teaching: bool) {
+        if let Some(instructor_id) = activity.instructor_id {
+            for slot in &activity.time_slots {
+                if teaching {
+                    self.teaching.insert((instructor_id, *slot));
+                } else {
+                    self.teaching.remove(&(instructor_id, *slot));
+                }
+            }
+        }
+    }
+
+    fn explore(&mut self, index: usize, count: usize) {
+        self.nodes += 1;
+
+        let remaining = self.activities.len() - index;
+
+        // Not even scheduling every remaining activity would beat the best
+        if count + remaining <= self.best_count {
+            return;
+        }
+
+        if index == self.activities.len() {
+            self.best_count = count;
+            self.best = self.current.clone();
+            return;
+        }
+
+        if self.nodes > MAX_SEARCH_NODES {
+            return;
+        }
+
+        let activity = self.activities[index];
+        let candidates = if self.instructor_is_free(activity) {
+            self.candidates[index].clone()
+        } else {
+            Vec::new()
+        };
+
+        for room in candidates {
+
+            if !self.is_free(room, activity) {
+                continue;
+            }
+
+            for slot in &activity.time_slots {
+                self.occupied.insert((room, *slot));
+            }
+            self.remaining_slots[room] -= activity.time_slots.len();
+            self.set_teaching(activity, true);
+            self.current[index] = Some(room);
+
+            self.explore(index + 1, count + 1);
+
+            self.current[index] = None;
+            self.set_teaching(activity, false);
+            self.remaining_slots[room] += activity.time_slots.len();
+            for slot in &activity.time_slots {
+                self.occupied.remove(&(room, *slot));
+            }
+        }
+
+        self.explore(index + 1, count);
+    }
+}
+
+/// Schedules the activities of a single day with the optimal number of placements.
+pub(crate) fn schedule_day_exact(
+    activities: Vec<Activity>,
+    rooms: &[Room],
+    options: &SchedulerOptions,
+    reservations: &Reservations,
+) -> (Vec<Activity>, Vec<UnscheduledActivity>) {
+    let mut unscheduled_activities = Vec::new();
+
+    let mut schedulable = Vec::new();
+
+    for activity in activities {
+        match instructor_conflict(&activity, options, &reservations.instructors) {
+            Some(reason) => unscheduled_activities.push(UnscheduledActivity { activity, reason }),
+            None => schedulable.push(activity),
+        }
+    }
+
+    let activities = schedulable;
+
+    let mut ordered: Vec<(&Activity, Vec<usize>)> = activities
+        .iter()
+        .map(|activity| {
+            let mut candidates: Vec<usize> = (0..rooms.len())
+                .filter(|r| room_fits_activity(&rooms[*r], activity, options) && !reservations.blocks(&rooms[*r], activity))
+                .collect();
+
+            candidates.sort_by(|a, b| {
+                let score_a = score_room(&rooms[*a], activity, &options.scoring_weights, &options.buildings).total;
+                let score_b = score_room(&rooms[*b], activity, &options.scoring_weights, &options.buildings).total;
+                score_b.total_cmp(&score_a).then(room_tie_break(&rooms[*a], &rooms[*b]))
+            });
+
+            (activity, candidates)
+        })
+        .collect();
+
+    // Most constrained activities first keeps the search tree narrow
+    ordered.sort_by_key(|(activity, candidates)| (candidates.len(), std::cmp::Reverse(activity.priority())));
+
+    let (ordered_activities, candidates): (Vec<&Activity>, Vec<Vec<usize>>) = ordered.into_iter().unzip();
+
+    let mut search = Search {
+        current: vec![None; ordered_activities.len()],
+        best: vec![None; ordered_activities.len()],
+        activities: ordered_activities,
+        candidates,
+        occupied: HashSet::new(),
+        teaching: HashSet::new(),
+        remaining_slots: rooms
+            .iter()
+            .map(|room| {
+                room.max_daily_slots
+                    .map_or(usize::MAX, |cap| (cap as usize).saturating_sub(reservations.booked_slots(room)))
+            })
+            .collect(),
+        best_count: 0,
+        nodes: 0,
+    };
+
+    search.explore(0, 0);
+
+    let mut scheduled_activities = Vec::new();
+    let mut skipped = Vec::new();
+
+    for (activity, room) in search.activities.iter().zip(search.best.iter()) {
+        let mut activity = (*activity).clone();
+
+        match room {
+            Some(room) => {
+                tracing::debug!("Assigning activity {} to room {}.", activity.subject, rooms[*room].name);
+                activity.room = rooms[*room].clone();
+                activity.assignment_reason = Some(AssignmentReason::OptimalSearch);
+                scheduled_activities.push(activity);
+            }
+            None => skipped.push(activity),
+        }
+    }
+
+    // Activities left out while their instructor teaches something overlapping
+    for activity in skipped {
+        let reason = if teaches_overlapping(&activity, &scheduled_activities) {
+            UnscheduledReason::InstructorConflict
+        } else {
+            UnscheduledReason::NoAvailableRoom
+        };
+
+        unscheduled_activities.push(UnscheduledActivity { activity, reason });
+    }
+
+    (scheduled_activities, unscheduled_activities)
+}
+
+fn teaches_overlapping(activity: &Activity, scheduled_activities: &[Activity]) -> bool {
+    activity.instructor_id.is_some_and(|instructor_id| {
+        scheduled_activities.iter().any(|other| {
+            other.instructor_id == Some(instructor_id)
+                && other.time_slots.iter().any(|slot| activity.time_slots.contains(slot))
+        })
+    })
+}
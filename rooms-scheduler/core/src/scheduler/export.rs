@@ -0,0 +1,213 @@
+//! Tabular exports of a schedule: one row per activity, scheduled ones first.
+
+use crate::models::{Activity, UnscheduledActivity};
+
+const HEADER: [&str; 7] = ["activity_id", "subject", "day", "rooms", "time_slots", "students_count", "unscheduled_reason"];
+
+/// A cell of an exported row.
+enum Cell {
+    Number(u32),
+    Text(String),
+}
+
+fn rows(scheduled_activities: &[Activity], unscheduled_activities: &[UnscheduledActivity]) -> Vec<Vec<Cell>> {
+    let row = |activity: &Activity, rooms: String, reason: String| {
+        vec![
+            Cell::Number(activity.id),
+            Cell::Text(activity.subject.clone()),
+            Cell::Number(activity.day as u32),
+            Cell::Text(rooms),
+            Cell::Text(activity.time_slots.iter().map(u32::to_string).collect::<Vec<_>>().join(" ")),
+            Cell::Number(activity.students_count),
+            Cell::Text(reason),
+        ]
+    };
+
+    let scheduled = scheduled_activities.iter().map(|activity| {
+        let rooms = activity.assigned_rooms().iter().map(|room| room.name.clone()).collect::<Vec<_>>().join(", ");
+        row(activity, rooms, String::new())
+    });
+
+    let unscheduled = unscheduled_activities.iter().map(|unscheduled| {
+        let reason = serde_json::to_value(unscheduled.reason)
+            .ok()
+            .and_then(|reason| reason.as_str().map(str::to_string))
+            .unwrap_or_default();
+        row(&unscheduled.activity, String::new(), reason)
+    });
+
+    scheduled.chain(unscheduled).collect()
+}
+
+/// Renders a schedule as RFC 4180 CSV with a header row.
+pub fn schedule_to_csv(scheduled_activities: &[Activity], unscheduled_activities: &[UnscheduledActivity]) -> String {
+    let mut lines = vec![HEADER.join(",")];
+
+    for row in rows(scheduled_activities, unscheduled_activities) {
+        let fields = row
+            .iter()
+            .map(|cell| match cell {
+                Cell::Number(number) => number.to_string(),
+                Cell::Text(text) => escape_csv(text),
+            })
+            .collect::<Vec<_>>();
+
+        lines.push(fields.join(","));
+    }
+
+    lines.iter().map(|line| line.clone() + "\r\n").collect()
+}
+
+fn escape_csv(value: &str) -> String {
+    match value.contains([',', '"', '\r', '\n']) {
+        true => format!("\"{}\"", value.replace('"', "\"\"")),
+        false => value.to_string(),
+    }
+}
+
+/// Renders a schedule as an Excel workbook with a single sheet laid out
+/// like the CSV export. The package is written uncompressed, which every
+/// spreadsheet application reads.
+pub fn schedule_to_xlsx(scheduled_activities: &[Activity], unscheduled_activities: &[UnscheduledActivity]) -> Vec<u8> {
+    let header = HEADER.iter().map(|title| Cell::Text(title.to_string())).collect();
+
+    let mut sheet_rows = String::new();
+
+    for (index, row) in std::iter::once(header).chain(rows(scheduled_activities, unscheduled_activities)).enumerate() {
+        sheet_rows.push_str(&format!("<row r=\"{}\">", index + 1));
+
+        for cell in row {
+            match cell {
+                Cell::Number(number) => sheet_rows.push_str(&format!("<c><v>{}</v></c>", number)),
+                Cell::Text(text) => sheet_rows.push_str(&format!("<c t=\"inlineStr\"><is><t>{}</t></is></c>", escape_xml(&text))),
+            }
+        }
+
+        sheet_rows.push_str("</row>");
+    }
+
+    let sheet = format!(
+        "{}<worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\"><sheetData>{}</sheetData></worksheet>",
+        XML_DECLARATION, sheet_rows
+    );
+
+    let files = [
+        ("[Content_Types].xml", format!("{}{}", XML_DECLARATION, CONTENT_TYPES)),
+        ("_rels/.rels", format!("{}{}", XML_DECLARATION, ROOT_RELS)),
+        ("xl/workbook.xml", format!("{}{}", XML_DECLARATION, WORKBOOK)),
+        ("xl/_rels/workbook.xml.rels", format!("{}{}", XML_DECLARATION, WORKBOOK_RELS)),
+        ("xl/worksheets/sheet1.xml", sheet),
+    ];
+
+    zip_stored(&files)
+}
+
+const XML_DECLARATION: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n";
+
+const CONTENT_TYPES: &str = concat!(
+    "<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">",
+    "<Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>",
+    "<Default Extension=\"xml\" ContentType=\"application/xml\"/>",
+    "<Override PartName=\"/xl/workbook.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>",
+    "<Override PartName=\"/xl/worksheets/sheet1.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>",
+    "</Types>",
+);
+
+const ROOT_RELS: &str = concat!(
+    "<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">",
+    "<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"xl/workbook.xml\"/>",
+    "</Relationships>",
+);
+
+const WORKBOOK: &str = concat!(
+    "<workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" ",
+    "xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">",
+    "<sheets><sheet name=\"Schedule\" sheetId=\"1\" r:id=\"rId1\"/></sheets>",
+    "</workbook>",
+);
+
+const WORKBOOK_RELS: &str = concat!(
+    "<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">",
+    "<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet\" Target=\"worksheets/sheet1.xml\"/>",
+    "</Relationships>",
+);
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Packs files into a ZIP archive without compression.
+fn zip_stored(files: &[(&str, String)]) -> Vec<u8> {
+    let mut archive = Vec::new();
+    let mut central_directory = Vec::new();
+
+    for (name, contents) in files {
+        let offset = archive.len() as u32;
+        let crc = crc32(contents.as_bytes());
+        let size = contents.len() as u32;
+
+        // Version 2.0, no flags, stored, timestamp 1980-01-01 00:00
+        let entry = |signature: u32, central: bool| {
+            let mut header = Vec::new();
+            header.extend_from_slice(&signature.to_le_bytes());
+            if central {
+                header.extend_from_slice(&20u16.to_le_bytes());
+            }
+            header.extend_from_slice(&20u16.to_le_bytes());
+            header.extend_from_slice(&0u16.to_le_bytes());
+            header.extend_from_slice(&0u16.to_le_bytes());
+            header.extend_from_slice(&0u16.to_le_bytes());
+            header.extend_from_slice(&0x21u16.to_le_bytes());
+            header.extend_from_slice(&crc.to_le_bytes());
+            header.extend_from_slice(&size.to_le_bytes());
+            header.extend_from_slice(&size.to_le_bytes());
+            header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            header.extend_from_slice(&0u16.to_le_bytes());
+            if central {
+                // Comment length, disk number, internal and external attributes
+                header.extend_from_slice(&[0; 10]);
+                header.extend_from_slice(&offset.to_le_bytes());
+            }
+            header.extend_from_slice(name.as_bytes());
+            header
+        };
+
+        archive.extend(entry(0x0403_4b50, false));
+        archive.extend_from_slice(contents.as_bytes());
+        central_directory.extend(entry(0x0201_4b50, true));
+    }
+
+    let directory_offset = archive.len() as u32;
+    let directory_size = central_directory.len() as u32;
+    archive.extend(central_directory);
+
+    archive.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    archive.extend_from_slice(&[0; 4]);
+    archive.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    archive.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    archive.extend_from_slice(&directory_size.to_le_bytes());
+    archive.extend_from_slice(&directory_offset.to_le_bytes());
+    archive.extend_from_slice(&0u16.to_le_bytes());
+
+    archive
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = match crc & 1 {
+                1 => (crc >> 1) ^ 0xEDB8_8320,
+                _ => crc >> 1,
+            };
+        }
+    }
+
+    !crc
+}
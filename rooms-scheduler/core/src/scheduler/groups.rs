@@ -0,0 +1,101 @@
+//! Cross-listed sections: activities sharing a `group_id` are scheduled as a
+//! single activity holding every member's students, then given back to each
+//! member in the output.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::models::{Activity, UnscheduledActivity};
+use crate::scheduler::error::SchedulerError;
+
+/// Members of every combined activity, by the id the combined activity took.
+pub(crate) type GroupMembers = HashMap<u32, Vec<Activity>>;
+
+/// Replaces the members of each group by one activity with their summed
+/// headcount, the strictest of their room requirements and the highest
+/// priority; it takes the id and everything else from the member with the
+/// lowest id. Members must meet on the same day, in the same slots, with the
+/// same instructor.
+pub(crate) fn combine_groups(activities: Vec<Activity>) -> Result<(Vec<Activity>, GroupMembers), SchedulerError> {
+    let mut grouped: BTreeMap<String, Vec<Activity>> = BTreeMap::new();
+    let mut combined = Vec::new();
+
+    for activity in activities {
+        match activity.group_id.clone() {
+            Some(group_id) => grouped.entry(group_id).or_default().push(activity),
+            None => combined.push(activity),
+        }
+    }
+
+    let mut members_by_id = GroupMembers::new();
+
+    for (group_id, mut members) in grouped {
+        members.sort_by_key(|member| member.id);
+
+        let mut activity = members[0].clone();
+
+        for member in &members[1..] {
+            if member.day != activity.day || member.time_slots != activity.time_slots || member.instructor_id != activity.instructor_id {
+                return Err(SchedulerError::InvalidInput(format!(
+                    "Activities {} and {} of group {} must share the same day, time slots and instructor.",
+                    activity.id, member.id, group_id
+                )));
+            }
+
+            activity.students_count += member.students_count;
+            activity.min_capacity = activity.min_capacity.max(member.min_capacity);
+            activity.priority = Some(activity.priority().max(member.priority()));
+            activity.pinned_room = activity.pinned_room.or(member.pinned_room);
+            activity.requires_accessibility |= member.requires_accessibility;
+
+            for feature in &member.required_features {
+                if !activity.required_features.contains(feature) {
+                    activity.required_features.push(feature.clone());
+                }
+            }
+        }
+
+        tracing::debug!("Combining {} activities of group {} into one.", members.len(), group_id);
+        members_by_id.insert(activity.id, members);
+        combined.push(activity);
+    }
+
+    Ok((combined, members_by_id))
+}
+
+/// Gives every member of a combined activity its room, slots and outcome.
+pub(crate) fn expand_groups(
+    scheduled_activities: Vec<Activity>,
+    unscheduled_activities: Vec<UnscheduledActivity>,
+    mut members_by_id: GroupMembers,
+) -> (Vec<Activity>, Vec<UnscheduledActivity>) {
+    let mut expand = |combined: Activity| -> Vec<Activity> {
+        let Some(members) = members_by_id.remove(&combined.id) else {
+            return vec![combined];
+        };
+
+        members
+            .into_iter()
+            .map(|mut member| {
+                member.room = combined.room.clone();
+                member.split_rooms = combined.split_rooms.clone();
+                member.time_slots = combined.time_slots.clone();
+                member.chosen_alternative = combined.chosen_alternative;
+                member.assignment_reason = combined.assignment_reason;
+                member
+            })
+            .collect()
+    };
+
+    let scheduled_activities = scheduled_activities.into_iter().flat_map(&mut expand).collect();
+    let unscheduled_activities = unscheduled_activities
+        .into_iter()
+        .flat_map(|unscheduled| {
+            let reason = unscheduled.reason;
+            expand(unscheduled.activity)
+                .into_iter()
+                .map(move |activity| UnscheduledActivity { activity, reason })
+        })
+        .collect();
+
+    (scheduled_activities, unscheduled_activities)
+}
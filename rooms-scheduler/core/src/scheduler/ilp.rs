@@ -0,0 +1,272 @@
+//! ILP strategy: the day is written as a 0-1 integer program and solved to
+//! optimality. There is one binary variable per fitting `(activity, room)`
+//! pair, and the constraints are:
+//!
+//! - each activity takes at most one room,
+//! - each room holds at most one activity per slot (slot exclusivity),
+//! - a room books at most `max_daily_slots` slots,
+//! - an instructor teaches at most one activity per slot.
+//!
+//! The objective minimizes unscheduled activities first and wasted seats
+//! second. Room capacity is enforced by only creating variables for rooms
+//! the activity fits in.
+//!
+//! The program is solved by the in-crate branch-and-bound below, which
+//! branches on the room of one activity at a time. Only `solve` depends on
+//! how the model is solved.
+
+use std::collections::HashMap;
+
+use crate::models::{Activity, AssignmentReason, Room, SchedulerOptions, UnscheduledActivity, UnscheduledReason};
+use crate::scheduler::algorithm::{Reservations, book_instructor, instructor_conflict, room_fits_activity, room_tie_break};
+
+/// Upper bound on explored nodes so pathological inputs still terminate;
+/// past it the best solution found so far is returned.
+const MAX_SEARCH_NODES: u64 = 2_000_000;
+
+struct Variable {
+    activity: usize,
+    room: usize,
+    /// Objective coefficient, maximized.
+    weight: i64,
+    /// `(constraint, coefficient)` pairs the variable appears in.
+    rows: Vec<(usize, u32)>,
+}
+
+/// A `sum(coefficient * variable) <= bound` row.
+struct Constraint {
+    bound: u32,
+}
+
+#[derive(PartialEq, Eq, Hash)]
+enum Row {
+    RoomSlot(usize, u32),
+    InstructorSlot(u32, u32),
+    RoomDailySlots(usize),
+}
+
+struct Model {
+    variables: Vec<Variable>,
+    constraints: Vec<Constraint>,
+    /// Variables of each activity, of which at most one is set, by
+    /// decreasing weight.
+    choices: Vec<Vec<usize>>,
+}
+
+impl Model {
+    fn build(activities: &[Activity], rooms: &[Room], options: &SchedulerOptions, reservations: &Reservations) -> Self {
+        // Any placement must outweigh every possible saving in wasted seats
+        let placement_weight = rooms.iter().map(|room| room.capacity as i64).max().unwrap_or(0) + 1;
+
+        let mut constraints = Vec::new();
+        let mut rows: HashMap<Row, usize> = HashMap::new();
+        let mut row = |key: Row, bound: u32, constraints: &mut Vec<Constraint>| {
+            *rows.entry(key).or_insert_with(|| {
+                constraints.push(Constraint { bound });
+                constraints.len() - 1
+            })
+        };
+
+        let mut variables = Vec::new();
+        let mut choices = Vec::new();
+
+        for (activity_index, activity) in activities.iter().enumerate() {
+            let mut choice = Vec::new();
+
+            for (room_index, room) in rooms.iter().enumerate() {
+                if !room_fits_activity(room, activity, options) || reservations.blocks(room, activity) {
+                    continue;
+                }
+
+                let mut variable_rows = Vec::new();
+
+                for slot in &activity.time_slots {
+                    variable_rows.push((row(Row::RoomSlot(room_index, *slot), 1, &mut constraints), 1));
+
+                    if let Some(instructor_id) = activity.instructor_id {
+                        variable_rows.push((row(Row::InstructorSlot(instructor_id, *slot), 1, &mut constraints), 1));
+                    }
+                }
+
+                if let Some(cap) = room.max_daily_slots {
+                    let cap = cap.saturating_sub(reservations.booked_slots(room) as u32);
+                    let coefficient = activity.time_slots.len() as u32;
+                    variable_rows.push((row(Row::RoomDailySlots(room_index), cap, &mut constraints), coefficient));
+                }
+
+                let wasted_seats = room.capacity.saturating_sub(activity.students_count) as i64;
+
+                choice.push(variables.len());
+                variables.push(Variable {
+                    activity: activity_index,
+                    room: room_index,
+                    weight: placement_weight - wasted_seats,
+                    rows: variable_rows,
+                });
+            }
+
+            choice.sort_by(|a: &usize, b: &usize| {
+                let (a, b) = (&variables[*a], &variables[*b]);
+                b.weight.cmp(&a.weight).then(room_tie_break(&rooms[a.room], &rooms[b.room]))
+            });
+            choices.push(choice);
+        }
+
+        Model { variables, constraints, choices }
+    }
+
+    /// The set variables of an optimal solution (or the best one found
+    /// within the node budget).
+    fn solve(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.choices.len()).filter(|a| !self.choices[*a].is_empty()).collect();
+
+        // Fewest options first keeps the search tree narrow
+        order.sort_by_key(|activity| self.choices[*activity].len());
+
+        // Best objective still reachable from each position in `order`
+        let mut bound = vec![0; order.len() + 1];
+        for position in (0..order.len()).rev() {
+            let best_weight = self.variables[self.choices[order[position]][0]].weight;
+            bound[position] = bound[position + 1] + best_weight;
+        }
+
+        let mut search = Search {
+            model: self,
+            order,
+            bound,
+            load: vec![0; self.constraints.len()],
+            current: Vec::new(),
+            best: Vec::new(),
+            best_value: -1,
+            nodes: 0,
+        };
+
+        search.explore(0, 0);
+
+        search.best
+    }
+}
+
+struct Search<'a> {
+    model: &'a Model,
+    order: Vec<usize>,
+    bound: Vec<i64>,
+    load: Vec<u32>,
+    current: Vec<usize>,
+    best: Vec<usize>,
+    best_value: i64,
+    nodes: u64,
+}
+
+impl Search<'_> {
+    fn fits(&self, variable: &Variable) -> bool {
+        variable
+            .rows
+            .iter()
+            .all(|(row, coefficient)| self.load[*row] + coefficient <= self.model.constraints[*row].bound)
+    }
+
+    fn set_load(&mut self, variable: usize, set: bool) {
+        for (row, coefficient) in &self.model.variables[variable].rows {
+            if set {
+                self.load[*row] += coefficient;
+            } else {
+                self.load[*row] -= coefficient;
+            }
+        }
+    }
+
+    fn explore(&mut self, position: usize, value: i64) {
+        self.nodes += 1;
+
+        // Not even the best choice for every remaining activity would beat the best
+        if value + self.bound[position] <= self.best_value {
+            return;
+        }
+
+        if position == self.order.len() {
+            self.best_value = value;
+            self.best = self.current.clone();
+            return;
+        }
+
+        if self.nodes > MAX_SEARCH_NODES {
+            return;
+        }
+
+        let model = self.model;
+
+        for &variable in &model.choices[self.order[position]] {
+            if !self.fits(&model.variables[variable]) {
+                continue;
+            }
+
+            self.set_load(variable, true);
+            self.current.push(variable);
+
+            self.explore(position + 1, value + model.variables[variable].weight);
+
+            self.current.pop();
+            self.set_load(variable, false);
+        }
+
+        self.explore(position + 1, value);
+    }
+}
+
+/// Schedules the activities of a single day with the fewest unscheduled
+/// activities, then the fewest wasted seats.
+pub(crate) fn schedule_day_ilp(
+    activities: Vec<Activity>,
+    rooms: &[Room],
+    options: &SchedulerOptions,
+    reservations: &Reservations,
+) -> (Vec<Activity>, Vec<UnscheduledActivity>) {
+    let mut unscheduled_activities = Vec::new();
+
+    let mut schedulable = Vec::new();
+
+    for activity in activities {
+        match instructor_conflict(&activity, options, &reservations.instructors) {
+            Some(reason) => unscheduled_activities.push(UnscheduledActivity { activity, reason }),
+            None => schedulable.push(activity),
+        }
+    }
+
+    let activities = schedulable;
+
+    let model = Model::build(&activities, rooms, options, reservations);
+
+    let mut assigned_rooms: Vec<Option<usize>> = vec![None; activities.len()];
+    for variable in model.solve() {
+        let variable = &model.variables[variable];
+        assigned_rooms[variable.activity] = Some(variable.room);
+    }
+
+    let mut scheduled_activities = Vec::new();
+    let mut skipped = Vec::new();
+    let mut instructor_occupancy = reservations.instructors.clone();
+
+    for (mut activity, room) in activities.into_iter().zip(assigned_rooms) {
+        match room {
+            Some(room) => {
+                tracing::debug!("Assigning activity {} to room {}.", activity.subject, rooms[room].name);
+                activity.room = rooms[room].clone();
+                activity.assignment_reason = Some(AssignmentReason::IlpSolution);
+                book_instructor(&activity, &mut instructor_occupancy);
+                scheduled_activities.push(activity);
+            }
+            None => skipped.push(activity),
+        }
+    }
+
+    // Activities left out while their instructor teaches something overlapping
+    for activity in skipped {
+        let reason = instructor_conflict(&activity, options, &instructor_occupancy)
+            .unwrap_or(UnscheduledReason::NoAvailableRoom);
+
+        unscheduled_activities.push(UnscheduledActivity { activity, reason });
+    }
+
+    (scheduled_activities, unscheduled_activities)
+}
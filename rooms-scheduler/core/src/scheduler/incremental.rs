@@ -0,0 +1,143 @@
+//! Rescheduling against a previous schedule: activities keep their previous
+//! room while it still fits and is free, and the differences between both
+//! schedules are reported as a change list.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::models::{Activity, ChangeKind, Room, Schedule, ScheduleChange, SchedulerOptions};
+use crate::scheduler::algorithm::{InstructorOccupancy, book_instructor, instructor_conflict, room_fits_activity, shares_parts};
+
+/// Rooms, instructors and daily slot counts taken so far, per day.
+#[derive(Default)]
+struct Bookings {
+    room_slots: HashSet<(u8, u32, u32)>,
+    instructors: HashMap<u8, InstructorOccupancy>,
+    daily_slots: HashMap<(u8, u32), usize>,
+}
+
+impl Bookings {
+    /// Books the room and every room sharing one of its parts.
+    fn book(&mut self, activity: &Activity, room: &Room, rooms: &[Room]) {
+        for blocked in rooms.iter().filter(|r| r.id == room.id || shares_parts(r, room)) {
+            for slot in &activity.time_slots {
+                self.room_slots.insert((activity.day, blocked.id, *slot));
+            }
+        }
+        *self.daily_slots.entry((activity.day, room.id)).or_default() += activity.time_slots.len();
+        book_instructor(activity, self.instructors.entry(activity.day).or_default());
+    }
+
+    fn admits(&self, activity: &Activity, room: &Room, options: &SchedulerOptions) -> bool {
+        let is_free = activity.time_slots
+            .iter()
+            .all(|slot| !self.room_slots.contains(&(activity.day, room.id, *slot)));
+
+        let within_cap = room.max_daily_slots.is_none_or(|cap| {
+            let used = self.daily_slots.get(&(activity.day, room.id)).copied().unwrap_or(0);
+            used + activity.time_slots.len() <= cap as usize
+        });
+
+        let no_bookings = InstructorOccupancy::new();
+        let occupancy = self.instructors.get(&activity.day).unwrap_or(&no_bookings);
+        let instructor_is_free = instructor_conflict(activity, options, occupancy).is_none();
+
+        is_free && within_cap && instructor_is_free
+    }
+}
+
+/// Pins every activity to its room in `previous` when that room still fits
+/// it and no activity ranked higher (or pinned by the request) holds the
+/// room or the instructor in the same slots. `activities` comes sorted like
+/// the greedy work list, so the last one is considered first. Returns the
+/// ids of the activities pinned this way.
+pub(crate) fn keep_previous_rooms(
+    activities: &mut [Activity],
+    rooms: &[Room],
+    previous: &Schedule,
+    options: &SchedulerOptions,
+) -> HashSet<u32> {
+    let previous_rooms: HashMap<u32, u32> = previous
+        .scheduled_activities
+        .iter()
+        .filter(|activity| activity.split_rooms.is_empty())
+        .map(|activity| (activity.id, activity.room.id))
+        .collect();
+
+    let mut bookings = Bookings::default();
+
+    // Pins of the request come first
+    for activity in activities.iter() {
+        if let Some(room) = activity.pinned_room.and_then(|room_id| rooms.iter().find(|room| room.id == room_id)) {
+            bookings.book(activity, room, rooms);
+        }
+    }
+
+    let mut kept = HashSet::new();
+
+    for activity in activities.iter_mut().rev() {
+        if activity.pinned_room.is_some() {
+            continue;
+        }
+
+        let Some(room) = previous_rooms
+            .get(&activity.id)
+            .and_then(|room_id| rooms.iter().find(|room| room.id == *room_id))
+        else {
+            continue;
+        };
+
+        if !room_fits_activity(room, activity, options) || !bookings.admits(activity, room, options) {
+            continue;
+        }
+
+        activity.pinned_room = Some(room.id);
+        bookings.book(activity, room, rooms);
+        kept.insert(activity.id);
+    }
+
+    kept
+}
+
+/// Every activity whose outcome differs between `previous` and `current`.
+pub fn schedule_changes(previous: &Schedule, current: &Schedule) -> Vec<ScheduleChange> {
+    let rooms_of = |schedule: &Schedule| -> HashMap<u32, u32> {
+        schedule
+            .scheduled_activities
+            .iter()
+            .map(|activity| (activity.id, activity.room.id))
+            .collect()
+    };
+
+    let previous_rooms = rooms_of(previous);
+    let current_rooms = rooms_of(current);
+
+    let mut activity_ids: Vec<u32> = previous_rooms.keys().chain(current_rooms.keys()).copied().collect();
+    activity_ids.extend(current.unscheduled_activities.iter().map(|unscheduled| unscheduled.activity.id));
+    activity_ids.sort_unstable();
+    activity_ids.dedup();
+
+    let unscheduled_now: HashSet<u32> = current
+        .unscheduled_activities
+        .iter()
+        .map(|unscheduled| unscheduled.activity.id)
+        .collect();
+
+    activity_ids
+        .into_iter()
+        .filter_map(|activity_id| {
+            let previous_room_id = previous_rooms.get(&activity_id).copied();
+            let room_id = current_rooms.get(&activity_id).copied();
+
+            let kind = match (previous_room_id, room_id) {
+                (Some(previous), Some(current)) if previous == current => return None,
+                (Some(_), Some(_)) => ChangeKind::Moved,
+                (None, Some(_)) => ChangeKind::Added,
+                (Some(_), None) if unscheduled_now.contains(&activity_id) => ChangeKind::Unscheduled,
+                (Some(_), None) => ChangeKind::Removed,
+                (None, None) => return None,
+            };
+
+            Some(ScheduleChange { activity_id, kind, previous_room_id, room_id })
+        })
+        .collect()
+}
@@ -0,0 +1,19 @@
+pub mod algorithm;
+pub mod alternatives;
+pub mod annealing;
+pub mod backtracking;
+pub mod calendar;
+pub mod error;
+#[cfg(feature = "exact")]
+pub mod exact;
+pub mod export;
+pub mod groups;
+#[cfg(feature = "ilp")]
+pub mod ilp;
+pub mod incremental;
+pub mod time_slots;
+pub mod timeline;
+pub mod scoring;
+pub mod validation;
+#[cfg(test)]
+pub(crate) mod test_support;
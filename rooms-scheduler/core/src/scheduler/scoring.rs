@@ -0,0 +1,118 @@
+use crate::models::{Activity, Building, Room, Schedule, ScoringWeights};
+
+/// Soft-preference breakdown of placing an activity in a room.
+/// Higher totals are better.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoomScore {
+    /// Seats between the room capacity and the headcount, in either direction.
+    pub wasted_seats: u32,
+    /// 1.0 when the room is in the activity's preferred building.
+    pub building_match: f64,
+    /// Distance from the preferred building to the room's building, in the
+    /// units of the building coordinates; 0.0 when either is unknown.
+    pub building_distance: f64,
+    /// Share of the activity's preferred features the room offers.
+    pub feature_match: f64,
+    /// 1.0 when the room moves the cohort or instructor to another building
+    /// than the one of their previous activity that day.
+    pub building_change: f64,
+    pub total: f64,
+}
+
+pub fn score_room(room: &Room, activity: &Activity, weights: &ScoringWeights, buildings: &[Building]) -> RoomScore {
+    score_room_after(room, activity, weights, buildings, None)
+}
+
+/// Like `score_room`, also penalizing a move away from `previous_building`,
+/// where the activity's attendees were right before.
+pub fn score_room_after(
+    room: &Room,
+    activity: &Activity,
+    weights: &ScoringWeights,
+    buildings: &[Building],
+    previous_building: Option<&str>,
+) -> RoomScore {
+    let wasted_seats = room.capacity.abs_diff(activity.students_count);
+
+    let building_match = match (&activity.preferred_building, &room.building) {
+        (Some(preferred), Some(building)) if preferred == building => 1.0,
+        _ => 0.0,
+    };
+
+    let located = |name: &String| buildings.iter().find(|building| building.name == *name);
+
+    let building_distance = match (
+        activity.preferred_building.as_ref().and_then(located),
+        room.building.as_ref().and_then(located),
+    ) {
+        (Some(preferred), Some(building)) => preferred.distance_to(building),
+        _ => 0.0,
+    };
+
+    let feature_match = if activity.preferred_features.is_empty() {
+        0.0
+    } else {
+        let matched = activity.preferred_features
+            .iter()
+            .filter(|feature| room.features.contains(feature))
+            .count();
+
+        matched as f64 / activity.preferred_features.len() as f64
+    };
+
+    let building_change = match (previous_building, &room.building) {
+        (Some(previous), Some(building)) if previous != building => 1.0,
+        _ => 0.0,
+    };
+
+    let total = weights.preferred_building * building_match
+        + weights.preferred_features * feature_match
+        - weights.wasted_seats * wasted_seats as f64
+        - weights.building_distance * building_distance
+        - weights.building_change * building_change;
+
+    RoomScore { wasted_seats, building_match, building_distance, feature_match, building_change, total }
+}
+
+/// Sum of the room scores of every scheduled activity; higher is better.
+pub fn schedule_score(schedule: &Schedule, weights: &ScoringWeights, buildings: &[Building]) -> f64 {
+    schedule
+        .scheduled_activities
+        .iter()
+        .map(|activity| score_room(&activity.room, activity, weights, buildings).total)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::test_support::{activity, room};
+
+    #[test]
+    fn default_weights_only_count_wasted_seats() {
+        let mut near = room(1, "A-101", 20);
+        near.building = Some("A".to_string());
+        let mut seminar = activity(1, 25, &[0]);
+        seminar.preferred_building = Some("A".to_string());
+
+        let score = score_room(&near, &seminar, &ScoringWeights::default(), &[]);
+
+        assert_eq!(score.wasted_seats, 5);
+        assert_eq!(score.building_match, 1.0);
+        assert_eq!(score.total, -5.0);
+    }
+
+    #[test]
+    fn feature_match_is_the_share_of_preferred_features() {
+        let mut lab = room(1, "Lab", 30);
+        lab.features = vec!["projector".to_string()];
+        let mut class = activity(1, 30, &[0]);
+        class.preferred_features = vec!["projector".to_string(), "whiteboard".to_string()];
+        let weights = ScoringWeights { preferred_features: 10.0, ..ScoringWeights::default() };
+
+        let score = score_room(&lab, &class, &weights, &[]);
+
+        assert_eq!(score.feature_match, 0.5);
+        assert_eq!(score.total, 5.0);
+    }
+}
@@ -0,0 +1,32 @@
+//! Builders for the records the scheduler tests work with.
+
+use serde_json::json;
+
+use crate::models::{Activity, Room, Schedule};
+
+pub(crate) fn room(id: u32, name: &str, capacity: u32) -> Room {
+    serde_json::from_value(json!({ "id": id, "name": name, "capacity": capacity })).unwrap()
+}
+
+/// An activity of `students_count` students in `time_slots`, on day 0.
+pub(crate) fn activity(id: u32, students_count: u32, time_slots: &[u32]) -> Activity {
+    serde_json::from_value(json!({
+        "id": id,
+        "subject": format!("Subject {}", id),
+        "room": { "id": 0, "name": "", "capacity": 0 },
+        "time_slots": time_slots,
+        "students_count": students_count,
+    }))
+    .unwrap()
+}
+
+/// Id of the room the activity got; panics when it was not scheduled.
+pub(crate) fn room_of(schedule: &Schedule, activity_id: u32) -> u32 {
+    schedule
+        .scheduled_activities
+        .iter()
+        .find(|a| a.id == activity_id)
+        .unwrap_or_else(|| panic!("activity {} is not scheduled", activity_id))
+        .room
+        .id
+}
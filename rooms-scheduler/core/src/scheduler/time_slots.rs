@@ -0,0 +1,197 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::models::{Activity, SlotGrid};
+use crate::scheduler::error::SchedulerError;
+
+/// Slot indices stay below this, a day of one-minute slots, which keeps slot
+/// arithmetic and the per-slot timelines bounded.
+pub const MAX_SLOTS_PER_DAY: u32 = 1_440;
+
+/// Converts activities given as `start_minute` (or `start`) +
+/// `duration_minutes` into slot indices of `slot_length_minutes` each.
+/// Activities that already carry `time_slots` are left untouched.
+pub fn resolve_time_slots(
+    activities: &mut [Activity],
+    slot_length_minutes: Option<u32>,
+    grid_start: Option<DateTime<Utc>>,
+) -> Result<(), SchedulerError> {
+    for activity in activities.iter_mut() {
+        if !activity.time_slots.is_empty() {
+            continue;
+        }
+
+        if let Some(start) = activity.start {
+            place_on_grid(activity, start, grid_start)?;
+        }
+
+        let (start_minute, duration_minutes) = match (activity.start_minute, activity.duration_minutes) {
+            (Some(start), Some(duration)) => (start, duration),
+            (None, None) => return Err(SchedulerError::InvalidInput(
+                format!("Activity {} has no time slots.", activity.id)
+            )),
+            _ => return Err(SchedulerError::InvalidInput(
+                format!("Activity {} must set both start_minute and duration_minutes.", activity.id)
+            )),
+        };
+
+        let slot_length = match slot_length_minutes {
+            Some(length) if length > 0 => length,
+            _ => return Err(SchedulerError::InvalidInput(
+                "slot_length_minutes must be a positive number when activities use minutes.".to_string()
+            )),
+        };
+
+        if duration_minutes == 0 || duration_minutes % slot_length != 0 {
+            return Err(SchedulerError::InvalidInput(format!(
+                "Activity {} lasts {} minutes, which is not a multiple of the {} minute slot length.",
+                activity.id, duration_minutes, slot_length
+            )));
+        }
+
+        if start_minute % slot_length != 0 {
+            return Err(SchedulerError::InvalidInput(format!(
+                "Activity {} starts at minute {}, which is not aligned to the {} minute slot length.",
+                activity.id, start_minute, slot_length
+            )));
+        }
+
+        let first_slot = start_minute / slot_length;
+        let slots_count = duration_minutes / slot_length;
+
+        let end_slot = first_slot
+            .checked_add(slots_count)
+            .filter(|end_slot| *end_slot <= MAX_SLOTS_PER_DAY)
+            .ok_or_else(|| SchedulerError::InvalidInput(format!(
+                "Activity {} runs past slot {}, the last slot of a day.",
+                activity.id, MAX_SLOTS_PER_DAY - 1
+            )))?;
+
+        activity.time_slots = (first_slot..end_slot).collect();
+    }
+
+    Ok(())
+}
+
+/// Turns an actual start time into the day and minute of day it falls on.
+fn place_on_grid(activity: &mut Activity, start: DateTime<Utc>, grid_start: Option<DateTime<Utc>>) -> Result<(), SchedulerError> {
+    if activity.start_minute.is_some() {
+        return Err(SchedulerError::InvalidInput(
+            format!("Activity {} must set either start or start_minute, not both.", activity.id)
+        ));
+    }
+
+    let Some(grid_start) = grid_start else {
+        return Err(SchedulerError::InvalidInput(
+            "grid_start must be set when activities use start.".to_string()
+        ));
+    };
+
+    let offset = start - grid_start;
+
+    if offset < Duration::zero() {
+        return Err(SchedulerError::InvalidInput(
+            format!("Activity {} starts at {}, before grid_start.", activity.id, start.to_rfc3339())
+        ));
+    }
+
+    let day = u8::try_from(offset.num_days()).map_err(|_| SchedulerError::InvalidInput(
+        format!("Activity {} starts more than {} days after grid_start.", activity.id, u8::MAX)
+    ))?;
+
+    let time_of_day = offset - Duration::days(day as i64);
+
+    if time_of_day.num_seconds() % 60 != 0 {
+        return Err(SchedulerError::InvalidInput(
+            format!("Activity {} starts at {}, which is not on a whole minute.", activity.id, start.to_rfc3339())
+        ));
+    }
+
+    activity.day = day;
+    activity.start_minute = Some(time_of_day.num_minutes() as u32);
+
+    Ok(())
+}
+
+/// Sets `start` and `end` from the day and slots every activity runs in.
+pub(crate) fn stamp_times<'a>(activities: impl Iterator<Item = &'a mut Activity>, grid: &SlotGrid) -> Result<(), SchedulerError> {
+    let slot_length = grid.slot_length_minutes as i64;
+
+    for activity in activities {
+        let (Some(first), Some(last)) = (activity.time_slots.iter().min(), activity.time_slots.iter().max()) else {
+            continue;
+        };
+
+        let at_slot = |slot: i64| {
+            slot.checked_mul(slot_length)
+                .and_then(Duration::try_minutes)
+                .zip(Duration::try_days(activity.day as i64))
+                .and_then(|(offset, day)| grid.start.checked_add_signed(day)?.checked_add_signed(offset))
+                .ok_or_else(|| SchedulerError::InvalidInput(format!(
+                    "Activity {} ends past the last representable date.",
+                    activity.id
+                )))
+        };
+
+        let (start, end) = (at_slot(*first as i64)?, at_slot(*last as i64 + 1)?);
+
+        activity.start = Some(start);
+        activity.end = Some(end);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::test_support::activity;
+
+    fn in_minutes(id: u32, start_minute: u32, duration_minutes: u32) -> Activity {
+        let mut activity = activity(id, 10, &[]);
+        activity.start_minute = Some(start_minute);
+        activity.duration_minutes = Some(duration_minutes);
+        activity
+    }
+
+    #[test]
+    fn minutes_become_the_slots_they_cover() {
+        let mut activities = vec![in_minutes(1, 90, 90), activity(2, 10, &[7])];
+
+        resolve_time_slots(&mut activities, Some(30), None).unwrap();
+
+        assert_eq!(activities[0].time_slots, vec![3, 4, 5]);
+        assert_eq!(activities[1].time_slots, vec![7]);
+    }
+
+    #[test]
+    fn duration_not_divisible_by_the_slot_length_is_rejected() {
+        let mut activities = vec![in_minutes(1, 60, 45)];
+
+        let Err(SchedulerError::InvalidInput(message)) = resolve_time_slots(&mut activities, Some(30), None) else {
+            panic!("expected InvalidInput");
+        };
+
+        assert!(message.contains("Activity 1 lasts 45 minutes"), "{}", message);
+        assert!(message.contains("30 minute slot length"), "{}", message);
+    }
+
+    #[test]
+    fn durations_past_a_day_are_rejected_without_overflowing() {
+        for (start_minute, duration_minutes) in [(u32::MAX, u32::MAX), (0, u32::MAX), (1_380, 120)] {
+            let mut activities = vec![in_minutes(1, start_minute, duration_minutes)];
+
+            let result = resolve_time_slots(&mut activities, Some(1), None);
+
+            assert!(matches!(result, Err(SchedulerError::InvalidInput(_))), "{} + {}", start_minute, duration_minutes);
+            assert!(activities[0].time_slots.is_empty());
+        }
+    }
+
+    #[test]
+    fn times_past_the_last_date_are_rejected() {
+        let grid = SlotGrid { start: DateTime::<Utc>::MAX_UTC - Duration::days(1), slot_length_minutes: u32::MAX };
+        let mut activities = [activity(1, 10, &[MAX_SLOTS_PER_DAY - 1])];
+
+        assert!(stamp_times(activities.iter_mut(), &grid).is_err());
+    }
+}
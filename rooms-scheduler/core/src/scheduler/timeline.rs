@@ -0,0 +1,112 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::models::{Activity, DaySchedule, Room, RoomTimeline, TimelineSlot, UnscheduledActivity, Weekday};
+
+/// Lays out, for every room, which activity holds it in each slot spanned
+/// by the activities of each day.
+pub fn build_room_timelines<'a>(
+    rooms: &[Room],
+    scheduled_activities: &[Activity],
+    all_activities: impl Iterator<Item = &'a Activity>,
+) -> Vec<RoomTimeline> {
+    let mut slot_ranges: BTreeMap<u8, (u32, u32)> = BTreeMap::new();
+
+    for activity in all_activities {
+        for slot in &activity.time_slots {
+            let range = slot_ranges.entry(activity.day).or_insert((*slot, *slot));
+            range.0 = range.0.min(*slot);
+            range.1 = range.1.max(*slot);
+        }
+    }
+
+    let mut occupancy: HashMap<(u32, u8, u32), u32> = HashMap::new();
+
+    for activity in scheduled_activities {
+        for room in activity.assigned_rooms() {
+            for slot in &activity.time_slots {
+                occupancy.insert((room.id, activity.day, *slot), activity.id);
+            }
+        }
+    }
+
+    rooms
+        .iter()
+        .map(|room| RoomTimeline {
+            room_id: room.id,
+            room_name: room.name.clone(),
+            slots: slot_ranges
+                .iter()
+                .flat_map(|(day, (first, last))| (*first..=*last).map(move |slot| (*day, slot)))
+                .map(|(day, slot)| TimelineSlot {
+                    day,
+                    slot,
+                    activity_id: occupancy.get(&(room.id, day, slot)).copied(),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Groups the outcome of every activity by day.
+pub fn group_by_day(scheduled_activities: &[Activity], unscheduled_activities: &[UnscheduledActivity]) -> Vec<DaySchedule> {
+    let mut days: BTreeMap<u8, DaySchedule> = BTreeMap::new();
+
+    let day_schedule = |day: u8| DaySchedule {
+        day,
+        weekday: Weekday::of_day(day),
+        scheduled_activity_ids: Vec::new(),
+        unscheduled_activity_ids: Vec::new(),
+    };
+
+    for activity in scheduled_activities {
+        days.entry(activity.day).or_insert_with(|| day_schedule(activity.day)).scheduled_activity_ids.push(activity.id);
+    }
+
+    for unscheduled in unscheduled_activities {
+        let day = unscheduled.activity.day;
+        days.entry(day).or_insert_with(|| day_schedule(day)).unscheduled_activity_ids.push(unscheduled.activity.id);
+    }
+
+    days.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::test_support::{activity, room};
+
+    fn in_room(id: u32, slots: &[u32], room: &Room) -> Activity {
+        let mut activity = activity(id, 10, slots);
+        activity.room = room.clone();
+        activity
+    }
+
+    fn slots(timeline: &RoomTimeline) -> Vec<(u8, u32, Option<u32>)> {
+        timeline.slots.iter().map(|slot| (slot.day, slot.slot, slot.activity_id)).collect()
+    }
+
+    #[test]
+    fn every_room_lists_free_and_occupied_slots() {
+        let rooms = vec![room(1, "A-101", 30), room(2, "B-201", 30)];
+        let scheduled = vec![in_room(1, &[1, 2], &rooms[0]), in_room(2, &[4], &rooms[1])];
+
+        let timelines = build_room_timelines(&rooms, &scheduled, scheduled.iter());
+
+        assert_eq!(timelines.len(), 2);
+        assert_eq!((timelines[0].room_id, timelines[0].room_name.as_str()), (1, "A-101"));
+        assert_eq!(slots(&timelines[0]), vec![(0, 1, Some(1)), (0, 2, Some(1)), (0, 3, None), (0, 4, None)]);
+        assert_eq!(slots(&timelines[1]), vec![(0, 1, None), (0, 2, None), (0, 3, None), (0, 4, Some(2))]);
+    }
+
+    #[test]
+    fn unscheduled_activities_widen_the_range_and_days_stay_apart() {
+        let rooms = vec![room(1, "A-101", 30)];
+        let scheduled = vec![in_room(1, &[1], &rooms[0])];
+        let mut tuesday = activity(2, 10, &[0]);
+        tuesday.day = 1;
+
+        let timelines = build_room_timelines(&rooms, &scheduled, scheduled.iter().chain([&tuesday]));
+
+        assert_eq!(slots(&timelines[0]), vec![(0, 1, Some(1)), (1, 0, None)]);
+    }
+}
@@ -0,0 +1,369 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::models::{ActivitiesRequest, Activity, Room};
+use crate::scheduler::alternatives::MAX_ALTERNATIVES;
+use crate::scheduler::error::{SchedulerError, ValidationError};
+use crate::scheduler::time_slots::MAX_SLOTS_PER_DAY;
+
+/// Rejects inputs that would make the scheduler produce nonsensical results,
+/// reporting every violation at once.
+pub fn validate_request(request: &ActivitiesRequest) -> Result<(), SchedulerError> {
+    let mut errors = Vec::new();
+    let mut room_ids = HashSet::new();
+
+    for (index, room) in request.rooms.iter().enumerate() {
+        if room.capacity == 0 {
+            errors.push(violation(
+                format!("rooms[{}].capacity", index),
+                format!("Room {} has a capacity of zero.", room.id),
+            ));
+        }
+
+        // The scheduler frees and books rooms by id, so duplicates would corrupt availability
+        if !room_ids.insert(room.id) {
+            errors.push(violation(
+                format!("rooms[{}].id", index),
+                format!("Room id {} is duplicated.", room.id),
+            ));
+        }
+    }
+
+    for (index, room) in request.rooms.iter().enumerate() {
+        for part_id in &room.combines {
+            let message = match request.rooms.iter().find(|part| part.id == *part_id) {
+                _ if *part_id == room.id => Some(format!("Room {} cannot combine itself.", room.id)),
+                None => Some(format!("Room {} combines unknown room {}.", room.id, part_id)),
+                Some(part) if !part.combines.is_empty() => Some(format!(
+                    "Room {} combines room {}, which is itself a combination.",
+                    room.id, part_id
+                )),
+                Some(_) => None,
+            };
+
+            if let Some(message) = message {
+                errors.push(violation(format!("rooms[{}].combines", index), message));
+            }
+        }
+    }
+
+    let mut building_names = HashSet::new();
+
+    for (index, building) in request.buildings.iter().enumerate() {
+        if !building_names.insert(&building.name) {
+            errors.push(violation(
+                format!("buildings[{}].name", index),
+                format!("Building {} is duplicated.", building.name),
+            ));
+        }
+    }
+
+    let mut instructor_ids = HashSet::new();
+
+    for (index, instructor) in request.instructors.iter().enumerate() {
+        if !instructor_ids.insert(instructor.id) {
+            errors.push(violation(
+                format!("instructors[{}].id", index),
+                format!("Instructor id {} is duplicated.", instructor.id),
+            ));
+        }
+    }
+
+    let activity_days: HashMap<u32, u8> = request.activities.iter().map(|activity| (activity.id, activity.day)).collect();
+    let mut activity_ids = HashSet::new();
+
+    for (index, activity) in request.activities.iter().enumerate() {
+        if activity.students_count == 0 {
+            errors.push(violation(
+                format!("activities[{}].students_count", index),
+                format!("Activity {} has zero students.", activity.id),
+            ));
+        }
+
+        if !activity_ids.insert(activity.id) {
+            errors.push(violation(
+                format!("activities[{}].id", index),
+                format!("Activity id {} is duplicated.", activity.id),
+            ));
+        }
+
+        if let Some(room_id) = activity.pinned_room
+            && !room_ids.contains(&room_id)
+        {
+            errors.push(violation(
+                format!("activities[{}].pinned_room", index),
+                format!("Activity {} is pinned to unknown room {}.", activity.id, room_id),
+            ));
+        }
+
+        if let Some(linked_id) = activity.same_building_as {
+            let message = match activity_days.get(&linked_id) {
+                _ if linked_id == activity.id => Some(format!("Activity {} cannot share a building with itself.", activity.id)),
+                None => Some(format!("Activity {} must share a building with unknown activity {}.", activity.id, linked_id)),
+                Some(day) if *day != activity.day => Some(format!(
+                    "Activity {} must share a building with activity {}, which is on another day.",
+                    activity.id, linked_id
+                )),
+                Some(_) => None,
+            };
+
+            if let Some(message) = message {
+                errors.push(violation(format!("activities[{}].same_building_as", index), message));
+            }
+        }
+
+        if let Some(instructor_id) = activity.instructor_id
+            && !instructor_ids.contains(&instructor_id)
+        {
+            errors.push(violation(
+                format!("activities[{}].instructor_id", index),
+                format!("Activity {} references unknown instructor {}.", activity.id, instructor_id),
+            ));
+        }
+
+        if activity.time_slots.is_empty() && activity.start_minute.is_none() && activity.start.is_none() && activity.duration_minutes.is_none() {
+            errors.push(violation(
+                format!("activities[{}].time_slots", index),
+                format!("Activity {} has no time slots.", activity.id),
+            ));
+        }
+
+        let field = format!("activities[{}].time_slots", index);
+        errors.extend(validate_time_slots(field, activity.id, &activity.time_slots, request.require_contiguous_slots));
+
+        for (window, time_slots) in activity.alternative_time_slots.iter().enumerate() {
+            let field = format!("activities[{}].alternative_time_slots[{}]", index, window);
+
+            if time_slots.is_empty() {
+                errors.push(violation(
+                    field.clone(),
+                    format!("Activity {} has an empty alternative time window.", activity.id),
+                ));
+            }
+
+            errors.extend(validate_time_slots(field, activity.id, time_slots, request.require_contiguous_slots));
+        }
+    }
+
+    if request.alternatives > MAX_ALTERNATIVES {
+        errors.push(violation(
+            "alternatives".to_string(),
+            format!("At most {} alternative schedules can be requested.", MAX_ALTERNATIVES),
+        ));
+    }
+
+    let annealing = &request.annealing;
+
+    if !(annealing.initial_temperature > 0.0 && annealing.initial_temperature.is_finite()) {
+        errors.push(violation(
+            "annealing.initial_temperature".to_string(),
+            "The initial temperature must be a positive number.".to_string(),
+        ));
+    }
+
+    if !(annealing.cooling_rate > 0.0 && annealing.cooling_rate < 1.0) {
+        errors.push(violation(
+            "annealing.cooling_rate".to_string(),
+            "The cooling rate must be strictly between 0 and 1.".to_string(),
+        ));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(SchedulerError::Validation(errors))
+    }
+}
+
+/// Rejects a room that cannot be stored in an inventory.
+pub fn validate_room(room: &Room) -> Result<(), SchedulerError> {
+    if room.capacity == 0 {
+        return Err(SchedulerError::InvalidInput(format!("Room {} has a capacity of zero.", room.id)));
+    }
+
+    Ok(())
+}
+
+/// Rejects an activity that cannot be stored in an inventory: one without
+/// students, or without a way to tell its time slots.
+pub fn validate_activity(activity: &Activity) -> Result<(), SchedulerError> {
+    if activity.students_count == 0 {
+        return Err(SchedulerError::InvalidInput(format!("Activity {} has zero students.", activity.id)));
+    }
+
+    if activity.time_slots.is_empty() && (activity.start_minute.is_none() || activity.duration_minutes.is_none()) {
+        return Err(SchedulerError::InvalidInput(format!(
+            "Activity {} needs time_slots or both start_minute and duration_minutes.",
+            activity.id
+        )));
+    }
+
+    Ok(())
+}
+
+fn violation(field: String, message: String) -> ValidationError {
+    ValidationError { field, message }
+}
+
+/// Slot lists must stay within a day, must not repeat a slot and must be
+/// ascending, since occupancy holds each slot once and the sweep starts an
+/// activity at its first listed slot; gaps are fine unless contiguity is
+/// requested.
+fn validate_time_slots(field: String, activity_id: u32, time_slots: &[u32], require_contiguous: bool) -> Option<ValidationError> {
+    let mut seen = HashSet::new();
+
+    if let Some(slot) = time_slots.iter().find(|slot| **slot >= MAX_SLOTS_PER_DAY) {
+        return Some(violation(
+            field,
+            format!("Activity {} uses time slot {}, past slot {}, the last slot of a day.", activity_id, slot, MAX_SLOTS_PER_DAY - 1),
+        ));
+    }
+
+    if let Some(slot) = time_slots.iter().find(|slot| !seen.insert(**slot)) {
+        return Some(violation(field, format!("Activity {} lists time slot {} more than once.", activity_id, slot)));
+    }
+
+    if time_slots.windows(2).any(|pair| pair[1] < pair[0]) {
+        return Some(violation(
+            field,
+            format!("Activity {} has time slots {:?}, which are not in ascending order.", activity_id, time_slots),
+        ));
+    }
+
+    if require_contiguous && time_slots.windows(2).any(|pair| pair[1] != pair[0] + 1) {
+        return Some(violation(
+            field,
+            format!("Activity {} has time slots {:?}, which are not consecutive.", activity_id, time_slots),
+        ));
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::scheduler::test_support::{activity, room};
+
+    fn request(rooms: Vec<Room>, activities: Vec<Activity>) -> ActivitiesRequest {
+        serde_json::from_value(json!({ "rooms": rooms, "activities": activities })).unwrap()
+    }
+
+    /// `(field, message)` of every violation, in order.
+    fn violations(request: &ActivitiesRequest) -> Vec<(String, String)> {
+        match validate_request(request) {
+            Err(SchedulerError::Validation(errors)) => errors.into_iter().map(|error| (error.field, error.message)).collect(),
+            other => panic!("expected violations, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn valid_request_passes() {
+        let request = request(vec![room(1, "A-101", 30)], vec![activity(1, 20, &[0]), activity(2, 20, &[1])]);
+
+        assert!(validate_request(&request).is_ok());
+    }
+
+    #[test]
+    fn zero_capacity_room_is_rejected() {
+        let request = request(vec![room(1, "A-101", 30), room(2, "Closet", 0)], vec![activity(1, 20, &[0])]);
+
+        assert_eq!(violations(&request), vec![("rooms[1].capacity".to_string(), "Room 2 has a capacity of zero.".to_string())]);
+    }
+
+    #[test]
+    fn activity_without_students_is_rejected() {
+        let request = request(vec![room(1, "A-101", 30)], vec![activity(7, 0, &[0])]);
+
+        assert_eq!(violations(&request), vec![("activities[0].students_count".to_string(), "Activity 7 has zero students.".to_string())]);
+    }
+
+    #[test]
+    fn duplicate_activity_id_is_rejected() {
+        let request = request(vec![room(1, "A-101", 30)], vec![activity(3, 10, &[0]), activity(3, 10, &[1])]);
+
+        assert_eq!(violations(&request), vec![("activities[1].id".to_string(), "Activity id 3 is duplicated.".to_string())]);
+    }
+
+    #[test]
+    fn time_slot_past_a_day_is_rejected() {
+        let request = request(vec![room(1, "A-101", 30)], vec![activity(1, 10, &[0, MAX_SLOTS_PER_DAY])]);
+
+        let violations = violations(&request);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].0, "activities[0].time_slots");
+        assert!(violations[0].1.contains("past slot 1439"), "{}", violations[0].1);
+    }
+
+    #[test]
+    fn stored_rooms_and_activities_are_checked_one_by_one() {
+        assert!(validate_room(&room(1, "A-101", 30)).is_ok());
+        assert!(matches!(validate_room(&room(1, "Closet", 0)), Err(SchedulerError::InvalidInput(_))));
+        assert!(matches!(validate_activity(&activity(1, 0, &[0])), Err(SchedulerError::InvalidInput(_))));
+        assert!(matches!(validate_activity(&activity(1, 10, &[])), Err(SchedulerError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn duplicate_room_id_is_rejected() {
+        let request = request(vec![room(1, "A-101", 30), room(1, "B-201", 40)], vec![activity(1, 20, &[0])]);
+
+        assert_eq!(violations(&request), vec![("rooms[1].id".to_string(), "Room id 1 is duplicated.".to_string())]);
+    }
+
+    #[test]
+    fn rooms_may_share_a_name() {
+        let request = request(vec![room(1, "Lab", 30), room(2, "Lab", 30)], vec![activity(1, 20, &[0])]);
+
+        assert!(validate_request(&request).is_ok());
+    }
+
+    #[test]
+    fn duplicated_slot_is_rejected_naming_the_activity() {
+        let request = request(vec![room(1, "A-101", 30)], vec![activity(5, 10, &[2, 2, 3])]);
+
+        assert_eq!(
+            violations(&request),
+            vec![("activities[0].time_slots".to_string(), "Activity 5 lists time slot 2 more than once.".to_string())]
+        );
+    }
+
+    #[test]
+    fn gaps_are_rejected_only_when_contiguity_is_required() {
+        let mut request = request(vec![room(1, "A-101", 30)], vec![activity(5, 10, &[1, 3])]);
+        assert!(validate_request(&request).is_ok());
+
+        request.require_contiguous_slots = true;
+        assert!(violations(&request)[0].1.contains("not consecutive"));
+    }
+
+    #[test]
+    fn clean_slot_list_passes() {
+        let mut request = request(vec![room(1, "A-101", 30)], vec![activity(5, 10, &[1, 2, 3])]);
+        request.require_contiguous_slots = true;
+
+        assert!(validate_request(&request).is_ok());
+    }
+
+    #[test]
+    fn every_violation_is_reported_together() {
+        let request = request(
+            vec![room(1, "A-101", 0), room(1, "B-201", 40)],
+            vec![activity(1, 0, &[0]), activity(1, 10, &[]), activity(2, 10, &[4, 4])],
+        );
+
+        let fields: Vec<String> = violations(&request).into_iter().map(|(field, _)| field).collect();
+
+        assert_eq!(
+            fields,
+            vec![
+                "rooms[0].capacity",
+                "rooms[1].id",
+                "activities[0].students_count",
+                "activities[1].id",
+                "activities[1].time_slots",
+                "activities[2].time_slots",
+            ]
+        );
+    }
+}
@@ -0,0 +1,11 @@
+-- Schema for persisted scheduling runs, mirroring `StoredSchedule`.
+CREATE TABLE IF NOT EXISTS schedules (
+    schedule_id            UUID PRIMARY KEY,
+    user_id                BIGINT      NOT NULL,
+    created_at             TIMESTAMPTZ NOT NULL DEFAULT now(),
+    request                JSONB       NOT NULL,
+    scheduled_activities   JSONB       NOT NULL,
+    unscheduled_activities JSONB       NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS schedules_user_id_idx ON schedules (user_id, created_at DESC);
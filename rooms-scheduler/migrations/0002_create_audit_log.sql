@@ -0,0 +1,20 @@
+-- Append-only record of scheduling runs, mirroring `AuditEntry`. Grant the
+-- service INSERT and SELECT only.
+CREATE TABLE IF NOT EXISTS audit_log (
+    audit_id     UUID PRIMARY KEY,
+    occurred_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    user_id      BIGINT      NOT NULL,
+    sub          TEXT        NOT NULL,
+    operation    TEXT        NOT NULL,
+    job_id       UUID,
+    activities   INTEGER     NOT NULL,
+    rooms        INTEGER     NOT NULL,
+    outcome      TEXT        NOT NULL,
+    scheduled    INTEGER,
+    unscheduled  INTEGER,
+    error        TEXT,
+    duration_ms  BIGINT      NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS audit_log_occurred_at_idx ON audit_log (occurred_at DESC);
+CREATE INDEX IF NOT EXISTS audit_log_user_id_idx ON audit_log (user_id, occurred_at DESC);
@@ -0,0 +1,77 @@
+//! Audit entries for scheduling runs. A request opens an `AuditContext`
+//! before it runs the scheduler and records the outcome where the run ends,
+//! so runs that outlive their request's timeout are still recorded.
+
+use std::{fmt::Display, time::Instant};
+
+use chrono::{DateTime, Utc};
+use rooms_scheduler_core::{Schedule, models::Claims};
+
+use crate::ids::new_id;
+use crate::repositories::audit::{AuditEntry, AuditOutcome, audit_repository};
+
+pub struct AuditContext {
+    occurred_at: DateTime<Utc>,
+    started_at: Instant,
+    user_id: i64,
+    sub: String,
+    operation: &'static str,
+    job_id: Option<String>,
+    activities: usize,
+    rooms: usize,
+}
+
+impl AuditContext {
+    pub fn new(claims: &Claims, operation: &'static str, activities: usize, rooms: usize) -> Self {
+        Self {
+            occurred_at: Utc::now(),
+            started_at: Instant::now(),
+            user_id: claims.user_id,
+            sub: claims.sub.clone(),
+            operation,
+            job_id: None,
+            activities,
+            rooms,
+        }
+    }
+
+    /// Ties the entry to the async job running it.
+    pub fn with_job(mut self, job_id: &str) -> Self {
+        self.job_id = Some(job_id.to_string());
+        self
+    }
+
+    pub fn record<E: Display>(self, result: &Result<Schedule, E>) {
+        match result {
+            Ok(schedule) => self.completed(schedule),
+            Err(err) => self.failed(err),
+        }
+    }
+
+    pub fn completed(self, schedule: &Schedule) {
+        let (scheduled, unscheduled) = (schedule.scheduled_activities.len(), schedule.unscheduled_activities.len());
+        self.append(AuditOutcome::Completed, Some((scheduled, unscheduled)), None);
+    }
+
+    pub fn failed(self, err: impl Display) {
+        self.append(AuditOutcome::Failed, None, Some(err.to_string()));
+    }
+
+    fn append(self, outcome: AuditOutcome, counts: Option<(usize, usize)>, error: Option<String>) {
+        audit_repository().append(AuditEntry {
+            audit_id: new_id(),
+            occurred_at: self.occurred_at,
+            user_id: self.user_id,
+            sub: self.sub,
+            operation: self.operation.to_string(),
+            job_id: self.job_id,
+            activities: self.activities,
+            rooms: self.rooms,
+            outcome,
+            scheduled: counts.map(|(scheduled, _)| scheduled),
+            unscheduled: counts.map(|(_, unscheduled)| unscheduled),
+            error,
+            duration_ms: self.started_at.elapsed().as_millis() as u64,
+        });
+    }
+}
@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use crate::config;
+
+use axum_responses::{Result, http::HttpResponse};
+
+/// `SCHEDULE_TIMEOUT_MS` as a duration, or `None` when the timeout is disabled.
+pub fn schedule_timeout() -> Option<Duration> {
+    match config::load_env().schedule_timeout_ms {
+        0 => None,
+        ms => Some(Duration::from_millis(ms)),
+    }
+}
+
+/// Runs CPU-bound scheduling work on the blocking pool so it cannot starve
+/// the async runtime, failing with 504 once `SCHEDULE_TIMEOUT_MS` elapses.
+/// A timed-out job keeps its blocking thread until it finishes, but its
+/// result is discarded. The job logs within the caller's span.
+pub async fn run_blocking<T, F>(job: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let span = tracing::Span::current();
+    let task = tokio::task::spawn_blocking(move || span.in_scope(job));
+
+    let joined = match schedule_timeout() {
+        Some(timeout) => tokio::time::timeout(timeout, task).await.map_err(|_| {
+            tracing::warn!(timeout_ms = timeout.as_millis() as u64, "Scheduling run exceeded its timeout, giving up");
+            HttpResponse::GatewayTimeout()
+                .error(format!("Scheduling did not finish within {} ms.", timeout.as_millis()))
+        })?,
+        None => task.await,
+    };
+
+    joined.map_err(|err| HttpResponse::InternalServerError().error(format!("Scheduling task failed: {}", err)))
+}
+
+/// Runs a schedule store call on the blocking pool, since the Postgres
+/// store waits on the network and would stall every request of the worker.
+/// A failing store answers 503; the cause is only logged, as it may name
+/// internal hosts.
+pub async fn run_store<T, F>(call: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> std::result::Result<T, String> + Send + 'static,
+{
+    let span = tracing::Span::current();
+    let result = tokio::task::spawn_blocking(move || span.in_scope(call))
+        .await
+        .map_err(|err| HttpResponse::InternalServerError().error(format!("Schedule store task failed: {}", err)))?;
+
+    result.map_err(|err| {
+        tracing::error!(error = %err, "Schedule store call failed");
+        HttpResponse::ServiceUnavailable().error("The schedule store is unavailable; try again later.")
+    })
+}
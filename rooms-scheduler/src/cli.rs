@@ -0,0 +1,198 @@
+//! Runs the scheduler on a request file instead of serving HTTP:
+//!
+//! ```text
+//! rooms-scheduler --schedule input.json --out result.json
+//! ```
+//!
+//! The input is the body `POST /api/v1/rooms/schedule` takes, with the
+//! configured scheduler defaults applied; the output is the computed
+//! schedule, without timings, so the same input always writes the same
+//! file.
+
+use std::{
+    io::{Read, Write},
+    time::Instant,
+};
+
+use rooms_scheduler_core::{ActivitiesRequest, Schedule, resolve_time_slots, run_scheduler, schedule_to_csv, schedule_to_xlsx, validate_request};
+use serde_json::Value;
+
+use crate::config;
+use crate::extractors::apply_defaults;
+
+const USAGE: &str = "\
+Usage: rooms-scheduler [--schedule <input.json> [options]]
+
+Without arguments, starts the HTTP service.
+
+Options:
+  --schedule <path>       Scheduling request to run; `-` reads standard input
+  --out <path>            Where to write the schedule; standard output when omitted or `-`
+  --format <format>       json, csv or xlsx; taken from the --out extension by default
+  --fail-on-unscheduled   Exit with 3 when any activity is left unscheduled
+  -h, --help              Print this help
+  -V, --version           Print the version";
+
+/// Exit codes other than 0.
+const EXIT_FAILED: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_UNSCHEDULED: i32 = 3;
+
+#[derive(Clone, Copy)]
+enum Format {
+    Json,
+    Csv,
+    Xlsx,
+}
+
+struct Options {
+    input: String,
+    output: Option<String>,
+    format: Format,
+    fail_on_unscheduled: bool,
+}
+
+/// Runs the command line given in `args`, without the program name, and
+/// returns the exit code; `None` when there are no arguments and the
+/// service should start.
+pub fn run(args: Vec<String>) -> Option<i32> {
+    if args.is_empty() {
+        return None;
+    }
+
+    let options = match parse_args(args) {
+        Ok(Some(options)) => options,
+        Ok(None) => return Some(0),
+        Err(err) => {
+            eprintln!("error: {}\nRun with --help for usage.", err);
+            return Some(EXIT_USAGE);
+        }
+    };
+
+    Some(match schedule_file(&options) {
+        Ok(schedule) if options.fail_on_unscheduled && !schedule.unscheduled_activities.is_empty() => EXIT_UNSCHEDULED,
+        Ok(_) => 0,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            EXIT_FAILED
+        }
+    })
+}
+
+/// `None` when only help or the version was asked for.
+fn parse_args(args: Vec<String>) -> Result<Option<Options>, String> {
+    let mut input = None;
+    let mut output = None;
+    let mut format = None;
+    let mut fail_on_unscheduled = false;
+
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        // `--name=value` and `--name value` are the same
+        let (name, inline) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name.to_string(), Some(value.to_string())),
+            _ => (arg, None),
+        };
+
+        let mut value = |name: &str| {
+            inline.clone().or_else(|| args.next()).ok_or_else(|| format!("{} needs a value", name))
+        };
+
+        match name.as_str() {
+            "--schedule" => input = Some(value("--schedule")?),
+            "--out" => output = Some(value("--out")?).filter(|path| path != "-"),
+            "--format" => format = Some(parse_format(&value("--format")?)?),
+            "--fail-on-unscheduled" => fail_on_unscheduled = true,
+            // A closed pipe (`| head`) is not an error
+            "-h" | "--help" => {
+                let _ = writeln!(std::io::stdout(), "{}", USAGE);
+                return Ok(None);
+            }
+            "-V" | "--version" => {
+                let _ = writeln!(std::io::stdout(), "{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+                return Ok(None);
+            }
+            other => return Err(format!("unknown argument `{}`", other)),
+        }
+    }
+
+    let input = input.ok_or("--schedule is required")?;
+
+    let format = match (format, output.as_deref().and_then(|path| path.rsplit_once('.'))) {
+        (Some(format), _) => format,
+        (None, Some((_, extension))) => parse_format(extension).unwrap_or(Format::Json),
+        (None, None) => Format::Json,
+    };
+
+    Ok(Some(Options { input, output, format, fail_on_unscheduled }))
+}
+
+fn parse_format(format: &str) -> Result<Format, String> {
+    match format.to_ascii_lowercase().as_str() {
+        "json" => Ok(Format::Json),
+        "csv" => Ok(Format::Csv),
+        "xlsx" => Ok(Format::Xlsx),
+        other => Err(format!("unknown format `{}`; expected json, csv or xlsx", other)),
+    }
+}
+
+fn schedule_file(options: &Options) -> Result<Schedule, String> {
+    let request = read_request(&options.input)?;
+
+    if !request.activity_ids.is_empty() || !request.room_ids.is_empty() {
+        return Err("activity_ids and room_ids refer to the service's stored inventory; list the activities and rooms in the file".to_string());
+    }
+
+    validate_request(&request).map_err(|err| err.to_string())?;
+
+    let request = request.normalized();
+    let scheduler_options = request.scheduler_options();
+
+    let mut activities = request.activities;
+    resolve_time_slots(&mut activities, request.slot_length_minutes, request.grid_start).map_err(|err| err.to_string())?;
+
+    let total = activities.len();
+    let started_at = Instant::now();
+    let schedule = run_scheduler(activities, request.rooms, &scheduler_options).map_err(|err| err.to_string())?;
+
+    eprintln!(
+        "Scheduled {} of {} activities in {} ms",
+        schedule.scheduled_activities.len(),
+        total,
+        started_at.elapsed().as_millis()
+    );
+
+    let bytes = match options.format {
+        Format::Json => {
+            let mut json = serde_json::to_vec_pretty(&schedule).map_err(|err| err.to_string())?;
+            json.push(b'\n');
+            json
+        }
+        Format::Csv => schedule_to_csv(&schedule.scheduled_activities, &schedule.unscheduled_activities).into_bytes(),
+        Format::Xlsx => schedule_to_xlsx(&schedule.scheduled_activities, &schedule.unscheduled_activities),
+    };
+
+    match &options.output {
+        Some(path) => std::fs::write(path, bytes).map_err(|err| format!("cannot write {}: {}", path, err))?,
+        None => std::io::stdout().write_all(&bytes).map_err(|err| err.to_string())?,
+    }
+
+    Ok(schedule)
+}
+
+fn read_request(path: &str) -> Result<ActivitiesRequest, String> {
+    let source = match path {
+        "-" => {
+            let mut source = Vec::new();
+            std::io::stdin().read_to_end(&mut source).map(|_| source)
+        }
+        path => std::fs::read(path),
+    }
+    .map_err(|err| format!("cannot read {}: {}", path, err))?;
+
+    let mut body: Value = serde_json::from_slice(&source).map_err(|err| format!("{} is not valid JSON: {}", path, err))?;
+    apply_defaults(&mut body, &config::load_env().scheduler_defaults);
+
+    serde_path_to_error::deserialize(body).map_err(|err| format!("{} at `{}`: {}", path, err.path(), err.inner()))
+}
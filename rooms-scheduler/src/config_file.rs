@@ -0,0 +1,978 @@
+//! Reads the `CONFIG_FILE` settings into a JSON value, from TOML, YAML or
+//! JSON by file extension. The TOML and YAML readers cover what a settings
+//! file needs rather than the whole languages:
+//!
+//! - TOML: tables, arrays of tables, dotted and quoted keys, basic, literal
+//!   and multi-line strings, integers, floats, booleans, arrays and inline
+//!   tables. Dates and times are not supported.
+//! - YAML: block mappings and sequences, plain and quoted scalars, `|` and
+//!   `>` block scalars, and single-line flow sequences and mappings.
+//!   Anchors, tags and multiple documents are not supported.
+
+use std::path::Path;
+
+use serde_json::{Map, Number, Value};
+
+pub fn load(path: &str) -> Result<Value, String> {
+    let source = std::fs::read_to_string(path).map_err(|err| format!("cannot read {}: {}", path, err))?;
+
+    let extension = Path::new(path).extension().and_then(|extension| extension.to_str()).unwrap_or_default();
+
+    let value = match extension.to_ascii_lowercase().as_str() {
+        "toml" => parse_toml(&source),
+        "yaml" | "yml" => parse_yaml(&source),
+        "json" => serde_json::from_str(&source).map_err(|err| err.to_string()),
+        _ => return Err(format!("{} must end in .toml, .yaml, .yml or .json", path)),
+    };
+
+    value.map_err(|err| format!("{}: {}", path, err))
+}
+
+/// The value at a dotted `key`, such as `jwt.secret`.
+pub fn lookup<'a>(root: &'a Value, key: &str) -> Option<&'a Value> {
+    key.split('.').try_fold(root, |value, segment| value.get(segment))
+}
+
+// TOML
+
+pub fn parse_toml(source: &str) -> Result<Value, String> {
+    let mut parser = TomlParser { chars: source.chars().collect(), pos: 0, line: 1 };
+    let mut root = Map::new();
+    let mut table: Vec<String> = Vec::new();
+
+    loop {
+        parser.skip_blank_lines();
+        let Some(c) = parser.peek() else {
+            break;
+        };
+
+        if c == '[' {
+            parser.advance();
+            let array = parser.eat('[');
+
+            parser.skip_spaces();
+            let path = parser.key()?;
+            parser.skip_spaces();
+
+            if !parser.eat(']') || (array && !parser.eat(']')) {
+                return Err(parser.error("expected `]` after the table name"));
+            }
+
+            if array {
+                let (last, parents) = path.split_last().expect("keys have a segment");
+                let parent = table_mut(&mut root, parents).map_err(|err| parser.error(&err))?;
+                let entry = parent.entry(last.clone()).or_insert_with(|| Value::Array(Vec::new()));
+                let Value::Array(tables) = entry else {
+                    return Err(parser.error(&format!("`{}` is not an array of tables", path.join("."))));
+                };
+                tables.push(Value::Object(Map::new()));
+            } else {
+                table_mut(&mut root, &path).map_err(|err| parser.error(&err))?;
+            }
+            parser.end_of_line()?;
+
+            table = path;
+            continue;
+        }
+
+        let key = parser.key()?;
+        parser.skip_spaces();
+        if !parser.eat('=') {
+            return Err(parser.error("expected `=` after the key"));
+        }
+        parser.skip_spaces();
+        let value = parser.value()?;
+
+        let full: Vec<String> = table.iter().chain(&key).cloned().collect();
+        insert(&mut root, &full, value).map_err(|err| parser.error(&err))?;
+        parser.end_of_line()?;
+    }
+
+    Ok(Value::Object(root))
+}
+
+/// The table at `path`, created when missing. Arrays of tables resolve to
+/// their last table.
+fn table_mut<'a>(root: &'a mut Map<String, Value>, path: &[String]) -> Result<&'a mut Map<String, Value>, String> {
+    let mut table = root;
+
+    for segment in path {
+        let entry = table.entry(segment.clone()).or_insert_with(|| Value::Object(Map::new()));
+        let entry = match entry {
+            Value::Array(tables) => tables.last_mut().ok_or_else(|| format!("`{}` is an empty array", segment))?,
+            entry => entry,
+        };
+
+        table = match entry {
+            Value::Object(next) => next,
+            _ => return Err(format!("`{}` is already a value, not a table", segment)),
+        };
+    }
+
+    Ok(table)
+}
+
+fn insert(root: &mut Map<String, Value>, path: &[String], value: Value) -> Result<(), String> {
+    let (last, parents) = path.split_last().expect("keys have a segment");
+    let table = table_mut(root, parents)?;
+
+    if table.contains_key(last) {
+        return Err(format!("`{}` is defined twice", path.join(".")));
+    }
+
+    table.insert(last.clone(), value);
+    Ok(())
+}
+
+struct TomlParser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl TomlParser {
+    fn error(&self, message: &str) -> String {
+        format!("line {}: {}", self.line, message)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn starts_with(&self, text: &str) -> bool {
+        text.chars().enumerate().all(|(offset, c)| self.chars.get(self.pos + offset) == Some(&c))
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.advance();
+            return true;
+        }
+        false
+    }
+
+    fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.advance();
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            while !matches!(self.peek(), None | Some('\n')) {
+                self.advance();
+            }
+        }
+    }
+
+    /// Skips whitespace, newlines and comments.
+    fn skip_blank_lines(&mut self) {
+        loop {
+            self.skip_spaces();
+            self.skip_comment();
+            if !self.eat('\n') && !self.eat('\r') {
+                break;
+            }
+        }
+    }
+
+    fn end_of_line(&mut self) -> Result<(), String> {
+        self.skip_spaces();
+        self.skip_comment();
+        self.eat('\r');
+
+        match self.peek() {
+            None => Ok(()),
+            Some('\n') => {
+                self.advance();
+                Ok(())
+            }
+            Some(c) => Err(self.error(&format!("unexpected `{}` after the value", c))),
+        }
+    }
+
+    /// A dotted key of bare and quoted parts.
+    fn key(&mut self) -> Result<Vec<String>, String> {
+        let mut parts = Vec::new();
+
+        loop {
+            self.skip_spaces();
+            let part = match self.peek() {
+                Some('"') => {
+                    self.advance();
+                    self.basic_string()?
+                }
+                Some('\'') => {
+                    self.advance();
+                    self.literal_string()?
+                }
+                _ => {
+                    let start = self.pos;
+                    while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                        self.advance();
+                    }
+                    if start == self.pos {
+                        return Err(self.error("expected a key"));
+                    }
+                    self.chars[start..self.pos].iter().collect()
+                }
+            };
+            parts.push(part);
+
+            self.skip_spaces();
+            if !self.eat('.') {
+                return Ok(parts);
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        match self.peek() {
+            Some('"') if self.starts_with("\"\"\"") => {
+                self.pos += 3;
+                self.multiline_string('"').map(Value::String)
+            }
+            Some('\'') if self.starts_with("'''") => {
+                self.pos += 3;
+                self.multiline_string('\'').map(Value::String)
+            }
+            Some('"') => {
+                self.advance();
+                self.basic_string().map(Value::String)
+            }
+            Some('\'') => {
+                self.advance();
+                self.literal_string().map(Value::String)
+            }
+            Some('[') => {
+                self.advance();
+                self.array()
+            }
+            Some('{') => {
+                self.advance();
+                self.inline_table()
+            }
+            _ => self.scalar(),
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, String> {
+        let mut text = String::new();
+
+        loop {
+            match self.advance() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some('"') => return Ok(text),
+                Some('\\') => text.push(self.escape()?),
+                Some(c) => text.push(c),
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, String> {
+        let mut text = String::new();
+
+        loop {
+            match self.advance() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some('\'') => return Ok(text),
+                Some(c) => text.push(c),
+            }
+        }
+    }
+
+    /// The body of a `"""` or `'''` string, after its opening quotes. A
+    /// newline right after them is dropped.
+    fn multiline_string(&mut self, quote: char) -> Result<String, String> {
+        let closing: String = std::iter::repeat_n(quote, 3).collect();
+        let mut text = String::new();
+
+        self.eat('\r');
+        self.eat('\n');
+
+        loop {
+            if self.starts_with(&closing) {
+                self.pos += 3;
+                return Ok(text);
+            }
+
+            match self.advance() {
+                None => return Err(self.error("unterminated multi-line string")),
+                Some('\\') if quote == '"' => {
+                    // A backslash ending a line trims the following whitespace
+                    if matches!(self.peek(), Some('\n' | '\r' | ' ' | '\t')) {
+                        while matches!(self.peek(), Some('\n' | '\r' | ' ' | '\t')) {
+                            self.advance();
+                        }
+                    } else {
+                        text.push(self.escape()?);
+                    }
+                }
+                Some(c) => text.push(c),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, String> {
+        let c = match self.advance() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some(kind @ ('u' | 'U')) => {
+                let digits = if kind == 'u' { 4 } else { 8 };
+                let hex: String = (0..digits).filter_map(|_| self.advance()).collect();
+                u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| self.error(&format!("invalid unicode escape `\\{}{}`", kind, hex)))?
+            }
+            other => return Err(self.error(&format!("invalid escape `\\{}`", other.unwrap_or(' ')))),
+        };
+
+        Ok(c)
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        let mut items = Vec::new();
+
+        loop {
+            self.skip_blank_lines();
+            if self.eat(']') {
+                return Ok(Value::Array(items));
+            }
+
+            items.push(self.value()?);
+
+            self.skip_blank_lines();
+            if self.eat(']') {
+                return Ok(Value::Array(items));
+            }
+            if !self.eat(',') {
+                return Err(self.error("expected `,` or `]` in the array"));
+            }
+        }
+    }
+
+    fn inline_table(&mut self) -> Result<Value, String> {
+        let mut table = Map::new();
+
+        self.skip_spaces();
+        if self.eat('}') {
+            return Ok(Value::Object(table));
+        }
+
+        loop {
+            let key = self.key()?;
+            self.skip_spaces();
+            if !self.eat('=') {
+                return Err(self.error("expected `=` after the key"));
+            }
+            self.skip_spaces();
+            let value = self.value()?;
+            insert(&mut table, &key, value).map_err(|err| self.error(&err))?;
+
+            self.skip_spaces();
+            if self.eat('}') {
+                return Ok(Value::Object(table));
+            }
+            if !self.eat(',') {
+                return Err(self.error("expected `,` or `}` in the inline table"));
+            }
+            self.skip_spaces();
+        }
+    }
+
+    /// Booleans and numbers.
+    fn scalar(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if !matches!(c, ' ' | '\t' | '\r' | '\n' | ',' | ']' | '}' | '#')) {
+            self.advance();
+        }
+        let token: String = self.chars[start..self.pos].iter().collect();
+
+        match token.as_str() {
+            "" => Err(self.error("expected a value")),
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            token => parse_number(&token.replace('_', ""))
+                .ok_or_else(|| self.error(&format!("unsupported value `{}`; quote strings", token))),
+        }
+    }
+}
+
+fn parse_number(token: &str) -> Option<Value> {
+    if let Ok(integer) = token.parse::<i64>() {
+        return Some(Value::Number(integer.into()));
+    }
+
+    token
+        .parse::<f64>()
+        .ok()
+        .filter(|float| float.is_finite() && token.chars().any(|c| c.is_ascii_digit()))
+        .and_then(Number::from_f64)
+        .map(Value::Number)
+}
+
+// YAML
+
+struct YamlLine {
+    number: usize,
+    indent: usize,
+    /// Text after the indentation, without comments.
+    content: String,
+    /// Text after the indentation as written, for block scalars.
+    raw: String,
+}
+
+pub fn parse_yaml(source: &str) -> Result<Value, String> {
+    let mut lines = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let number = index + 1;
+        let trimmed = line.trim_start_matches(' ');
+        if trimmed.starts_with('\t') {
+            return Err(format!("line {}: indent with spaces, not tabs", number));
+        }
+
+        let content = strip_yaml_comment(trimmed).trim_end().to_string();
+        if content == "---" || content == "..." {
+            continue;
+        }
+
+        lines.push(YamlLine { number, indent: line.len() - trimmed.len(), content, raw: trimmed.trim_end().to_string() });
+    }
+
+    let mut index = 0;
+    skip_empty(&lines, &mut index);
+    if index == lines.len() {
+        return Ok(Value::Object(Map::new()));
+    }
+
+    let indent = lines[index].indent;
+    let value = yaml_block(&mut lines, &mut index, indent)?;
+
+    skip_empty(&lines, &mut index);
+    if let Some(line) = lines.get(index) {
+        return Err(format!("line {}: unexpected indentation", line.number));
+    }
+
+    Ok(value)
+}
+
+fn skip_empty(lines: &[YamlLine], index: &mut usize) {
+    while lines.get(*index).is_some_and(|line| line.content.is_empty()) {
+        *index += 1;
+    }
+}
+
+/// A `#` starts a comment at the start of a line or after a space, outside
+/// quotes.
+fn strip_yaml_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((offset, c)) = chars.next() {
+        match (quote, c) {
+            (None, '#') if previous == ' ' => return &line[..offset],
+            (None, '"' | '\'') if previous == ' ' || offset == 0 || previous == ':' => quote = Some(c),
+            // `''` and `\"` are quotes inside the scalar, not its end
+            (Some('\''), '\'') if chars.peek().is_some_and(|(_, next)| *next == '\'') => {
+                chars.next();
+            }
+            (Some('"'), '\\') => {
+                chars.next();
+            }
+            (Some(open), c) if c == open => quote = None,
+            _ => {}
+        }
+        previous = c;
+    }
+
+    line
+}
+
+/// The mapping or sequence whose entries start at `indent`.
+fn yaml_block(lines: &mut [YamlLine], index: &mut usize, indent: usize) -> Result<Value, String> {
+    skip_empty(lines, index);
+
+    match lines.get(*index) {
+        Some(line) if line.content == "-" || line.content.starts_with("- ") => yaml_sequence(lines, index, indent),
+        Some(_) => yaml_mapping(lines, index, indent),
+        None => Ok(Value::Null),
+    }
+}
+
+fn yaml_sequence(lines: &mut [YamlLine], index: &mut usize, indent: usize) -> Result<Value, String> {
+    let mut items = Vec::new();
+
+    loop {
+        skip_empty(lines, index);
+        let Some(line) = lines.get_mut(*index) else {
+            break;
+        };
+        if line.indent != indent || !(line.content == "-" || line.content.starts_with("- ")) {
+            break;
+        }
+
+        let rest = line.content[1..].trim_start().to_string();
+
+        if rest.is_empty() {
+            *index += 1;
+            items.push(yaml_nested(lines, index, indent)?);
+            continue;
+        }
+
+        // `- key: value` and `- - item` open a block indented like its
+        // first entry
+        let offset = line.content.len() - rest.len();
+        let nested_sequence = rest == "-" || rest.starts_with("- ");
+        if nested_sequence || (split_yaml_key(&rest).is_some() && !rest.starts_with(['[', '{'])) {
+            line.indent += offset;
+            line.raw = line.raw[offset..].to_string();
+            line.content = rest;
+            let item_indent = line.indent;
+            items.push(yaml_block(lines, index, item_indent)?);
+            continue;
+        }
+
+        let number = line.number;
+        *index += 1;
+        items.push(yaml_scalar(&rest).map_err(|err| format!("line {}: {}", number, err))?);
+    }
+
+    Ok(Value::Array(items))
+}
+
+fn yaml_mapping(lines: &mut [YamlLine], index: &mut usize, indent: usize) -> Result<Value, String> {
+    let mut map = Map::new();
+
+    loop {
+        skip_empty(lines, index);
+        let Some(line) = lines.get(*index) else {
+            break;
+        };
+        if line.indent < indent {
+            break;
+        }
+        if line.indent > indent {
+            return Err(format!("line {}: unexpected indentation", line.number));
+        }
+
+        let number = line.number;
+        let (key, value) = split_yaml_key(&line.content)
+            .ok_or_else(|| format!("line {}: expected `key: value`", number))?;
+        let (key, value) = (yaml_key(key).map_err(|err| format!("line {}: {}", number, err))?, value.to_string());
+
+        if map.contains_key(&key) {
+            return Err(format!("line {}: `{}` is defined twice", number, key));
+        }
+
+        *index += 1;
+
+        let value = match value.as_str() {
+            "" => {
+                // A sequence may sit at the indentation of its key
+                skip_empty(lines, index);
+                match lines.get(*index) {
+                    Some(next) if next.indent == indent && (next.content == "-" || next.content.starts_with("- ")) => {
+                        yaml_sequence(lines, index, indent)?
+                    }
+                    _ => yaml_nested(lines, index, indent)?,
+                }
+            }
+            "|" | "|-" | "|+" | ">" | ">-" | ">+" => yaml_block_scalar(lines, index, indent, &value),
+            value => yaml_scalar(value).map_err(|err| format!("line {}: {}", number, err))?,
+        };
+
+        map.insert(key, value);
+    }
+
+    Ok(Value::Object(map))
+}
+
+/// The block indented deeper than `indent` that follows, or null.
+fn yaml_nested(lines: &mut [YamlLine], index: &mut usize, indent: usize) -> Result<Value, String> {
+    skip_empty(lines, index);
+
+    match lines.get(*index) {
+        Some(next) if next.indent > indent => {
+            let nested = next.indent;
+            yaml_block(lines, index, nested)
+        }
+        _ => Ok(Value::Null),
+    }
+}
+
+/// Splits `key: value` on the first `: ` (or trailing `:`) outside quotes.
+fn split_yaml_key(content: &str) -> Option<(&str, &str)> {
+    let mut quote = None;
+
+    for (offset, c) in content.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') if offset == 0 => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            (None, ':') => {
+                let rest = &content[offset + 1..];
+                if rest.is_empty() || rest.starts_with(' ') {
+                    return Some((content[..offset].trim(), rest.trim()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn yaml_key(key: &str) -> Result<String, String> {
+    match yaml_scalar(key)? {
+        Value::String(key) => Ok(key),
+        Value::Null => Err("empty key".to_string()),
+        other => Ok(other.to_string()),
+    }
+}
+
+/// The lines of a `|` or `>` scalar: everything indented deeper than its key.
+fn yaml_block_scalar(lines: &[YamlLine], index: &mut usize, indent: usize, style: &str) -> Value {
+    let start = *index;
+    while lines.get(*index).is_some_and(|line| line.raw.is_empty() || line.indent > indent) {
+        *index += 1;
+    }
+
+    let block = &lines[start..*index];
+    let content_indent = block.iter().filter(|line| !line.raw.is_empty()).map(|line| line.indent).min().unwrap_or(0);
+
+    let texts: Vec<String> = block
+        .iter()
+        .map(|line| match line.raw.is_empty() {
+            true => String::new(),
+            false => format!("{}{}", " ".repeat(line.indent - content_indent), line.raw),
+        })
+        .collect();
+
+    let mut text = match style.starts_with('|') {
+        true => texts.join("\n"),
+        false => texts.join(" "),
+    };
+
+    match style.chars().nth(1) {
+        Some('+') => text.push('\n'),
+        Some('-') => text = text.trim_end_matches('\n').to_string(),
+        _ => {
+            text = text.trim_end_matches('\n').to_string();
+            text.push('\n');
+        }
+    }
+
+    Value::String(text)
+}
+
+fn yaml_scalar(text: &str) -> Result<Value, String> {
+    let text = text.trim();
+
+    if let Some(inner) = text.strip_prefix('[') {
+        let inner = inner.strip_suffix(']').ok_or("unterminated flow sequence")?;
+        return split_flow(inner).into_iter().map(|item| yaml_scalar(&item)).collect::<Result<_, _>>().map(Value::Array);
+    }
+
+    if let Some(inner) = text.strip_prefix('{') {
+        let inner = inner.strip_suffix('}').ok_or("unterminated flow mapping")?;
+        let mut map = Map::new();
+        for entry in split_flow(inner) {
+            let (key, value) = split_yaml_key(&entry).ok_or_else(|| format!("expected `key: value` in `{}`", entry))?;
+            map.insert(yaml_key(key)?, yaml_scalar(value)?);
+        }
+        return Ok(Value::Object(map));
+    }
+
+    if let Some(inner) = text.strip_prefix('"') {
+        let inner = inner.strip_suffix('"').ok_or("unterminated string")?;
+        return Ok(Value::String(unescape_double_quoted(inner)?));
+    }
+
+    if let Some(inner) = text.strip_prefix('\'') {
+        let inner = inner.strip_suffix('\'').ok_or("unterminated string")?;
+        return Ok(Value::String(inner.replace("''", "'")));
+    }
+
+    if text.starts_with(['&', '*', '!']) {
+        return Err(format!("anchors, aliases and tags are not supported: `{}`", text));
+    }
+
+    Ok(match text {
+        "" | "~" | "null" | "Null" | "NULL" => Value::Null,
+        "true" | "True" | "TRUE" => Value::Bool(true),
+        "false" | "False" | "FALSE" => Value::Bool(false),
+        text => parse_number(text).unwrap_or_else(|| Value::String(text.to_string())),
+    })
+}
+
+/// Splits the inside of a one-line flow collection on commas outside quotes
+/// and brackets.
+fn split_flow(inner: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut depth = 0;
+
+    for c in inner.chars() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            (None, '[' | '{') => depth += 1,
+            (None, ']' | '}') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                items.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+
+    items.push(current);
+    items.into_iter().map(|item| item.trim().to_string()).filter(|item| !item.is_empty()).collect()
+}
+
+fn unescape_double_quoted(text: &str) -> Result<String, String> {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        unescaped.push(match chars.next() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('0') => '\0',
+            Some('u') => {
+                let hex: String = chars.by_ref().take(4).collect();
+                u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| format!("invalid unicode escape `\\u{}`", hex))?
+            }
+            other => return Err(format!("invalid escape `\\{}`", other.unwrap_or(' '))),
+        });
+    }
+
+    Ok(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn toml_constructs() {
+        let cases = [
+            ("bare key", "port = 8080", json!({ "port": 8080 })),
+            ("comments", "# top\nport = 1 # trailing\n\n", json!({ "port": 1 })),
+            ("crlf line endings", "a = 1\r\nb = 2\r\n", json!({ "a": 1, "b": 2 })),
+            ("dotted key", "jwt.secret = \"s\"", json!({ "jwt": { "secret": "s" } })),
+            ("quoted key", "\"a.b\" = 1\n'c d' = 2", json!({ "a.b": 1, "c d": 2 })),
+            ("table", "[server]\nport = 1", json!({ "server": { "port": 1 } })),
+            ("dotted table", "[scheduler.defaults]\nseed = 3", json!({ "scheduler": { "defaults": { "seed": 3 } } })),
+            ("table after a dotted key", "a.b = 1\n[a.c]\nd = 2", json!({ "a": { "b": 1, "c": { "d": 2 } } })),
+            (
+                "array of tables",
+                "[[rooms]]\nid = 1\n[[rooms]]\nid = 2",
+                json!({ "rooms": [{ "id": 1 }, { "id": 2 }] }),
+            ),
+            (
+                "table inside an array of tables",
+                "[[rooms]]\nid = 1\n[rooms.tags]\nlab = true",
+                json!({ "rooms": [{ "id": 1, "tags": { "lab": true } }] }),
+            ),
+            ("basic string escapes", r#"s = "a\tb\n\"c\"\\""#, json!({ "s": "a\tb\n\"c\"\\" })),
+            ("unicode escapes", r#"s = "\u00e9\U0001F600""#, json!({ "s": "é😀" })),
+            ("literal string", r"s = 'C:\path'", json!({ "s": "C:\\path" })),
+            ("multi-line basic string", "s = \"\"\"\nline one\nline two\"\"\"", json!({ "s": "line one\nline two" })),
+            ("line-ending backslash", "s = \"\"\"\none \\\n    two\"\"\"", json!({ "s": "one two" })),
+            ("multi-line literal string", "s = '''\nkeep \\n as is'''", json!({ "s": "keep \\n as is" })),
+            ("integers", "a = 42\nb = -7\nc = 1_000", json!({ "a": 42, "b": -7, "c": 1000 })),
+            ("floats", "a = 0.5\nb = -1e3", json!({ "a": 0.5, "b": -1000.0 })),
+            ("booleans", "a = true\nb = false", json!({ "a": true, "b": false })),
+            ("array", "a = [1, \"two\", [3]]", json!({ "a": [1, "two", [3]] })),
+            ("multi-line array", "a = [\n  1, # one\n  2,\n]", json!({ "a": [1, 2] })),
+            ("empty array", "a = []", json!({ "a": [] })),
+            ("inline table", "a = { x = 1, y.z = \"w\" }", json!({ "a": { "x": 1, "y": { "z": "w" } } })),
+            ("empty inline table", "a = {}", json!({ "a": {} })),
+        ];
+
+        for (construct, source, expected) in cases {
+            assert_eq!(parse_toml(source), Ok(expected), "{}", construct);
+        }
+    }
+
+    #[test]
+    fn toml_errors() {
+        let cases = [
+            ("missing equals", "port 8080", "line 1: expected `=` after the key"),
+            ("missing key", "= 1", "line 1: expected a key"),
+            ("missing value", "a =", "line 1: expected a value"),
+            ("unclosed table header", "[server\nport = 1", "line 1: expected `]` after the table name"),
+            ("unclosed array of tables", "[[rooms]\nid = 1", "line 1: expected `]` after the table name"),
+            ("trailing text", "a = 1 2", "line 1: unexpected `2` after the value"),
+            ("trailing text after a table", "[a] b", "line 1: unexpected `b` after the value"),
+            ("duplicate key", "a = 1\na = 2", "line 2: `a` is defined twice"),
+            ("duplicate key in an inline table", "a = { b = 1, b = 2 }", "line 1: `b` is defined twice"),
+            ("table over a value", "a = 1\n[a]", "line 2: `a` is already a value, not a table"),
+            ("dotted key through a value", "a = 1\na.b = 2", "line 2: `a` is already a value, not a table"),
+            ("array of tables over a table", "[a]\n[[a]]", "line 2: `a` is not an array of tables"),
+            ("table in an empty array", "a = []\n[a.b]", "line 2: `a` is an empty array"),
+            ("unterminated string", "s = \"open\n", "line 2: unterminated string"),
+            ("unterminated literal string", "s = 'open", "line 1: unterminated string"),
+            ("unterminated multi-line string", "s = \"\"\"open", "line 1: unterminated multi-line string"),
+            ("invalid escape", r#"s = "\q""#, "line 1: invalid escape `\\q`"),
+            ("invalid unicode escape", r#"s = "\uZZZZ""#, "line 1: invalid unicode escape `\\uZZZZ`"),
+            ("unclosed array", "a = [1 2]", "line 1: expected `,` or `]` in the array"),
+            ("unclosed inline table", "a = { b = 1 c = 2 }", "line 1: expected `,` or `}` in the inline table"),
+            ("inline table without equals", "a = { b }", "line 1: expected `=` after the key"),
+            ("bare string", "a = hello", "line 1: unsupported value `hello`; quote strings"),
+            ("date", "a = 2024-01-01", "line 1: unsupported value `2024-01-01`; quote strings"),
+            ("infinity", "a = inf", "line 1: unsupported value `inf`; quote strings"),
+        ];
+
+        for (case, source, expected) in cases {
+            assert_eq!(parse_toml(source), Err(expected.to_string()), "{}", case);
+        }
+    }
+
+    #[test]
+    fn yaml_constructs() {
+        let cases = [
+            ("empty document", "", json!({})),
+            ("comments only", "# nothing\n", json!({})),
+            ("document markers", "---\nport: 1\n...", json!({ "port": 1 })),
+            ("mapping", "port: 8080\nname: rooms", json!({ "port": 8080, "name": "rooms" })),
+            ("nested mapping", "jwt:\n  secret: s\n  leeway: 5", json!({ "jwt": { "secret": "s", "leeway": 5 } })),
+            ("comments", "# top\na: 1 # trailing\nb: x#y", json!({ "a": 1, "b": "x#y" })),
+            ("sequence", "origins:\n  - a\n  - b", json!({ "origins": ["a", "b"] })),
+            ("sequence at the key's indentation", "origins:\n- a\n- b", json!({ "origins": ["a", "b"] })),
+            (
+                "sequence of mappings",
+                "rooms:\n  - id: 1\n    name: A\n  - id: 2",
+                json!({ "rooms": [{ "id": 1, "name": "A" }, { "id": 2 }] }),
+            ),
+            ("nested sequence", "- - 1\n  - 2\n- 3", json!([[1, 2], 3])),
+            ("item on the next line", "-\n  a: 1", json!([{ "a": 1 }])),
+            ("empty value", "a:\nb: 1", json!({ "a": null, "b": 1 })),
+            ("nulls", "a: ~\nb: null", json!({ "a": null, "b": null })),
+            ("booleans", "a: true\nb: False", json!({ "a": true, "b": false })),
+            ("numbers", "a: 42\nb: -0.5", json!({ "a": 42, "b": -0.5 })),
+            ("plain string with colon", "url: http://auth:8080/x", json!({ "url": "http://auth:8080/x" })),
+            ("double-quoted string", r#"s: "a\tb \"c\" \u00e9""#, json!({ "s": "a\tb \"c\" é" })),
+            ("single-quoted string", "s: 'it''s # here'", json!({ "s": "it's # here" })),
+            ("escaped quote before a hash", r#"s: "say \" # here""#, json!({ "s": "say \" # here" })),
+            ("quoted key", "\"a: b\": 1", json!({ "a: b": 1 })),
+            ("numeric key", "1: one", json!({ "1": "one" })),
+            ("literal block", "s: |\n  one\n    two\nn: 1", json!({ "s": "one\n  two\n", "n": 1 })),
+            ("literal block, strip", "s: |-\n  one\n  two", json!({ "s": "one\ntwo" })),
+            ("literal block, keep", "s: |+\n  one", json!({ "s": "one\n" })),
+            ("folded block", "s: >\n  one\n  two", json!({ "s": "one two\n" })),
+            ("folded block, strip", "s: >-\n  one\n  two", json!({ "s": "one two" })),
+            ("flow sequence", "a: [1, \"b, c\", [d]]", json!({ "a": [1, "b, c", ["d"]] })),
+            ("flow mapping", "a: {x: 1, y: [2, 3]}", json!({ "a": { "x": 1, "y": [2, 3] } })),
+            ("empty flow collections", "a: []\nb: {}", json!({ "a": [], "b": {} })),
+            ("flow sequence item", "- [1, 2]", json!([[1, 2]])),
+        ];
+
+        for (construct, source, expected) in cases {
+            assert_eq!(parse_yaml(source), Ok(expected), "{}", construct);
+        }
+    }
+
+    #[test]
+    fn yaml_errors() {
+        let cases = [
+            ("tab indentation", "a:\n\tb: 1", "line 2: indent with spaces, not tabs"),
+            ("deeper sibling", "a: 1\n  b: 2", "line 2: unexpected indentation"),
+            ("shallower trailing line", "  a: 1\nb: 2", "line 2: unexpected indentation"),
+            ("line without a key", "a: 1\njust text", "line 2: expected `key: value`"),
+            ("duplicate key", "a: 1\na: 2", "line 2: `a` is defined twice"),
+            ("empty key", ": 1", "line 1: empty key"),
+            ("anchor", "a: &base 1", "line 1: anchors, aliases and tags are not supported: `&base 1`"),
+            ("alias", "a: *base", "line 1: anchors, aliases and tags are not supported: `*base`"),
+            ("tag", "a: !!str 1", "line 1: anchors, aliases and tags are not supported: `!!str 1`"),
+            ("unterminated double quote", "a: \"open", "line 1: unterminated string"),
+            ("unterminated single quote", "a: 'open", "line 1: unterminated string"),
+            ("invalid escape", r#"a: "\q""#, "line 1: invalid escape `\\q`"),
+            ("invalid unicode escape", r#"a: "\uZZZZ""#, "line 1: invalid unicode escape `\\uZZZZ`"),
+            ("multi-line flow sequence", "a: [1,\n  2]", "line 1: unterminated flow sequence"),
+            ("multi-line flow mapping", "a: {x: 1,\n  y: 2}", "line 1: unterminated flow mapping"),
+            ("flow mapping entry without a value", "a: {x}", "line 1: expected `key: value` in `x`"),
+            ("scalar item with an error", "- \"open", "line 1: unterminated string"),
+        ];
+
+        for (case, source, expected) in cases {
+            assert_eq!(parse_yaml(source), Err(expected.to_string()), "{}", case);
+        }
+    }
+
+    #[test]
+    fn load_reads_by_extension() {
+        let dir = std::env::temp_dir().join(format!("config-file-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let cases = [
+            ("settings.toml", "[jwt]\nsecret = \"s\"", Ok(json!({ "jwt": { "secret": "s" } }))),
+            ("settings.yaml", "jwt:\n  secret: s", Ok(json!({ "jwt": { "secret": "s" } }))),
+            ("settings.YML", "jwt:\n  secret: s", Ok(json!({ "jwt": { "secret": "s" } }))),
+            ("settings.json", r#"{"jwt": {"secret": "s"}}"#, Ok(json!({ "jwt": { "secret": "s" } }))),
+            ("settings.ini", "secret = s", Err("must end in .toml, .yaml, .yml or .json".to_string())),
+            ("broken.toml", "secret", Err("broken.toml: line 1: expected `=` after the key".to_string())),
+            ("broken.json", "{", Err("broken.json: EOF while parsing an object".to_string())),
+        ];
+
+        for (name, source, expected) in cases {
+            let path = dir.join(name);
+            std::fs::write(&path, source).unwrap();
+
+            match (load(path.to_str().unwrap()), expected) {
+                (Ok(value), Ok(expected)) => assert_eq!(value, expected, "{}", name),
+                (Err(err), Err(expected)) => assert!(err.contains(&expected), "{}: {}", name, err),
+                (found, expected) => panic!("{}: got {:?}, expected {:?}", name, found, expected),
+            }
+        }
+
+        let missing = load(dir.join("missing.toml").to_str().unwrap()).unwrap_err();
+        assert!(missing.starts_with("cannot read "), "{}", missing);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn lookup_follows_dotted_keys() {
+        let root = json!({ "jwt": { "secret": "s" }, "port": 1 });
+
+        let cases = [("port", Some(json!(1))), ("jwt.secret", Some(json!("s"))), ("jwt.missing", None), ("port.nested", None)];
+
+        for (key, expected) in cases {
+            assert_eq!(lookup(&root, key).cloned(), expected, "{}", key);
+        }
+    }
+}
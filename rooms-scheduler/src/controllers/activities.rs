@@ -0,0 +1,79 @@
+use crate::errors::scheduler_error_response;
+use crate::extractors::JsonBody;
+use crate::repositories::activities::activity_repository;
+use rooms_scheduler_core::{Activity, validate_activity};
+
+use axum::extract::Path;
+use axum_responses::{Result, http::HttpResponse};
+
+pub async fn create_activity_controller(JsonBody(activity): JsonBody<Activity>) -> Result<HttpResponse> {
+    validate_activity(&activity).map_err(scheduler_error_response)?;
+
+    let repository = activity_repository();
+
+    if repository.find(activity.id).is_some() {
+        return Err(HttpResponse::Conflict().error(format!("Activity {} already exists.", activity.id)));
+    }
+
+    repository.save(activity.clone());
+
+    Ok(HttpResponse::Created()
+        .message("activity created successfully")
+        .data(activity)
+    )
+}
+
+pub async fn list_activities_controller() -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok()
+        .message("activities retrieved successfully")
+        .data(activity_repository().list())
+    )
+}
+
+pub async fn get_activity_controller(Path(activity_id): Path<u32>) -> Result<HttpResponse> {
+    let activity = activity_repository()
+        .find(activity_id)
+        .ok_or_else(|| activity_not_found(activity_id))?;
+
+    Ok(HttpResponse::Ok()
+        .message("activity retrieved successfully")
+        .data(activity)
+    )
+}
+
+/// Replaces a stored activity; the id in the path wins over the one in the body.
+pub async fn update_activity_controller(
+    Path(activity_id): Path<u32>,
+    JsonBody(mut activity): JsonBody<Activity>,
+) -> Result<HttpResponse> {
+    activity.id = activity_id;
+    validate_activity(&activity).map_err(scheduler_error_response)?;
+
+    let repository = activity_repository();
+
+    if repository.find(activity_id).is_none() {
+        return Err(activity_not_found(activity_id));
+    }
+
+    repository.save(activity.clone());
+
+    Ok(HttpResponse::Ok()
+        .message("activity updated successfully")
+        .data(activity)
+    )
+}
+
+pub async fn delete_activity_controller(Path(activity_id): Path<u32>) -> Result<HttpResponse> {
+    let activity = activity_repository()
+        .delete(activity_id)
+        .ok_or_else(|| activity_not_found(activity_id))?;
+
+    Ok(HttpResponse::Ok()
+        .message("activity deleted successfully")
+        .data(activity)
+    )
+}
+
+fn activity_not_found(activity_id: u32) -> HttpResponse {
+    HttpResponse::NotFound().error(format!("Activity {} not found.", activity_id))
+}
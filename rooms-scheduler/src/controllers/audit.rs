@@ -0,0 +1,46 @@
+use crate::repositories::audit::{AuditFilter, AuditOutcome, audit_repository};
+
+use axum::extract::{Query, rejection::QueryRejection};
+use axum_responses::{Result, http::HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+const DEFAULT_LIMIT: usize = 100;
+const MAX_LIMIT: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    pub user_id: Option<i64>,
+    pub operation: Option<String>,
+    pub outcome: Option<AuditOutcome>,
+    /// RFC 3339 timestamps bounding `occurred_at`, inclusive.
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+}
+
+/// Lists audited scheduling runs, newest first, optionally filtered by
+/// `user_id`, `operation`, `outcome` and a `from`/`to` time range.
+pub async fn audit_controller(query: std::result::Result<Query<AuditQuery>, QueryRejection>) -> Result<HttpResponse> {
+    let Query(query) = query.map_err(|err| HttpResponse::BadRequest().error(err.body_text()))?;
+
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    if limit == 0 || limit > MAX_LIMIT {
+        return Err(HttpResponse::BadRequest().error(format!("limit must be between 1 and {}.", MAX_LIMIT)));
+    }
+
+    let filter = AuditFilter {
+        user_id: query.user_id,
+        operation: query.operation,
+        outcome: query.outcome,
+        from: query.from,
+        to: query.to,
+    };
+
+    let entries = audit_repository().list(&filter, limit);
+
+    Ok(HttpResponse::Ok()
+        .message("audit entries retrieved successfully")
+        .data(entries)
+    )
+}
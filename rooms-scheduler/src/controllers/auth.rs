@@ -0,0 +1,74 @@
+use crate::extractors::JsonBody;
+use crate::jwt::{JwtVerificationError, issue_tokens, verify_refresh_token};
+use crate::passwords;
+use crate::repositories::users::{User, user_repository};
+
+use axum_responses::{Result, http::HttpResponse};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct TokenRequest {
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+/// Signs in one of the `AUTH_USERS` accounts, for deployments that run
+/// without the main backend issuing tokens.
+pub async fn token_controller(JsonBody(body): JsonBody<TokenRequest>) -> Result<HttpResponse> {
+    // Password hashing is deliberately slow; keep it off the async workers
+    let user = tokio::task::spawn_blocking(move || {
+        match user_repository().find_by_email(body.email.trim()) {
+            Some(user) if passwords::verify(&body.password, &user.password_hash) => Some(user),
+            Some(_) => None,
+            None => {
+                passwords::verify_dummy(&body.password);
+                None
+            }
+        }
+    })
+    .await
+    .map_err(|err| HttpResponse::InternalServerError().error(format!("Sign-in failed: {}", err)))?;
+
+    let user = user.ok_or_else(|| HttpResponse::Unauthorized().error("Invalid email or password."))?;
+
+    token_response(&user, "tokens issued successfully")
+}
+
+/// Trades a refresh token for a new token pair. The account is looked up
+/// again, so removed accounts and changed roles take effect on refresh.
+pub async fn refresh_controller(JsonBody(body): JsonBody<RefreshRequest>) -> Result<HttpResponse> {
+    let claims = verify_refresh_token(&body.refresh_token).map_err(|err| match err {
+        JwtVerificationError::UnsupportedAlgorithm(algorithm) => not_issued_here(&algorithm),
+        _ => HttpResponse::Unauthorized().error("Invalid or expired refresh token."),
+    })?;
+
+    let user = user_repository()
+        .find(claims.user_id)
+        .filter(|user| user.email.eq_ignore_ascii_case(&claims.sub))
+        .ok_or_else(|| HttpResponse::Unauthorized().error("The account of this refresh token no longer exists."))?;
+
+    token_response(&user, "tokens refreshed successfully")
+}
+
+fn token_response(user: &User, message: &str) -> Result<HttpResponse> {
+    let tokens = issue_tokens(user).map_err(|err| match err {
+        JwtVerificationError::UnsupportedAlgorithm(algorithm) => not_issued_here(&algorithm),
+        err => HttpResponse::InternalServerError().error(format!("Could not sign tokens: {:?}", err)),
+    })?;
+
+    Ok(HttpResponse::Ok()
+        .message(message)
+        .data(tokens)
+    )
+}
+
+/// With an asymmetric `JWT_ALGORITHM` only the identity provider holds the
+/// signing key.
+fn not_issued_here(algorithm: &str) -> HttpResponse {
+    HttpResponse::NotImplemented().error(format!("Tokens are issued by the identity provider when JWT_ALGORITHM is {}.", algorithm))
+}
@@ -0,0 +1,18 @@
+use crate::config;
+
+use axum_responses::{Result, http::HttpResponse};
+use serde_json::json;
+
+/// Reloads the configuration, as SIGHUP does. Only the scheduler defaults,
+/// CORS, rate limit and webhook host settings change without a restart.
+pub async fn reload_config_controller() -> Result<HttpResponse> {
+    let reload = config::reload()
+        .map_err(|err| HttpResponse::InternalServerError().error(format!("Configuration not reloaded: {}", err)))?;
+
+    Ok(HttpResponse::Ok()
+        .message("configuration reloaded successfully")
+        .data(json!({
+            "applied": reload.applied,
+            "restart_required": reload.restart_required,
+        })))
+}
@@ -0,0 +1,57 @@
+use crate::config;
+
+use axum_responses::{ContentDisposition, FileResponse, FileResult};
+use serde_json::{Value, json};
+
+/// OpenAPI description of every route. It is written by hand: keep it in
+/// step with the router and with the models it references.
+const OPENAPI_SPEC: &str = include_str!("../openapi.json");
+
+/// Serves the OpenAPI document, with the deployment's base path as its
+/// server so that clients generated from it call the right URLs.
+pub async fn openapi_controller() -> FileResult {
+    let config = config::load_env();
+
+    let mut spec: Value = serde_json::from_str(OPENAPI_SPEC).expect("openapi.json is valid JSON");
+    spec["servers"] = json!([{ "url": config.base_path.as_deref().unwrap_or("/") }]);
+
+    Ok(FileResponse::builder()
+        .bytes(spec.to_string().into_bytes())
+        .filename("openapi.json")
+        .content_type("application/json")
+        .disposition(ContentDisposition::Inline)
+    )
+}
+
+/// Serves a Swagger UI page for the OpenAPI document. The UI itself is
+/// loaded from a CDN by the browser.
+pub async fn docs_controller() -> FileResult {
+    let config = config::load_env();
+    let spec_url = format!("{}/api/v1/openapi.json", config.base_path.as_deref().unwrap_or(""));
+
+    let page = format!(
+        r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>rooms-scheduler API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+  <script>
+    window.ui = SwaggerUIBundle({{ url: "{spec_url}", dom_id: "#swagger-ui" }});
+  </script>
+</body>
+</html>
+"##
+    );
+
+    Ok(FileResponse::builder()
+        .bytes(page.into_bytes())
+        .filename("docs.html")
+        .content_type("text/html; charset=utf-8")
+        .disposition(ContentDisposition::Inline)
+    )
+}
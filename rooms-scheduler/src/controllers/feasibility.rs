@@ -0,0 +1,17 @@
+use crate::extractors::JsonBody;
+use crate::repositories::blackouts::with_room_blackouts;
+use rooms_scheduler_core::{FeasibilityRequest, feasible_rooms};
+
+use axum_responses::{Result, http::HttpResponse};
+
+pub async fn feasibility_controller(JsonBody(mut body): JsonBody<FeasibilityRequest>) -> Result<HttpResponse> {
+    with_room_blackouts(&mut body.rooms);
+
+    let options = body.scheduler_options();
+    let rooms = feasible_rooms(&body.activity, &body.rooms, &options);
+
+    Ok(HttpResponse::Ok()
+        .message("feasible rooms computed successfully")
+        .data(rooms)
+    )
+}
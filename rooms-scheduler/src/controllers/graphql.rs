@@ -0,0 +1,136 @@
+use crate::blocking::run_store;
+use crate::extractors::{AuthenticatedUser, JsonBody};
+use crate::graphql::{Field, execute, filter, parse_query};
+use crate::permissions::{ACTIVITIES_READ, ROOMS_READ, SCHEDULE_READ, has_permission};
+use crate::repositories::activities::activity_repository;
+use crate::repositories::rooms::room_repository;
+use crate::repositories::schedules::{StoredSchedule, schedule_repository};
+
+use std::collections::HashMap;
+
+use axum_responses::{Result, http::HttpResponse};
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+
+#[derive(Debug, Deserialize)]
+pub struct GraphQlRequest {
+    pub query: String,
+    #[serde(default)]
+    pub variables: Option<Map<String, Value>>,
+}
+
+/// Runs a GraphQL query over the stored inventory and the caller's stored
+/// schedules. The root fields are `rooms`, `activities`, `schedules` and
+/// `schedule(schedule_id:)`; list fields take the filters of
+/// `graphql::filter`, e.g. `scheduled_activities(room_id: 3, slot_from: 2)`.
+/// Each root field needs the read permission of what it returns.
+pub async fn graphql_controller(
+    AuthenticatedUser(claims): AuthenticatedUser,
+    JsonBody(request): JsonBody<GraphQlRequest>,
+) -> Result<HttpResponse> {
+    let variables = request.variables.unwrap_or_default();
+
+    let invalid_query = |err: String| HttpResponse::BadRequest().error(json!({ "message": "Invalid query.", "errors": [{ "message": err }] }));
+
+    let fields = parse_query(&request.query, &variables).map_err(invalid_query)?;
+
+    let missing = fields
+        .iter()
+        .filter_map(|field| read_permission(&field.name))
+        .find(|permission| !has_permission(&claims, permission));
+
+    if let Some(permission) = missing {
+        return Err(HttpResponse::Forbidden().error(format!("This token lacks the `{}` permission.", permission)));
+    }
+
+    let stored = load_schedules(&fields, claims.user_id).await?;
+    let data = execute(&fields, |field| resolve_root(field, &stored)).map_err(invalid_query)?;
+
+    Ok(HttpResponse::Ok()
+        .message("query executed successfully")
+        .data(data)
+    )
+}
+
+fn read_permission(root_field: &str) -> Option<&'static str> {
+    match root_field {
+        "rooms" => Some(ROOMS_READ),
+        "activities" => Some(ACTIVITIES_READ),
+        "schedules" | "schedule" => Some(SCHEDULE_READ),
+        _ => None,
+    }
+}
+
+/// The caller's stored schedules the root fields ask for, read before the
+/// query runs so the store is called off the async workers.
+#[derive(Default)]
+struct StoredSchedules {
+    listed: Vec<StoredSchedule>,
+    by_id: HashMap<String, Option<StoredSchedule>>,
+}
+
+/// `None` when persistence is disabled.
+async fn load_schedules(fields: &[Field], user_id: i64) -> Result<Option<StoredSchedules>> {
+    let Some(repository) = schedule_repository() else {
+        return Ok(None);
+    };
+
+    let list = fields.iter().any(|field| field.name == "schedules");
+    let ids: Vec<String> = fields
+        .iter()
+        .filter(|field| field.name == "schedule")
+        .filter_map(|field| field.arguments.get("schedule_id")?.as_str().map(str::to_string))
+        .collect();
+
+    if !list && ids.is_empty() {
+        return Ok(Some(Default::default()));
+    }
+
+    run_store(move || {
+        let listed = match list {
+            true => repository.list_for_user(user_id)?,
+            false => Vec::new(),
+        };
+
+        let by_id = ids
+            .into_iter()
+            .map(|schedule_id| Ok((schedule_id.clone(), repository.find_for_user(&schedule_id, user_id)?)))
+            .collect::<std::result::Result<_, String>>()?;
+
+        Ok(Some(StoredSchedules { listed, by_id }))
+    })
+    .await
+}
+
+fn resolve_root(field: &Field, stored: &Option<StoredSchedules>) -> std::result::Result<Value, String> {
+    let records = match field.name.as_str() {
+        "rooms" => json!(room_repository().list()),
+        "activities" => json!(activity_repository().list()),
+        "schedules" => json!(schedules(stored)?.listed),
+        "schedule" => {
+            if let Some(argument) = field.arguments.keys().find(|argument| *argument != "schedule_id") {
+                return Err(format!("Unknown argument `{}` on field `schedule`.", argument));
+            }
+
+            let schedule_id = field
+                .arguments
+                .get("schedule_id")
+                .and_then(Value::as_str)
+                .ok_or("Field `schedule` needs a `schedule_id` string argument.")?;
+
+            return Ok(json!(schedules(stored)?.by_id.get(schedule_id).cloned().flatten()));
+        }
+        name => {
+            return Err(format!(
+                "Unknown field `{}`; query rooms, activities, schedules or schedule.",
+                name
+            ));
+        }
+    };
+
+    filter(records, &field.arguments, &field.name)
+}
+
+fn schedules(stored: &Option<StoredSchedules>) -> std::result::Result<&StoredSchedules, String> {
+    stored.as_ref().ok_or_else(|| "Schedule persistence is disabled.".to_string())
+}
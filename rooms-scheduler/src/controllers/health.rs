@@ -0,0 +1,76 @@
+use crate::jobs;
+use crate::repositories::schedules::schedule_repository;
+
+use axum_responses::{Result, http::HttpResponse};
+use serde_json::json;
+
+/// Answers as long as the process serves requests, for liveness probes.
+pub async fn health_controller() -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok()
+        .message("service is alive")
+        .data(json!({ "status": "ok" }))
+    )
+}
+
+/// Checks the job queue and, when enabled, the schedule store; answers 503
+/// while any of them cannot serve requests.
+pub async fn ready_controller() -> Result<HttpResponse> {
+    let mut checks = serde_json::Map::new();
+    let mut ready = true;
+
+    let mut check = |name: &str, result: std::result::Result<serde_json::Value, String>| {
+        let value = match result {
+            Ok(details) => json!({ "status": "ok", "details": details }),
+            Err(err) => {
+                ready = false;
+                json!({ "status": "failing", "error": err })
+            }
+        };
+        checks.insert(name.to_string(), value);
+    };
+
+    check("jobs", jobs::active_count().map(|active| json!({ "active": active })));
+
+    if let Some(repository) = schedule_repository() {
+        // The Postgres store waits on the network; keep it off the async workers
+        let ping = tokio::task::spawn_blocking(move || repository.ping())
+            .await
+            .unwrap_or_else(|err| Err(err.to_string()));
+        check("schedule_store", ping.map(|_| serde_json::Value::Null));
+    }
+
+    match ready {
+        true => Ok(HttpResponse::Ok()
+            .message("service is ready")
+            .data(json!({ "status": "ready", "checks": checks }))
+        ),
+        false => Err(HttpResponse::ServiceUnavailable().error(json!({
+            "message": "service is not ready",
+            "checks": checks,
+        }))),
+    }
+}
+
+/// Build information, set at compile time from `GIT_SHA` and `BUILD_DATE`
+/// when they are present in the build environment.
+pub async fn version_controller() -> Result<HttpResponse> {
+    let mut features = Vec::new();
+    if cfg!(feature = "exact") {
+        features.push("exact");
+    }
+    if cfg!(feature = "ilp") {
+        features.push("ilp");
+    }
+
+    Ok(HttpResponse::Ok()
+        .message("version information retrieved successfully")
+        .data(json!({
+            "name": env!("CARGO_PKG_NAME"),
+            "version": env!("CARGO_PKG_VERSION"),
+            "git_sha": option_env!("GIT_SHA").filter(|sha| !sha.is_empty()),
+            "build_date": option_env!("BUILD_DATE").filter(|date| !date.is_empty()),
+            "profile": if cfg!(debug_assertions) { "debug" } else { "release" },
+            "features": features,
+        }))
+    )
+}
@@ -0,0 +1,229 @@
+use crate::extractors::MultipartForm;
+use crate::repositories::activities::activity_repository;
+use crate::repositories::rooms::room_repository;
+use rooms_scheduler_core::{Activity, Room, SchedulerError, validate_activity, validate_room};
+
+use std::collections::HashMap;
+
+use axum_responses::{Result, http::HttpResponse};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value, json};
+
+/// Columns holding several numbers, separated by spaces or `;`.
+const NUMBER_LIST_COLUMNS: [&str; 3] = ["time_slots", "unavailable_slots", "combines"];
+/// Columns holding several words or phrases, separated by `;`.
+const TEXT_LIST_COLUMNS: [&str; 4] = ["features", "allowed_subjects", "required_features", "preferred_features"];
+/// Columns read as text even when they look like numbers.
+const TEXT_COLUMNS: [&str; 6] = ["name", "subject", "building", "preferred_building", "cohort_id", "group_id"];
+
+/// Problem with one row of an uploaded file. Rows are numbered as in a
+/// spreadsheet: the header is row 1.
+#[derive(Debug, Serialize)]
+struct RowError {
+    file: &'static str,
+    row: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    field: Option<String>,
+    message: String,
+}
+
+/// Imports rooms and activities from CSV files uploaded as the `rooms` and
+/// `activities` fields of a multipart form. The first row names the
+/// columns after the JSON fields; empty cells take the field's default.
+/// Nothing is stored unless every row is valid, and rows replace stored
+/// records with the same id.
+pub async fn import_controller(MultipartForm(parts): MultipartForm) -> Result<HttpResponse> {
+    let mut rooms = Vec::new();
+    let mut activities = Vec::new();
+    let mut errors = Vec::new();
+
+    for part in parts {
+        let contents = String::from_utf8(part.contents)
+            .map_err(|_| HttpResponse::BadRequest().error(format!("Field `{}` is not UTF-8 text.", part.name)))?;
+
+        match part.name.as_str() {
+            "rooms" => rooms.extend(read_records::<Room>("rooms", &contents, &mut errors, validate_room, |room| room.id)),
+            "activities" => activities.extend(read_records::<Activity>("activities", &contents, &mut errors, validate_activity, |activity| activity.id)),
+            name => {
+                return Err(HttpResponse::BadRequest().error(format!(
+                    "Unknown field `{}`; upload `rooms` and `activities` CSV files.",
+                    name
+                )));
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(HttpResponse::UnprocessableEntity().error(json!({
+            "message": format!("{} rows could not be imported; nothing was stored.", errors.len()),
+            "errors": errors,
+        })));
+    }
+
+    let room_count = rooms.len();
+    let activity_count = activities.len();
+
+    let room_repository = room_repository();
+    for room in rooms {
+        room_repository.save(room);
+    }
+
+    let activity_repository = activity_repository();
+    for activity in activities {
+        activity_repository.save(activity);
+    }
+
+    Ok(HttpResponse::Ok()
+        .message("inventory imported successfully")
+        .data(json!({ "rooms": room_count, "activities": activity_count }))
+    )
+}
+
+/// Parses every row of a CSV file into a record, recording the rows that
+/// do not parse, fail `validate`, or repeat an id.
+fn read_records<T: DeserializeOwned>(
+    file: &'static str,
+    contents: &str,
+    errors: &mut Vec<RowError>,
+    validate: fn(&T) -> std::result::Result<(), SchedulerError>,
+    id: fn(&T) -> u32,
+) -> Vec<T> {
+    let mut rows = parse_csv(contents).into_iter();
+
+    let Some((_, header)) = rows.next() else {
+        return Vec::new();
+    };
+    let header: Vec<String> = header.iter().map(|column| column.trim().to_string()).collect();
+
+    let mut records = Vec::new();
+    let mut rows_by_id: HashMap<u32, usize> = HashMap::new();
+
+    for (row, cells) in rows {
+        let mut error = |field: Option<String>, message: String| errors.push(RowError { file, row, field, message });
+
+        if cells.len() > header.len() {
+            error(None, format!("Row has {} cells but the header names {} columns.", cells.len(), header.len()));
+            continue;
+        }
+
+        let mut object: Map<String, Value> = header
+            .iter()
+            .zip(cells)
+            .filter(|(_, cell)| !cell.trim().is_empty())
+            .map(|(column, cell)| (column.clone(), cell_value(column, cell.trim())))
+            .collect();
+
+        // Activities are stored without a room; the placeholder satisfies the model
+        if file == "activities" && !object.contains_key("room") {
+            object.insert("room".to_string(), json!({ "id": 0, "name": "", "capacity": 0 }));
+        }
+
+        let record: T = match serde_path_to_error::deserialize(Value::Object(object)) {
+            Ok(record) => record,
+            Err(err) => {
+                let field = err.path().to_string();
+                error(Some(field).filter(|field| field != "."), err.into_inner().to_string());
+                continue;
+            }
+        };
+
+        if let Err(err) = validate(&record) {
+            error(None, err.to_string());
+            continue;
+        }
+
+        if let Some(first_row) = rows_by_id.insert(id(&record), row) {
+            error(Some("id".to_string()), format!("Id {} is already used on row {}.", id(&record), first_row));
+            continue;
+        }
+
+        records.push(record);
+    }
+
+    records
+}
+
+fn cell_value(column: &str, cell: &str) -> Value {
+    if NUMBER_LIST_COLUMNS.contains(&column) {
+        let items = cell
+            .split(|c: char| c == ';' || c.is_whitespace())
+            .filter(|item| !item.is_empty())
+            .map(literal_value);
+        return Value::Array(items.collect());
+    }
+
+    if TEXT_LIST_COLUMNS.contains(&column) {
+        let items = cell
+            .split(';')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| Value::String(item.to_string()));
+        return Value::Array(items.collect());
+    }
+
+    match TEXT_COLUMNS.contains(&column) {
+        true => Value::String(cell.to_string()),
+        false => literal_value(cell),
+    }
+}
+
+/// Numbers, booleans and JSON arrays or objects as such, anything else as
+/// text.
+fn literal_value(cell: &str) -> Value {
+    match serde_json::from_str::<Value>(cell) {
+        Ok(value @ (Value::Number(_) | Value::Bool(_) | Value::Array(_) | Value::Object(_))) => value,
+        _ => match cell.to_ascii_lowercase().as_str() {
+            "true" | "yes" => Value::Bool(true),
+            "false" | "no" => Value::Bool(false),
+            _ => Value::String(cell.to_string()),
+        },
+    }
+}
+
+/// Reads RFC 4180 CSV, returning each record with the row it starts on.
+/// Blank lines are skipped.
+fn parse_csv(contents: &str) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut row = 1;
+    let mut record_row = 1;
+
+    let mut chars = contents.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match (ch, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\r', false) if chars.peek() == Some(&'\n') => {}
+            ('\n', false) => {
+                record.push(std::mem::take(&mut field));
+                match record.iter().any(|cell| !cell.is_empty()) {
+                    true => records.push((record_row, std::mem::take(&mut record))),
+                    false => record.clear(),
+                }
+                row += 1;
+                record_row = row;
+            }
+            (ch, _) => {
+                if ch == '\n' {
+                    row += 1;
+                }
+                field.push(ch);
+            }
+        }
+    }
+
+    record.push(field);
+    if record.iter().any(|cell| !cell.is_empty()) {
+        records.push((record_row, record));
+    }
+
+    records
+}
@@ -0,0 +1,54 @@
+use crate::config;
+
+use axum_responses::{Result, http::HttpResponse};
+use serde_json::json;
+
+/// Describes what this build and deployment support, so clients do not
+/// have to hard-code it.
+pub async fn info_controller() -> Result<HttpResponse> {
+    let config = config::load_env();
+
+    let mut strategies = vec!["greedy", "backtracking", "annealing"];
+    if cfg!(feature = "exact") {
+        strategies.push("exact");
+    }
+    if cfg!(feature = "ilp") {
+        strategies.push("ilp");
+    }
+
+    Ok(HttpResponse::Ok()
+        .message("service information retrieved successfully")
+        .data(json!({
+            "name": env!("CARGO_PKG_NAME"),
+            "version": env!("CARGO_PKG_VERSION"),
+            "base_path": config.base_path.as_deref().unwrap_or(""),
+            "strategies": strategies,
+            "time_slots": {
+                "indexing": "zero-based",
+                "minutes_require_slot_length": true,
+                "days": "zero-based u8, scheduled independently",
+            },
+            "max_body_bytes": config.max_body_bytes,
+            "request_timeout_ms": config.request_timeout_ms,
+            "persistence": config.persist_schedules,
+            "schedule_cache": {
+                "ttl_seconds": config.schedule_cache_ttl_seconds,
+                "capacity": config.schedule_cache_capacity,
+            },
+            "idempotency_ttl_seconds": config.idempotency_ttl_seconds,
+            "schedule_timeout_ms": config.schedule_timeout_ms,
+            "async_jobs": {
+                "prefer": "respond-async",
+                "ttl_seconds": config.job_ttl_seconds,
+                "webhooks": { "signed": config.webhook_secret.is_some(), "max_attempts": config.webhook_max_attempts },
+            },
+            "docs": { "openapi": "/api/v1/openapi.json", "swagger_ui": "/api/v1/docs" },
+            "rate_limits": {
+                "per_user_per_minute": config.rate_limit_per_user_per_minute,
+                "per_ip_per_minute": config.rate_limit_per_ip_per_minute,
+            },
+            "metrics": "/metrics",
+            "probes": { "liveness": "/healthz", "readiness": "/readyz", "version": "/version" },
+        }))
+    )
+}
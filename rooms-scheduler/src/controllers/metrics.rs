@@ -0,0 +1,13 @@
+use crate::metrics;
+
+use axum_responses::{ContentDisposition, FileResponse, FileResult};
+
+/// Serves the process metrics for Prometheus to scrape.
+pub async fn metrics_controller() -> FileResult {
+    Ok(FileResponse::builder()
+        .bytes(metrics::render().into_bytes())
+        .filename("metrics")
+        .content_type("text/plain; version=0.0.4; charset=utf-8")
+        .disposition(ContentDisposition::Inline)
+    )
+}
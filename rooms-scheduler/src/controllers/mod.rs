@@ -1 +1,18 @@
-pub mod rooms_scheduler;
\ No newline at end of file
+pub mod activities;
+pub mod audit;
+pub mod auth;
+pub mod config;
+pub mod docs;
+pub mod feasibility;
+pub mod graphql;
+pub mod health;
+pub mod import;
+pub mod info;
+pub mod metrics;
+pub mod rooms;
+pub mod rooms_scheduler;
+pub mod schedule_batch;
+pub mod schedule_calendar;
+pub mod schedule_jobs;
+pub mod schedule_stream;
+pub mod schedule_validation;
\ No newline at end of file
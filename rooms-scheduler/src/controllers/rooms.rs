@@ -0,0 +1,105 @@
+use crate::errors::scheduler_error_response;
+use crate::extractors::JsonBody;
+use crate::repositories::blackouts::blackout_repository;
+use crate::repositories::rooms::room_repository;
+use rooms_scheduler_core::{Room, RoomBlackout, validate_room};
+
+use axum::extract::Path;
+use axum_responses::{Result, http::HttpResponse};
+
+pub async fn create_room_controller(JsonBody(room): JsonBody<Room>) -> Result<HttpResponse> {
+    validate_room(&room).map_err(scheduler_error_response)?;
+
+    let repository = room_repository();
+
+    if repository.find(room.id).is_some() {
+        return Err(HttpResponse::Conflict().error(format!("Room {} already exists.", room.id)));
+    }
+
+    repository.save(room.clone());
+
+    Ok(HttpResponse::Created()
+        .message("room created successfully")
+        .data(room)
+    )
+}
+
+pub async fn list_rooms_controller() -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok()
+        .message("rooms retrieved successfully")
+        .data(room_repository().list())
+    )
+}
+
+pub async fn get_room_controller(Path(room_id): Path<u32>) -> Result<HttpResponse> {
+    let room = room_repository()
+        .find(room_id)
+        .ok_or_else(|| room_not_found(room_id))?;
+
+    Ok(HttpResponse::Ok()
+        .message("room retrieved successfully")
+        .data(room)
+    )
+}
+
+/// Replaces a stored room; the id in the path wins over the one in the body.
+pub async fn update_room_controller(
+    Path(room_id): Path<u32>,
+    JsonBody(mut room): JsonBody<Room>,
+) -> Result<HttpResponse> {
+    room.id = room_id;
+    validate_room(&room).map_err(scheduler_error_response)?;
+
+    let repository = room_repository();
+
+    if repository.find(room_id).is_none() {
+        return Err(room_not_found(room_id));
+    }
+
+    repository.save(room.clone());
+
+    Ok(HttpResponse::Ok()
+        .message("room updated successfully")
+        .data(room)
+    )
+}
+
+pub async fn delete_room_controller(Path(room_id): Path<u32>) -> Result<HttpResponse> {
+    let room = room_repository()
+        .delete(room_id)
+        .ok_or_else(|| room_not_found(room_id))?;
+
+    Ok(HttpResponse::Ok()
+        .message("room deleted successfully")
+        .data(room)
+    )
+}
+
+/// Stores a closure of the room, which every later scheduling run honors.
+/// The room does not need to be in the stored inventory.
+pub async fn create_room_blackout_controller(
+    Path(room_id): Path<u32>,
+    JsonBody(blackout): JsonBody<RoomBlackout>,
+) -> Result<HttpResponse> {
+    if blackout.slots.is_empty() {
+        return Err(HttpResponse::BadRequest().error("A blackout must close at least one slot."));
+    }
+
+    blackout_repository().add(room_id, blackout.clone());
+
+    Ok(HttpResponse::Created()
+        .message("room blackout created successfully")
+        .data(blackout)
+    )
+}
+
+pub async fn list_room_blackouts_controller(Path(room_id): Path<u32>) -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok()
+        .message("room blackouts retrieved successfully")
+        .data(blackout_repository().list(room_id))
+    )
+}
+
+fn room_not_found(room_id: u32) -> HttpResponse {
+    HttpResponse::NotFound().error(format!("Room {} not found.", room_id))
+}
@@ -1,19 +1,415 @@
-use crate::{models::ActivitiesRequest, scheduler::algorithm::run_scheduler};
+use crate::{audit::AuditContext, blocking::{run_blocking, run_store}, config, errors::scheduler_error_response, idempotency, ids::new_id, jobs, metrics, schedule_cache};
+use crate::extractors::{AuthenticatedUser, SchedulingBody};
+use crate::idempotency::{IDEMPOTENCY_KEY_HEADER, IdempotencyLookup, IdempotentResult};
+use crate::repositories::with_stored_records;
+use crate::repositories::schedules::{StoredSchedule, schedule_repository};
+use crate::webhooks::Callback;
+use rooms_scheduler_core::{ActivitiesRequest, Activity, DaySchedule, RankedSchedule, RoomTimeline, ScheduleChange, UnscheduledActivity, resolve_time_slots, run_scheduler, schedule_to_csv, schedule_to_xlsx, validate_request};
 
-use axum::extract::Json;
-use axum_responses::{Result, http::HttpResponse};
+use std::time::Instant;
 
-pub async fn rooms_scheduler_controller(Json(body): Json<ActivitiesRequest>) -> Result<HttpResponse> {
-    // Placeholder for rooms scheduler controller logic
+use axum::{extract::{Path, Query, rejection::QueryRejection}, http::HeaderMap, response::{IntoResponse, Response}};
+use axum_responses::{FileResponse, Result, http::HttpResponse};
+use serde::{Deserialize, Serialize};
 
-    let activities = body.activities;
+pub const SCHEDULE_ID_HEADER: &str = "x-schedule-id";
+
+/// `Prefer` value (RFC 7240) asking for a job id instead of waiting for the schedule.
+const RESPOND_ASYNC: &str = "respond-async";
+
+const DEFAULT_PAGE_SIZE: usize = 50;
+
+const XLSX_CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct PaginationQuery {
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    /// `json`, `csv` or `xlsx`.
+    pub format: Option<String>,
+}
+
+enum ExportFormat {
+    Json,
+    Csv,
+    Xlsx,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+struct PaginationMeta {
+    page: usize,
+    page_size: usize,
+    scheduled_total: usize,
+    unscheduled_total: usize,
+}
+
+/// Result of one scheduling run, as cached for idempotent retries.
+#[derive(Debug, Clone)]
+pub struct ScheduleOutcome {
+    pub scheduled_activities: Vec<Activity>,
+    pub unscheduled_activities: Vec<UnscheduledActivity>,
+    pub room_timelines: Vec<RoomTimeline>,
+    pub days: Vec<DaySchedule>,
+    pub alternatives: Vec<RankedSchedule>,
+    pub changes: Option<Vec<ScheduleChange>>,
+    pub schedule_id: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// Totals of a run, unaffected by pagination.
+#[derive(Debug, Clone, Copy, Serialize)]
+struct ScheduleStats {
+    total_activities: usize,
+    scheduled: usize,
+    unscheduled: usize,
+    rooms_used: usize,
+    duration_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct ScheduleResponse {
+    scheduled_activities: Vec<Activity>,
+    unscheduled_activities: Vec<UnscheduledActivity>,
+    room_timelines: Vec<RoomTimeline>,
+    days: Vec<DaySchedule>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    alternatives: Vec<RankedSchedule>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changes: Option<Vec<ScheduleChange>>,
+    stats: ScheduleStats,
+    /// Same as `stats.duration_ms`, kept for older clients.
+    duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pagination: Option<PaginationMeta>,
+    /// Same as the `X-Schedule-Id` header; set when the run was persisted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schedule_id: Option<String>,
+}
+
+pub async fn rooms_scheduler_controller(
+    AuthenticatedUser(claims): AuthenticatedUser,
+    headers: HeaderMap,
+    pagination: std::result::Result<Query<PaginationQuery>, QueryRejection>,
+    SchedulingBody(body): SchedulingBody<ActivitiesRequest>,
+) -> Result<HttpResponse> {
+    let pagination = parse_pagination(pagination)?;
+
+    let idempotency_key = headers
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(|key| format!("{}:{}", claims.user_id, key));
+
+    let body_hash = idempotency::hash_body(&body);
+
+    if let Some(key) = &idempotency_key {
+        match idempotency::lookup(key, body_hash) {
+            IdempotencyLookup::Hit(IdempotentResult::Schedule(outcome)) => return Ok(schedule_response(outcome, pagination)),
+            IdempotencyLookup::Hit(IdempotentResult::Job(job_id)) => {
+                // A job pruned before its key expired is started again
+                if let Some(job) = jobs::find_for_user(&job_id, claims.user_id) {
+                    return Ok(job_accepted(&job_id, job.status));
+                }
+            }
+            IdempotencyLookup::Conflict => {
+                return Err(HttpResponse::Conflict()
+                    .error("Idempotency-Key was already used with a different request body."));
+            }
+            IdempotencyLookup::Miss => {}
+        }
+    }
+
+    let body = with_stored_records(body).map_err(scheduler_error_response)?;
+
+    let repository = schedule_repository();
+    let request = repository.as_ref().map(|_| body.clone());
+
+    validate_request(&body).map_err(scheduler_error_response)?;
+
+    let body = body.normalized();
+    let request_hash = idempotency::hash_body(&body);
+    let options = body.scheduler_options();
+
+    tracing::info!(
+        user_id = claims.user_id,
+        activities = body.activities.len(),
+        rooms = body.rooms.len(),
+        "Scheduling activities"
+    );
+
+    let mut activities = body.activities;
     let rooms = body.rooms;
 
-    let (scheduled_activities, unscheduled_activities) = run_scheduler(activities, rooms)?;
+    resolve_time_slots(&mut activities, body.slot_length_minutes, body.grid_start)
+        .map_err(scheduler_error_response)?;
+
+    let callback = body
+        .callback_url
+        .as_deref()
+        .map(Callback::parse)
+        .transpose()
+        .map_err(|err| HttpResponse::BadRequest().error(err))?;
+
+    let audit = AuditContext::new(&claims, "/api/v1/rooms/schedule", activities.len(), rooms.len());
+
+    if prefers_async(&headers) {
+        let job_id = jobs::submit(claims.user_id, activities, rooms, options, callback, audit);
+
+        if let Some(key) = &idempotency_key {
+            idempotency::store(key, body_hash, IdempotentResult::Job(job_id.clone()));
+        }
+
+        return Ok(job_accepted(&job_id, jobs::JobStatus::Pending));
+    }
+
+    if callback.is_some() {
+        return Err(HttpResponse::BadRequest().error("callback_url needs an async run; send `Prefer: respond-async`."));
+    }
+
+    let started_at = Instant::now();
+
+    let schedule = match schedule_cache::get(request_hash) {
+        Some(schedule) => {
+            audit.completed(&schedule);
+            schedule
+        }
+        None => {
+            let schedule = run_blocking(move || {
+                let result = metrics::observe_solve(|| run_scheduler(activities, rooms, &options));
+                audit.record(&result);
+                result
+            })
+            .await?
+            .map_err(scheduler_error_response)?;
+
+            schedule_cache::insert(request_hash, schedule.clone());
+            schedule
+        }
+    };
 
-    Ok(HttpResponse::Ok()
+    let duration_ms = started_at.elapsed().as_millis() as u64;
+
+    let schedule_id = match (repository, request) {
+        (Some(repository), Some(request)) => {
+            let schedule_id = new_id();
+
+            // Handing out the id of a schedule that was not kept would answer 404 later
+            let stored = StoredSchedule {
+                schedule_id: schedule_id.clone(),
+                user_id: claims.user_id,
+                created_at: chrono::Utc::now().to_rfc3339(),
+                request,
+                scheduled_activities: schedule.scheduled_activities.clone(),
+                unscheduled_activities: schedule.unscheduled_activities.clone(),
+            };
+            run_store(move || repository.save(stored)).await?;
+
+            Some(schedule_id)
+        }
+        _ => None,
+    };
+
+    let outcome = ScheduleOutcome {
+        scheduled_activities: schedule.scheduled_activities,
+        unscheduled_activities: schedule.unscheduled_activities,
+        room_timelines: schedule.room_timelines,
+        days: schedule.days,
+        alternatives: schedule.alternatives,
+        changes: schedule.changes,
+        schedule_id,
+        duration_ms,
+    };
+
+    if let Some(key) = &idempotency_key {
+        idempotency::store(key, body_hash, IdempotentResult::Schedule(outcome.clone()));
+    }
+
+    Ok(schedule_response(outcome, pagination))
+}
+
+/// Returns a stored schedule as JSON, or as a CSV or Excel download when
+/// asked for by `?format=` or the `Accept` header.
+pub async fn get_schedule_controller(
+    AuthenticatedUser(claims): AuthenticatedUser,
+    Path(schedule_id): Path<String>,
+    headers: HeaderMap,
+    query: std::result::Result<Query<ExportQuery>, QueryRejection>,
+) -> Result<Response> {
+    let Query(query) = query.map_err(|err| HttpResponse::BadRequest().error(err.body_text()))?;
+    let format = export_format(query.format.as_deref(), &headers)?;
+
+    let repository = schedule_repository()
+        .ok_or_else(|| HttpResponse::NotFound().error("Schedule persistence is disabled."))?;
+
+    let schedule = {
+        let schedule_id = schedule_id.clone();
+        run_store(move || repository.find_for_user(&schedule_id, claims.user_id)).await?
+    }
+    .ok_or_else(|| HttpResponse::NotFound().error(format!("Schedule {} not found.", schedule_id)))?;
+
+    let (bytes, extension, content_type) = match format {
+        ExportFormat::Json => {
+            return Ok(HttpResponse::Ok()
+                .message("schedule retrieved successfully")
+                .data(schedule)
+                .into_response());
+        }
+        ExportFormat::Csv => (
+            schedule_to_csv(&schedule.scheduled_activities, &schedule.unscheduled_activities).into_bytes(),
+            "csv",
+            "text/csv; charset=utf-8",
+        ),
+        ExportFormat::Xlsx => (
+            schedule_to_xlsx(&schedule.scheduled_activities, &schedule.unscheduled_activities),
+            "xlsx",
+            XLSX_CONTENT_TYPE,
+        ),
+    };
+
+    Ok(FileResponse::builder()
+        .bytes(bytes)
+        .filename(&format!("schedule-{}.{}", schedule_id, extension))
+        .content_type(content_type)
+        .into_response())
+}
+
+/// `?format=` takes precedence; otherwise the first of CSV or Excel named in
+/// `Accept` is used, and JSON when neither is.
+fn export_format(format: Option<&str>, headers: &HeaderMap) -> Result<ExportFormat> {
+    if let Some(format) = format {
+        return match format.to_ascii_lowercase().as_str() {
+            "json" => Ok(ExportFormat::Json),
+            "csv" => Ok(ExportFormat::Csv),
+            "xlsx" => Ok(ExportFormat::Xlsx),
+            _ => Err(HttpResponse::BadRequest().error(format!("Unknown format `{}`; expected json, csv or xlsx.", format))),
+        };
+    }
+
+    let accepted = headers
+        .get_all("accept")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|media_type| media_type.split(';').next())
+        .map(|media_type| media_type.trim().to_ascii_lowercase())
+        .find_map(|media_type| match media_type.as_str() {
+            "text/csv" => Some(ExportFormat::Csv),
+            XLSX_CONTENT_TYPE => Some(ExportFormat::Xlsx),
+            _ => None,
+        });
+
+    Ok(accepted.unwrap_or(ExportFormat::Json))
+}
+
+/// 202 pointing at the job's polling URL.
+fn job_accepted(job_id: &str, status: jobs::JobStatus) -> HttpResponse {
+    let location = format!(
+        "{}/api/v1/rooms/schedule/jobs/{}",
+        config::load_env().base_path.as_deref().unwrap_or(""),
+        job_id
+    );
+
+    HttpResponse::Accepted()
+        .message("scheduling job accepted")
+        .data(serde_json::json!({ "job_id": job_id, "status": status }))
+        .add_header("location", &location)
+}
+
+fn prefers_async(headers: &HeaderMap) -> bool {
+    headers
+        .get_all("prefer")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|preference| preference.trim().eq_ignore_ascii_case(RESPOND_ASYNC))
+}
+
+/// Returns `(page, page_size)` when the caller asked for a paginated response.
+fn parse_pagination(
+    query: std::result::Result<Query<PaginationQuery>, QueryRejection>,
+) -> Result<Option<(usize, usize)>> {
+    let Query(query) = query.map_err(|err| HttpResponse::BadRequest().error(err.body_text()))?;
+
+    if query.page.is_none() && query.page_size.is_none() {
+        return Ok(None);
+    }
+
+    let page = query.page.unwrap_or(1);
+    let page_size = query.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+
+    if page == 0 || page_size == 0 {
+        return Err(HttpResponse::BadRequest().error("page and page_size must be greater than zero."));
+    }
+
+    Ok(Some((page, page_size)))
+}
+
+fn paginate<T>(items: Vec<T>, page: usize, page_size: usize) -> Vec<T> {
+    items
+        .into_iter()
+        .skip((page - 1).saturating_mul(page_size))
+        .take(page_size)
+        .collect()
+}
+
+fn schedule_response(outcome: ScheduleOutcome, pagination: Option<(usize, usize)>) -> HttpResponse {
+    let ScheduleOutcome { scheduled_activities, unscheduled_activities, room_timelines, days, alternatives, changes, schedule_id, duration_ms } = outcome;
+
+    let rooms_used: std::collections::HashSet<u32> = scheduled_activities
+        .iter()
+        .flat_map(|activity| activity.assigned_rooms())
+        .map(|room| room.id)
+        .collect();
+
+    let stats = ScheduleStats {
+        total_activities: scheduled_activities.len() + unscheduled_activities.len(),
+        scheduled: scheduled_activities.len(),
+        unscheduled: unscheduled_activities.len(),
+        rooms_used: rooms_used.len(),
+        duration_ms,
+    };
+
+    let data = match pagination {
+        None => ScheduleResponse {
+            scheduled_activities,
+            unscheduled_activities,
+            room_timelines,
+            days,
+            alternatives,
+            changes,
+            stats,
+            duration_ms,
+            pagination: None,
+            schedule_id: schedule_id.clone(),
+        },
+        Some((page, page_size)) => ScheduleResponse {
+            pagination: Some(PaginationMeta {
+                page,
+                page_size,
+                scheduled_total: scheduled_activities.len(),
+                unscheduled_total: unscheduled_activities.len(),
+            }),
+            scheduled_activities: paginate(scheduled_activities, page, page_size),
+            unscheduled_activities: paginate(unscheduled_activities, page, page_size),
+            room_timelines,
+            days,
+            alternatives,
+            changes,
+            stats,
+            duration_ms,
+            schedule_id: schedule_id.clone(),
+        },
+    };
+
+    let response = HttpResponse::Ok()
         .message("activities scheduled successfully")
-        .data(scheduled_activities)
-        .data(unscheduled_activities)
-    )
-}
\ No newline at end of file
+        .data(data);
+
+    match schedule_id {
+        Some(schedule_id) => response.add_header(SCHEDULE_ID_HEADER, &schedule_id),
+        None => response,
+    }
+}
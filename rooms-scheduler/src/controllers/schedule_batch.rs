@@ -0,0 +1,75 @@
+use crate::audit::AuditContext;
+use crate::blocking::run_blocking;
+use crate::errors::{scheduler_error_details, scheduler_error_status};
+use crate::extractors::{AuthenticatedUser, SchedulingBody};
+use crate::metrics;
+use crate::repositories::with_stored_records;
+use rooms_scheduler_core::{ActivitiesRequest, BatchScenario, Schedule, SchedulerError, models::Claims, resolve_time_slots, run_scheduler, validate_request};
+
+use axum_responses::{Result, http::HttpResponse};
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug, Serialize)]
+struct ScenarioResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Schedule>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<Value>,
+}
+
+/// Schedules every scenario independently. A failing scenario is reported
+/// in its own result and does not abort the rest of the batch; the
+/// schedule timeout applies to the batch as a whole.
+/// Each scenario is audited as its own run.
+pub async fn schedule_batch_controller(
+    AuthenticatedUser(claims): AuthenticatedUser,
+    SchedulingBody(scenarios): SchedulingBody<Vec<BatchScenario>>,
+) -> Result<HttpResponse> {
+    tracing::info!(scenarios = scenarios.len(), "Scheduling a batch of scenarios");
+
+    let results = run_blocking(move || schedule_scenarios(&claims, scenarios)).await?;
+
+    Ok(HttpResponse::Ok()
+        .message("batch scheduled successfully")
+        .data(results)
+    )
+}
+
+fn schedule_scenarios(claims: &Claims, scenarios: Vec<BatchScenario>) -> Vec<ScenarioResult> {
+    scenarios
+        .into_iter()
+        .map(|BatchScenario { label, request }| match schedule_scenario(claims, request) {
+            Ok(schedule) => ScenarioResult { label, success: true, data: Some(schedule), code: None, error: None },
+            Err(err) => ScenarioResult {
+                label,
+                success: false,
+                data: None,
+                code: Some(scheduler_error_status(&err)),
+                error: Some(scheduler_error_details(&err)),
+            },
+        })
+        .collect()
+}
+
+fn schedule_scenario(claims: &Claims, request: ActivitiesRequest) -> std::result::Result<Schedule, SchedulerError> {
+    let request = with_stored_records(request)?;
+
+    validate_request(&request)?;
+
+    let request = request.normalized();
+    let options = request.scheduler_options();
+
+    let mut activities = request.activities;
+    resolve_time_slots(&mut activities, request.slot_length_minutes, request.grid_start)?;
+
+    let audit = AuditContext::new(claims, "/api/v1/rooms/schedule/batch", activities.len(), request.rooms.len());
+    let result = metrics::observe_solve(|| run_scheduler(activities, request.rooms, &options));
+    audit.record(&result);
+    result
+}
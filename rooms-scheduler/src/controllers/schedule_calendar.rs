@@ -0,0 +1,94 @@
+use crate::{audit::AuditContext, blocking::{run_blocking, run_store}, config, errors::scheduler_error_response, metrics};
+use crate::extractors::{AuthenticatedUser, SchedulingBody};
+use crate::repositories::{schedules::schedule_repository, with_stored_records};
+use rooms_scheduler_core::{CalendarMapping, CalendarRequest, resolve_time_slots, run_scheduler, schedule_to_ics, validate_request};
+
+use axum::extract::{Path, Query, rejection::QueryRejection};
+use axum_responses::{FileResponse, FileResult, http::HttpResponse};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct StoredCalendarQuery {
+    /// Date of day 0, as `YYYY-MM-DD`.
+    pub start_date: String,
+    /// Defaults to the configured `CALENDAR_DAY_START`.
+    pub day_start: Option<String>,
+    /// Defaults to the slot length of the stored request, then 60.
+    pub slot_length_minutes: Option<u32>,
+}
+
+/// Schedules the request and returns the scheduled activities as an
+/// iCalendar file instead of JSON.
+pub async fn schedule_calendar_controller(
+    AuthenticatedUser(claims): AuthenticatedUser,
+    SchedulingBody(CalendarRequest { request, calendar }): SchedulingBody<CalendarRequest>,
+) -> FileResult {
+    let request = with_stored_records(request).map_err(scheduler_error_response)?;
+
+    validate_request(&request).map_err(scheduler_error_response)?;
+
+    let request = request.normalized();
+    let options = request.scheduler_options();
+
+    let mut activities = request.activities;
+
+    resolve_time_slots(&mut activities, request.slot_length_minutes, request.grid_start)
+        .map_err(scheduler_error_response)?;
+
+    let rooms = request.rooms;
+    let audit = AuditContext::new(&claims, "/api/v1/rooms/schedule/ics", activities.len(), rooms.len());
+
+    let schedule = run_blocking(move || {
+        let result = metrics::observe_solve(|| run_scheduler(activities, rooms, &options));
+        audit.record(&result);
+        result
+    })
+    .await?
+        .map_err(scheduler_error_response)?;
+
+    let ics = schedule_to_ics(&schedule.scheduled_activities, &calendar, request.slot_length_minutes)
+        .map_err(scheduler_error_response)?;
+
+    Ok(FileResponse::builder()
+        .bytes(ics.into_bytes())
+        .filename("schedule.ics")
+        .content_type("text/calendar; charset=utf-8")
+    )
+}
+
+/// Returns a stored schedule as an iCalendar file. Slots are mapped to
+/// times by the configured slot table, or from `day_start` when there is
+/// none.
+pub async fn stored_schedule_calendar_controller(
+    AuthenticatedUser(claims): AuthenticatedUser,
+    Path(schedule_id): Path<String>,
+    query: std::result::Result<Query<StoredCalendarQuery>, QueryRejection>,
+) -> FileResult {
+    let Query(query) = query.map_err(|err| HttpResponse::BadRequest().error(err.body_text()))?;
+    let config = config::load_env();
+
+    let repository = schedule_repository()
+        .ok_or_else(|| HttpResponse::NotFound().error("Schedule persistence is disabled."))?;
+
+    let schedule = {
+        let schedule_id = schedule_id.clone();
+        run_store(move || repository.find_for_user(&schedule_id, claims.user_id)).await?
+    }
+    .ok_or_else(|| HttpResponse::NotFound().error(format!("Schedule {} not found.", schedule_id)))?;
+
+    let calendar = CalendarMapping {
+        start_date: query.start_date,
+        day_start: query.day_start.unwrap_or_else(|| config.calendar_day_start.to_string()),
+        slot_length_minutes: query.slot_length_minutes,
+        slot_times: config.calendar_slot_times.iter().map(|time| time.to_string()).collect(),
+    };
+
+    let ics = schedule_to_ics(&schedule.scheduled_activities, &calendar, schedule.request.slot_length_minutes)
+        .map_err(scheduler_error_response)?;
+
+    Ok(FileResponse::builder()
+        .bytes(ics.into_bytes())
+        .filename(&format!("schedule-{}.ics", schedule_id))
+        .content_type("text/calendar; charset=utf-8")
+    )
+}
@@ -0,0 +1,72 @@
+use crate::extractors::AuthenticatedUser;
+use crate::jobs::{self, Job, JobEvent, JobStatus};
+use crate::websocket::{WebSocket, WebSocketUpgrade};
+
+use axum::{extract::Path, response::Response};
+use axum_responses::{Result, http::HttpResponse};
+use serde_json::json;
+use tokio::sync::broadcast::{Receiver, error::RecvError};
+
+pub async fn get_schedule_job_controller(
+    AuthenticatedUser(claims): AuthenticatedUser,
+    Path(job_id): Path<String>,
+) -> Result<HttpResponse> {
+    let job = jobs::find_for_user(&job_id, claims.user_id)
+        .ok_or_else(|| HttpResponse::NotFound().error(format!("Job {} not found.", job_id)))?;
+
+    Ok(HttpResponse::Ok()
+        .message("job retrieved successfully")
+        .data(job)
+    )
+}
+
+/// Streams the job over a WebSocket: a `progress` message per processed
+/// slot, then a `done` or `failed` message carrying the job as polled, and
+/// a close. A job that already finished gets the last two right away.
+pub async fn schedule_job_socket_controller(
+    AuthenticatedUser(claims): AuthenticatedUser,
+    Path(job_id): Path<String>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response> {
+    let (job, events) = jobs::subscribe(&job_id, claims.user_id)
+        .ok_or_else(|| HttpResponse::NotFound().error(format!("Job {} not found.", job_id)))?;
+
+    Ok(upgrade.on_upgrade(move |socket| async move {
+        if let Err(err) = stream_job(socket, job, events).await {
+            tracing::debug!(job_id = %job_id, error = %err, "Job socket closed early");
+        }
+    }))
+}
+
+async fn stream_job(mut socket: WebSocket, job: Job, mut events: Receiver<JobEvent>) -> std::io::Result<()> {
+    if let Some(progress) = job.progress {
+        socket.send_text(&json!({ "event": "progress", "data": progress }).to_string()).await?;
+    }
+
+    let mut finished = match job.status {
+        JobStatus::Done | JobStatus::Failed => Some(job),
+        JobStatus::Pending | JobStatus::Running => None,
+    };
+
+    while finished.is_none() {
+        match events.recv().await {
+            Ok(JobEvent::Progress(progress)) => {
+                socket.send_text(&json!({ "event": "progress", "data": progress }).to_string()).await?;
+            }
+            Ok(JobEvent::Finished(job)) => finished = Some(*job),
+            // Slow clients skip progress they fell behind on
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        }
+    }
+
+    if let Some(job) = finished {
+        let event = match job.status {
+            JobStatus::Failed => "failed",
+            _ => "done",
+        };
+        socket.send_text(&json!({ "event": event, "data": job }).to_string()).await?;
+    }
+
+    socket.close().await
+}
@@ -0,0 +1,185 @@
+use std::convert::Infallible;
+
+use crate::{audit::AuditContext, blocking::schedule_timeout, config, errors::scheduler_error_response, metrics};
+use crate::extractors::{AuthenticatedUser, SchedulingBody, parse_scheduling_json};
+use crate::repositories::with_stored_records;
+use crate::websocket::{WebSocket, WebSocketUpgrade};
+use rooms_scheduler_core::{ActivitiesRequest, Activity, Room, Schedule, SchedulerError, SchedulerOptions, SchedulingProgress, models::Claims, resolve_time_slots, run_scheduler_with_progress, validate_request};
+
+use axum::{body::to_bytes, response::{IntoResponse, Response, sse::{Event, Sse}}};
+use axum_responses::{Result, http::HttpResponse};
+use futures_util::stream::{self, Stream};
+use serde_json::{Value, json};
+use tokio::sync::mpsc;
+
+/// What the scheduling thread reports to a socket.
+enum SocketEvent {
+    Progress(SchedulingProgress),
+    Finished(std::result::Result<Schedule, SchedulerError>),
+}
+
+/// Streams one `progress` event per processed slot (per day for strategies
+/// other than greedy), then a final `result` (or `error`) event carrying the
+/// same payload as the schedule endpoint.
+/// Past `SCHEDULE_TIMEOUT_MS` the stream ends with a timeout `error` event.
+pub async fn schedule_stream_controller(
+    AuthenticatedUser(claims): AuthenticatedUser,
+    SchedulingBody(body): SchedulingBody<ActivitiesRequest>,
+) -> Result<Sse<impl Stream<Item = std::result::Result<Event, Infallible>>>> {
+    let (activities, rooms, options) = prepare(body).map_err(scheduler_error_response)?;
+
+    let audit = AuditContext::new(&claims, "/api/v1/rooms/schedule/stream", activities.len(), rooms.len());
+    let (sender, receiver) = mpsc::unbounded_channel::<Event>();
+
+    let span = tracing::Span::current();
+
+    tokio::task::spawn_blocking(move || {
+        let _entered = span.enter();
+        let result = metrics::observe_solve(|| {
+            run_scheduler_with_progress(activities, rooms, &options, &mut |progress| {
+                let _ = sender.send(Event::default().event("progress").data(json!(progress).to_string()));
+            })
+        });
+        audit.record(&result);
+
+        let event = match result {
+            Ok(schedule) => Event::default()
+                .event("result")
+                .data(json!(schedule).to_string()),
+            Err(err) => Event::default()
+                .event("error")
+                .data(json!({ "error": err.to_string() }).to_string()),
+        };
+
+        let _ = sender.send(event);
+    });
+
+    let deadline = schedule_timeout().map(|timeout| (tokio::time::Instant::now() + timeout, timeout));
+
+    let events = stream::unfold(Some(receiver), move |receiver| async move {
+        let mut receiver = receiver?;
+
+        let event = match deadline {
+            Some((deadline, timeout)) => match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(event) => event,
+                Err(_) => {
+                    let event = Event::default().event("error").data(
+                        json!({ "error": format!("Scheduling did not finish within {} ms.", timeout.as_millis()) }).to_string()
+                    );
+                    return Some((Ok(event), None));
+                }
+            },
+            None => receiver.recv().await,
+        };
+
+        event.map(|event| (Ok(event), Some(receiver)))
+    });
+
+    Ok(Sse::new(events))
+}
+
+/// Takes the `ActivitiesRequest` as the first message of the socket, then
+/// sends a `progress` message per processed slot, a `result` message with
+/// the schedule (or an `error` message with the envelope the schedule
+/// endpoint would answer with), and a close.
+pub async fn schedule_socket_controller(
+    AuthenticatedUser(claims): AuthenticatedUser,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    upgrade.on_upgrade(move |socket| async move {
+        if let Err(err) = stream_schedule(socket, claims).await {
+            tracing::debug!(error = %err, "Schedule socket closed early");
+        }
+    })
+}
+
+async fn stream_schedule(mut socket: WebSocket, claims: Claims) -> std::io::Result<()> {
+    let Some(message) = socket.receive_text(config::load_env().max_body_bytes).await? else {
+        return Ok(());
+    };
+
+    let prepared = parse_scheduling_json::<ActivitiesRequest>(message.as_bytes())
+        .and_then(|body| prepare(body).map_err(scheduler_error_response));
+
+    let (activities, rooms, options) = match prepared {
+        Ok(prepared) => prepared,
+        Err(response) => {
+            socket.send_text(&error_message(response).await).await?;
+            return socket.close().await;
+        }
+    };
+
+    let audit = AuditContext::new(&claims, "/api/v1/rooms/schedule/ws", activities.len(), rooms.len());
+    let (sender, mut receiver) = mpsc::unbounded_channel::<SocketEvent>();
+
+    let span = tracing::Span::current();
+
+    tokio::task::spawn_blocking(move || {
+        let _entered = span.enter();
+        let result = metrics::observe_solve(|| {
+            run_scheduler_with_progress(activities, rooms, &options, &mut |progress| {
+                let _ = sender.send(SocketEvent::Progress(progress));
+            })
+        });
+        audit.record(&result);
+
+        let _ = sender.send(SocketEvent::Finished(result));
+    });
+
+    let deadline = schedule_timeout().map(|timeout| (tokio::time::Instant::now() + timeout, timeout));
+
+    loop {
+        let event = match deadline {
+            Some((deadline, timeout)) => match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(event) => event,
+                Err(_) => {
+                    let response = HttpResponse::GatewayTimeout()
+                        .error(format!("Scheduling did not finish within {} ms.", timeout.as_millis()));
+                    socket.send_text(&error_message(response).await).await?;
+                    break;
+                }
+            },
+            None => receiver.recv().await,
+        };
+
+        match event {
+            Some(SocketEvent::Progress(progress)) => {
+                socket.send_text(&json!({ "event": "progress", "data": progress }).to_string()).await?;
+            }
+            Some(SocketEvent::Finished(Ok(schedule))) => {
+                socket.send_text(&json!({ "event": "result", "data": schedule }).to_string()).await?;
+                break;
+            }
+            Some(SocketEvent::Finished(Err(err))) => {
+                socket.send_text(&error_message(scheduler_error_response(err)).await).await?;
+                break;
+            }
+            None => break,
+        }
+    }
+
+    socket.close().await
+}
+
+/// Resolves stored records and time slots, rejecting what the schedule
+/// endpoint would reject.
+fn prepare(body: ActivitiesRequest) -> std::result::Result<(Vec<Activity>, Vec<Room>, SchedulerOptions), SchedulerError> {
+    let body = with_stored_records(body)?;
+
+    validate_request(&body)?;
+
+    let options = body.scheduler_options();
+
+    let mut activities = body.activities;
+
+    resolve_time_slots(&mut activities, body.slot_length_minutes, body.grid_start)?;
+
+    Ok((activities, body.rooms, options))
+}
+
+async fn error_message(response: HttpResponse) -> String {
+    let body = to_bytes(response.into_response().into_body(), usize::MAX).await.unwrap_or_default();
+    let envelope: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+    json!({ "event": "error", "data": envelope }).to_string()
+}
@@ -0,0 +1,22 @@
+use crate::{errors::scheduler_error_response, extractors::SchedulingBody};
+use crate::repositories::with_stored_records;
+use rooms_scheduler_core::{ActivitiesRequest, validate_request};
+
+use axum_responses::{Result, http::HttpResponse};
+use serde_json::json;
+
+/// Runs every input check of the schedule endpoint without solving; an
+/// invalid request gets the same 422 body as a schedule request would.
+pub async fn schedule_validation_controller(SchedulingBody(body): SchedulingBody<ActivitiesRequest>) -> Result<HttpResponse> {
+    let body = with_stored_records(body).map_err(scheduler_error_response)?;
+
+    validate_request(&body).map_err(scheduler_error_response)?;
+
+    Ok(HttpResponse::Ok()
+        .message("request is valid")
+        .data(json!({
+            "activities": body.activities.len(),
+            "rooms": body.rooms.len(),
+        }))
+    )
+}
@@ -0,0 +1,43 @@
+use axum_responses::http::HttpResponse;
+use rooms_scheduler_core::SchedulerError;
+use serde_json::{Value, json};
+
+pub fn scheduler_error_response(err: SchedulerError) -> HttpResponse {
+    let details = scheduler_error_details(&err);
+
+    match err {
+        SchedulerError::NotEnoughRooms(_) | SchedulerError::PinConflict(_) => HttpResponse::Conflict().error(details),
+        SchedulerError::Validation(_) => HttpResponse::UnprocessableEntity().error(details),
+        SchedulerError::InvalidInput(_) => HttpResponse::BadRequest().error(details),
+        SchedulerError::Internal(_) => HttpResponse::InternalServerError().error(details),
+    }
+}
+
+/// HTTP status the error maps to, for responses that embed several errors.
+pub fn scheduler_error_status(err: &SchedulerError) -> u16 {
+    match err {
+        SchedulerError::NotEnoughRooms(_) | SchedulerError::PinConflict(_) => 409,
+        SchedulerError::Validation(_) => 422,
+        SchedulerError::InvalidInput(_) => 400,
+        SchedulerError::Internal(_) => 500,
+    }
+}
+
+/// The `error` payload of `scheduler_error_response`.
+pub fn scheduler_error_details(err: &SchedulerError) -> Value {
+    match err {
+        SchedulerError::NotEnoughRooms(shortfalls) => json!({
+            "message": err.to_string(),
+            "conflicts": shortfalls,
+        }),
+        SchedulerError::PinConflict(conflicts) => json!({
+            "message": err.to_string(),
+            "conflicts": conflicts,
+        }),
+        SchedulerError::Validation(errors) => json!({
+            "message": err.to_string(),
+            "errors": errors,
+        }),
+        _ => json!(err.to_string()),
+    }
+}
@@ -0,0 +1,285 @@
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, FromRequestParts, Request, rejection::BytesRejection},
+    http::{StatusCode, header, request::Parts},
+};
+use axum_responses::http::HttpResponse;
+use rooms_scheduler_core::models::Claims;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value, json};
+
+use crate::config;
+
+pub struct JsonBody<T>(pub T);
+
+impl<S, T> FromRequest<S> for JsonBody<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = HttpResponse;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !has_json_content_type(&req) {
+            return Err(HttpResponse::UnsupportedMediaType()
+                .error("Expected request with `Content-Type: application/json`."));
+        }
+
+        let bytes = Bytes::from_request(req, state)
+            .await
+            .map_err(body_rejection)?;
+
+        parse_json(&bytes).map(JsonBody)
+    }
+}
+
+/// A scheduling request body, with the configured `scheduler.defaults` filled
+/// in for the fields it leaves out. Arrays (batches) get them per element.
+pub struct SchedulingBody<T>(pub T);
+
+impl<S, T> FromRequest<S> for SchedulingBody<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = HttpResponse;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let defaults = &config::load_env().scheduler_defaults;
+        if defaults.is_empty() {
+            return JsonBody::from_request(req, state).await.map(|JsonBody(body)| SchedulingBody(body));
+        }
+
+        let JsonBody(mut body) = JsonBody::<Value>::from_request(req, state).await?;
+
+        match &mut body {
+            Value::Array(items) => items.iter_mut().for_each(|item| apply_defaults(item, defaults)),
+            body => apply_defaults(body, defaults),
+        }
+
+        // Parsed again from bytes to keep the error paths of `parse_json`
+        let bytes = serde_json::to_vec(&body).map_err(|err| HttpResponse::BadRequest().error(err.to_string()))?;
+        parse_json(&bytes).map(SchedulingBody)
+    }
+}
+
+/// Parses a scheduling request that did not come as an HTTP body, with the
+/// defaults and errors of `SchedulingBody`.
+pub fn parse_scheduling_json<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, HttpResponse> {
+    let defaults = &config::load_env().scheduler_defaults;
+    if defaults.is_empty() {
+        return parse_json(bytes);
+    }
+
+    let mut body: Value = parse_json(bytes)?;
+    apply_defaults(&mut body, defaults);
+
+    let bytes = serde_json::to_vec(&body).map_err(|err| HttpResponse::BadRequest().error(err.to_string()))?;
+    parse_json(&bytes)
+}
+
+pub fn apply_defaults(body: &mut Value, defaults: &Map<String, Value>) {
+    if let Value::Object(fields) = body {
+        for (key, value) in defaults {
+            fields.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    }
+}
+
+/// The parts of a `multipart/form-data` body, in order.
+pub struct MultipartForm(pub Vec<FormPart>);
+
+/// One field of a `multipart/form-data` body.
+pub struct FormPart {
+    pub name: String,
+    pub contents: Vec<u8>,
+}
+
+impl<S> FromRequest<S> for MultipartForm
+where
+    S: Send + Sync,
+{
+    type Rejection = HttpResponse;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let boundary = multipart_boundary(&req).ok_or_else(|| {
+            HttpResponse::UnsupportedMediaType()
+                .error("Expected request with `Content-Type: multipart/form-data` and a boundary.")
+        })?;
+
+        let bytes = Bytes::from_request(req, state)
+            .await
+            .map_err(body_rejection)?;
+
+        parse_multipart(&bytes, &boundary)
+            .map(MultipartForm)
+            .ok_or_else(|| HttpResponse::BadRequest().error("Malformed multipart/form-data body."))
+    }
+}
+
+/// 413 when the body is over `MAX_BODY_BYTES`, 400 for any other read failure.
+fn body_rejection(err: BytesRejection) -> HttpResponse {
+    match err.status() {
+        StatusCode::PAYLOAD_TOO_LARGE => HttpResponse::PayloadTooLarge().error(format!(
+            "Request body is larger than the {} byte limit.",
+            config::load_env().max_body_bytes
+        )),
+        _ => HttpResponse::BadRequest().error(err.body_text()),
+    }
+}
+
+fn multipart_boundary(req: &Request) -> Option<String> {
+    let content_type = req.headers().get(header::CONTENT_TYPE)?.to_str().ok()?;
+    let mut params = content_type.split(';');
+
+    if !params.next()?.trim().eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+
+    params
+        .filter_map(|param| param.trim().split_once('='))
+        .find(|(key, _)| key.eq_ignore_ascii_case("boundary"))
+        .map(|(_, boundary)| boundary.trim_matches('"').to_string())
+        .filter(|boundary| !boundary.is_empty())
+}
+
+/// Splits a body on its boundary delimiters (RFC 7578). Parts without a
+/// `Content-Disposition` name are dropped.
+fn parse_multipart(body: &[u8], boundary: &str) -> Option<Vec<FormPart>> {
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let mut rest = &body[find(body, &delimiter)? + delimiter.len()..];
+    let mut parts = Vec::new();
+
+    loop {
+        if rest.starts_with(b"--") {
+            return Some(parts);
+        }
+
+        rest = rest.strip_prefix(b"\r\n")?;
+
+        let headers_end = find(rest, b"\r\n\r\n")?;
+        let headers = std::str::from_utf8(&rest[..headers_end]).ok()?;
+        let contents_start = headers_end + 4;
+        let contents_end = contents_start + find(&rest[contents_start..], &delimiter)?;
+
+        let disposition = headers
+            .split("\r\n")
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-disposition"))
+            .map(|(_, value)| value);
+
+        let param = |key: &str| {
+            disposition?
+                .split(';')
+                .filter_map(|param| param.trim().split_once('='))
+                .find(|(name, _)| name.eq_ignore_ascii_case(key))
+                .map(|(_, value)| value.trim_matches('"').to_string())
+        };
+
+        if let Some(name) = param("name") {
+            let contents = &rest[contents_start..contents_end];
+
+            parts.push(FormPart {
+                name,
+                contents: contents.strip_suffix(b"\r\n").unwrap_or(contents).to_vec(),
+            });
+        }
+
+        rest = &rest[contents_end + delimiter.len()..];
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+/// Verified claims inserted by `require_access_token`.
+pub struct AuthenticatedUser(pub Claims);
+
+impl<S> FromRequestParts<S> for AuthenticatedUser
+where
+    S: Send + Sync,
+{
+    type Rejection = HttpResponse;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Claims>()
+            .cloned()
+            .map(AuthenticatedUser)
+            .ok_or(HttpResponse::Unauthorized())
+    }
+}
+
+pub fn parse_json<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, HttpResponse> {
+    let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+
+    let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|err| {
+        let path = err.path().to_string();
+        let inner = err.into_inner();
+
+        let response = match inner.classify() {
+            serde_json::error::Category::Data => HttpResponse::UnprocessableEntity(),
+            _ => HttpResponse::BadRequest(),
+        };
+
+        response.error(json!({
+            "message": inner.to_string(),
+            "path": path,
+        }))
+    })?;
+
+    deserializer
+        .end()
+        .map_err(|err| HttpResponse::BadRequest().error(json!({ "message": err.to_string(), "path": "." })))?;
+
+    Ok(value)
+}
+
+fn has_json_content_type(req: &Request) -> bool {
+    req.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|mime| {
+            let mime = mime.trim();
+            mime.eq_ignore_ascii_case("application/json")
+                || (mime.starts_with("application/") && mime.ends_with("+json"))
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, body::Body, http::Request, routing::get};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn app() -> Router {
+        Router::new().route("/", get(|AuthenticatedUser(claims): AuthenticatedUser| async move { claims.user_id.to_string() }))
+    }
+
+    fn claims(user_id: i64) -> Claims {
+        serde_json::from_value(json!({ "sub": "planner@example.edu", "exp": 0, "user_id": user_id, "rol": "administrator", "type": "access" })).unwrap()
+    }
+
+    #[tokio::test]
+    async fn handlers_read_the_verified_claims() {
+        let mut request = Request::new(Body::empty());
+        request.extensions_mut().insert(claims(42));
+
+        let response = app().oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        assert_eq!(&body[..], b"42");
+    }
+
+    #[tokio::test]
+    async fn missing_claims_are_401() {
+        let response = app().oneshot(Request::new(Body::empty())).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
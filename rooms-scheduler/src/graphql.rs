@@ -0,0 +1,403 @@
+//! A small GraphQL subset, executed over the JSON form of stored records:
+//! query operations with nested selections, aliases, arguments and
+//! variables. Fragments, directives, mutations and subscriptions are not
+//! supported, and fields a record does not have resolve to `null`.
+
+use serde_json::{Map, Number, Value};
+
+#[derive(Debug)]
+pub struct Field {
+    pub alias: Option<String>,
+    pub name: String,
+    pub arguments: Map<String, Value>,
+    pub selection: Vec<Field>,
+}
+
+impl Field {
+    fn response_key(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Punct(char),
+    Spread,
+    Name(String),
+    Number(Number),
+    Str(String),
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+
+    while let Some(&ch) = chars.peek() {
+        match ch {
+            ' ' | '\t' | '\n' | '\r' | ',' | '\u{feff}' => {
+                chars.next();
+            }
+            '#' => {
+                while chars.next_if(|&ch| ch != '\n').is_some() {}
+            }
+            '{' | '}' | '(' | ')' | '[' | ']' | ':' | '$' | '!' | '=' | '@' => {
+                chars.next();
+                tokens.push(Token::Punct(ch));
+            }
+            '.' => {
+                let dots: String = std::iter::from_fn(|| chars.next_if_eq(&'.')).collect();
+                if dots != "..." {
+                    return Err("Unexpected `.`.".to_string());
+                }
+                tokens.push(Token::Spread);
+            }
+            '"' => {
+                chars.next();
+                let mut text = String::new();
+
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => text.push('\n'),
+                            Some('t') => text.push('\t'),
+                            Some('r') => text.push('\r'),
+                            Some('u') => {
+                                let code: String = (0..4).filter_map(|_| chars.next()).collect();
+                                let ch = u32::from_str_radix(&code, 16).ok().and_then(char::from_u32);
+                                text.push(ch.ok_or_else(|| format!("Invalid escape `\\u{}`.", code))?);
+                            }
+                            Some(escaped @ ('"' | '\\' | '/')) => text.push(escaped),
+                            _ => return Err("Invalid escape in string.".to_string()),
+                        },
+                        Some('\n') | None => return Err("Unterminated string.".to_string()),
+                        Some(ch) => text.push(ch),
+                    }
+                }
+
+                tokens.push(Token::Str(text));
+            }
+            ch if ch == '-' || ch.is_ascii_digit() => {
+                let literal: String =
+                    std::iter::from_fn(|| chars.next_if(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '+' | '.'))).collect();
+                let number = serde_json::from_str::<Number>(&literal).map_err(|_| format!("Invalid number `{}`.", literal))?;
+                tokens.push(Token::Number(number));
+            }
+            ch if ch == '_' || ch.is_ascii_alphabetic() => {
+                let name: String = std::iter::from_fn(|| chars.next_if(|ch| *ch == '_' || ch.is_ascii_alphanumeric())).collect();
+                tokens.push(Token::Name(name));
+            }
+            ch => return Err(format!("Unexpected character `{}`.", ch)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Selection sets, lists and objects nested deeper than this are refused,
+/// since parsing them recurses on the worker thread's stack.
+const MAX_DEPTH: usize = 32;
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    variables: &'a Map<String, Value>,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, punct: char) -> bool {
+        if self.peek() == Some(&Token::Punct(punct)) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, punct: char) -> Result<(), String> {
+        match self.eat(punct) {
+            true => Ok(()),
+            false => Err(format!("Expected `{}`.", punct)),
+        }
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        match self.next() {
+            Some(Token::Name(name)) => Ok(name),
+            _ => Err("Expected a name.".to_string()),
+        }
+    }
+
+    fn document(&mut self) -> Result<Vec<Field>, String> {
+        if let Some(Token::Name(keyword)) = self.peek().cloned() {
+            if keyword != "query" {
+                return Err(format!("Only query operations are supported, not `{}`.", keyword));
+            }
+            self.position += 1;
+
+            if let Some(Token::Name(_)) = self.peek() {
+                self.position += 1;
+            }
+
+            // Variable types and defaults are not checked; values come from `variables`
+            if self.eat('(') {
+                while !self.eat(')') {
+                    self.next().ok_or("Unterminated variable definitions.")?;
+                }
+            }
+        }
+
+        let selection = self.selection_set()?;
+
+        if self.peek().is_some() {
+            return Err("Only a single operation per document is supported.".to_string());
+        }
+
+        Ok(selection)
+    }
+
+    /// Runs `parse` one nesting level deeper.
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T, String>) -> Result<T, String> {
+        if self.depth == MAX_DEPTH {
+            return Err("Query is nested too deeply.".to_string());
+        }
+
+        self.depth += 1;
+        let parsed = parse(self);
+        self.depth -= 1;
+        parsed
+    }
+
+    fn selection_set(&mut self) -> Result<Vec<Field>, String> {
+        self.expect('{')?;
+        let fields = self.nested(|parser| {
+            let mut fields = Vec::new();
+            while !parser.eat('}') {
+                fields.push(parser.field()?);
+            }
+            Ok(fields)
+        })?;
+
+        match fields.is_empty() {
+            true => Err("Selections cannot be empty.".to_string()),
+            false => Ok(fields),
+        }
+    }
+
+    fn field(&mut self) -> Result<Field, String> {
+        match self.peek() {
+            Some(Token::Spread) => return Err("Fragments are not supported.".to_string()),
+            Some(Token::Punct('@')) => return Err("Directives are not supported.".to_string()),
+            _ => {}
+        }
+
+        let mut name = self.name()?;
+        let mut alias = None;
+
+        if self.eat(':') {
+            alias = Some(name);
+            name = self.name()?;
+        }
+
+        let mut arguments = Map::new();
+        if self.eat('(') {
+            while !self.eat(')') {
+                let argument = self.name()?;
+                self.expect(':')?;
+                let value = self.value()?;
+                arguments.insert(argument, value);
+            }
+        }
+
+        if self.peek() == Some(&Token::Punct('@')) {
+            return Err("Directives are not supported.".to_string());
+        }
+
+        let selection = match self.peek() {
+            Some(Token::Punct('{')) => self.selection_set()?,
+            _ => Vec::new(),
+        };
+
+        Ok(Field { alias, name, arguments, selection })
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        match self.next() {
+            Some(Token::Punct('$')) => {
+                let name = self.name()?;
+                Ok(self.variables.get(&name).cloned().unwrap_or(Value::Null))
+            }
+            Some(Token::Number(number)) => Ok(Value::Number(number)),
+            Some(Token::Str(text)) => Ok(Value::String(text)),
+            Some(Token::Name(name)) => Ok(match name.as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                "null" => Value::Null,
+                // Enum values, passed as their name
+                _ => Value::String(name),
+            }),
+            Some(Token::Punct('[')) => self.nested(|parser| {
+                let mut items = Vec::new();
+                while !parser.eat(']') {
+                    items.push(parser.value()?);
+                }
+                Ok(Value::Array(items))
+            }),
+            Some(Token::Punct('{')) => self.nested(|parser| {
+                let mut object = Map::new();
+                while !parser.eat('}') {
+                    let key = parser.name()?;
+                    parser.expect(':')?;
+                    object.insert(key, parser.value()?);
+                }
+                Ok(Value::Object(object))
+            }),
+            _ => Err("Expected a value.".to_string()),
+        }
+    }
+}
+
+/// Parses a query document into its root fields, substituting variables.
+pub fn parse_query(query: &str, variables: &Map<String, Value>) -> Result<Vec<Field>, String> {
+    Parser { tokens: tokenize(query)?, position: 0, variables, depth: 0 }.document()
+}
+
+/// Resolves every root field with `resolve_root` and keeps only the
+/// selected parts. Arguments of list fields below the root filter their
+/// items; see `matches_filters`.
+pub fn execute(fields: &[Field], resolve_root: impl Fn(&Field) -> Result<Value, String>) -> Result<Value, String> {
+    let mut data = Map::new();
+
+    for field in fields {
+        let value = resolve_root(field)?;
+        data.insert(field.response_key().to_string(), project(value, &field.selection, &field.name)?);
+    }
+
+    Ok(Value::Object(data))
+}
+
+fn project(value: Value, selection: &[Field], path: &str) -> Result<Value, String> {
+    match value {
+        Value::Array(items) => items
+            .into_iter()
+            .map(|item| project(item, selection, path))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array),
+        Value::Object(mut object) => {
+            if selection.is_empty() {
+                return Err(format!("Field `{}` needs a selection of subfields.", path));
+            }
+
+            let mut projected = Map::new();
+
+            for field in selection {
+                let path = format!("{}.{}", path, field.name);
+                let mut value = object.remove(&field.name).unwrap_or(Value::Null);
+
+                if !field.arguments.is_empty() {
+                    value = filter(value, &field.arguments, &path)?;
+                }
+
+                // A field may be selected twice under different aliases
+                object.insert(field.name.clone(), value.clone());
+                projected.insert(field.response_key().to_string(), project(value, &field.selection, &path)?);
+            }
+
+            Ok(Value::Object(projected))
+        }
+        scalar if selection.is_empty() => Ok(scalar),
+        Value::Null => Ok(Value::Null),
+        _ => Err(format!("Field `{}` has no subfields to select.", path)),
+    }
+}
+
+/// Keeps the items of a list that match every argument, then applies
+/// `offset` and `limit`.
+pub fn filter(value: Value, arguments: &Map<String, Value>, path: &str) -> Result<Value, String> {
+    let Value::Array(items) = value else {
+        return Err(format!("Field `{}` takes no arguments.", path));
+    };
+
+    let offset = count_argument(arguments, "offset", path)?.unwrap_or(0);
+    let limit = count_argument(arguments, "limit", path)?.unwrap_or(usize::MAX);
+
+    let mut kept = Vec::new();
+    for item in items {
+        if matches_filters(&item, arguments, path)? {
+            kept.push(item);
+        }
+    }
+
+    Ok(Value::Array(kept.into_iter().skip(offset).take(limit).collect()))
+}
+
+fn count_argument(arguments: &Map<String, Value>, name: &str, path: &str) -> Result<Option<usize>, String> {
+    match arguments.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => value
+            .as_u64()
+            .map(|count| Some(count as usize))
+            .ok_or_else(|| format!("Argument `{}` of `{}` must be a non-negative integer.", name, path)),
+    }
+}
+
+/// Filters on rooms, activities and schedules:
+/// - `id`, `day`, `reason`, `building`, `subject` (case-insensitive): the
+///   field equals the argument;
+/// - `room_id`: the activity is held in that room, alone or split;
+/// - `slot_from`, `slot_to`: the activity holds a slot in that range;
+/// - `min_capacity`: the room seats at least that many;
+/// - `feature`: the room has that feature.
+fn matches_filters(item: &Value, arguments: &Map<String, Value>, path: &str) -> Result<bool, String> {
+    let slot_from = arguments.get("slot_from").and_then(Value::as_u64);
+    let slot_to = arguments.get("slot_to").and_then(Value::as_u64);
+
+    for (name, expected) in arguments {
+        if expected.is_null() {
+            continue;
+        }
+
+        let matches = match name.as_str() {
+            "offset" | "limit" => true,
+            "id" | "day" | "reason" | "building" => item.get(name) == Some(expected),
+            "subject" => match (item.get("subject").and_then(Value::as_str), expected.as_str()) {
+                (Some(subject), Some(expected)) => subject.eq_ignore_ascii_case(expected),
+                _ => false,
+            },
+            "room_id" => {
+                let room = item.get("room").and_then(|room| room.get("id"));
+                let mut split_rooms = item.get("split_rooms").and_then(Value::as_array).into_iter().flatten();
+                room == Some(expected) || split_rooms.any(|room| room.get("id") == Some(expected))
+            }
+            "slot_from" | "slot_to" => item
+                .get("time_slots")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_u64)
+                .any(|slot| slot_from.is_none_or(|from| slot >= from) && slot_to.is_none_or(|to| slot <= to)),
+            "min_capacity" => match (item.get("capacity").and_then(Value::as_u64), expected.as_u64()) {
+                (Some(capacity), Some(minimum)) => capacity >= minimum,
+                _ => false,
+            },
+            "feature" => item.get("features").and_then(Value::as_array).is_some_and(|features| features.contains(expected)),
+            _ => return Err(format!("Unknown argument `{}` on field `{}`.", name, path)),
+        };
+
+        if !matches {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
@@ -0,0 +1,65 @@
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use lazy_static::lazy_static;
+use serde::Serialize;
+
+use crate::{config, controllers::rooms_scheduler::ScheduleOutcome};
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+struct IdempotentEntry {
+    body_hash: u64,
+    stored_at: Instant,
+    result: IdempotentResult,
+}
+
+/// What the first request under a key produced.
+#[derive(Clone)]
+pub enum IdempotentResult {
+    /// The schedule of a synchronous run.
+    Schedule(ScheduleOutcome),
+    /// The job started by a `Prefer: respond-async` run.
+    Job(String),
+}
+
+pub enum IdempotencyLookup {
+    Miss,
+    Hit(IdempotentResult),
+    Conflict,
+}
+
+lazy_static! {
+    static ref STORE: Mutex<HashMap<String, IdempotentEntry>> = Mutex::new(HashMap::new());
+}
+
+pub fn hash_body<T: Serialize>(body: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(body).unwrap_or_default().hash(&mut hasher);
+    hasher.finish()
+}
+
+pub fn lookup(key: &str, body_hash: u64) -> IdempotencyLookup {
+    let ttl = Duration::from_secs(config::load_env().idempotency_ttl_seconds);
+    let mut store = STORE.lock().unwrap();
+
+    store.retain(|_, entry| entry.stored_at.elapsed() < ttl);
+
+    match store.get(key) {
+        None => IdempotencyLookup::Miss,
+        Some(entry) if entry.body_hash != body_hash => IdempotencyLookup::Conflict,
+        Some(entry) => IdempotencyLookup::Hit(entry.result.clone()),
+    }
+}
+
+pub fn store(key: &str, body_hash: u64, result: IdempotentResult) {
+    STORE.lock().unwrap().insert(key.to_string(), IdempotentEntry {
+        body_hash,
+        stored_at: Instant::now(),
+        result,
+    });
+}
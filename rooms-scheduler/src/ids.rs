@@ -0,0 +1,8 @@
+/// Random identifier in the UUID v4 textual layout.
+pub fn new_id() -> String {
+    let bits = rand::random::<u128>();
+    let bits = (bits & !(0xf << 76) | (0x4 << 76)) & !(0x3 << 62) | (0x2 << 62);
+    let hex = format!("{:032x}", bits);
+
+    format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32])
+}
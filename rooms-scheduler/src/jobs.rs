@@ -0,0 +1,205 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use lazy_static::lazy_static;
+use rooms_scheduler_core::{Activity, Room, Schedule, SchedulerOptions, SchedulingProgress, run_scheduler_with_progress};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::broadcast;
+use tracing::Instrument;
+
+use crate::{audit::AuditContext, config, errors::scheduler_error_details, ids::new_id, metrics, webhooks::{self, Callback}};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Done,
+    Failed,
+}
+
+/// A scheduling run started with `Prefer: respond-async`, polled through
+/// `GET /api/v1/rooms/schedule/jobs/{job_id}`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Job {
+    pub job_id: String,
+    #[serde(skip)]
+    pub user_id: i64,
+    pub status: JobStatus,
+    pub created_at: String,
+    /// Latest slot the run got through.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<SchedulingProgress>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Schedule>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<Value>,
+    #[serde(skip)]
+    finished: Option<Instant>,
+    #[serde(skip)]
+    events: broadcast::Sender<JobEvent>,
+}
+
+/// What subscribers of a running job are told.
+#[derive(Debug, Clone)]
+pub enum JobEvent {
+    Progress(SchedulingProgress),
+    /// The job as it finished; no events follow.
+    Finished(Box<Job>),
+}
+
+lazy_static! {
+    static ref JOBS: Mutex<HashMap<String, Job>> = Mutex::new(HashMap::new());
+}
+
+/// Queues a scheduling run on the blocking pool and returns its job id.
+/// Jobs exist for long runs, so `SCHEDULE_TIMEOUT_MS` does not apply.
+/// `callback` is notified once the job finishes, and the run is recorded
+/// under `audit`.
+pub fn submit(
+    user_id: i64,
+    activities: Vec<Activity>,
+    rooms: Vec<Room>,
+    options: SchedulerOptions,
+    callback: Option<Callback>,
+    audit: AuditContext,
+) -> String {
+    let job_id = new_id();
+    let audit = audit.with_job(&job_id);
+
+    {
+        let mut jobs = JOBS.lock().unwrap();
+        prune_finished(&mut jobs);
+
+        jobs.insert(job_id.clone(), Job {
+            job_id: job_id.clone(),
+            user_id,
+            status: JobStatus::Pending,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            progress: None,
+            finished_at: None,
+            duration_ms: None,
+            result: None,
+            error: None,
+            finished: None,
+            events: broadcast::channel(64).0,
+        });
+    }
+
+    let task_job_id = job_id.clone();
+    let span = tracing::info_span!("job", job_id = %job_id);
+    let task_span = span.clone();
+
+    tokio::spawn(async move {
+        let blocking_job_id = task_job_id.clone();
+
+        let outcome = tokio::task::spawn_blocking(move || {
+            let _entered = task_span.enter();
+            update(&blocking_job_id, |job| job.status = JobStatus::Running);
+
+            let started_at = Instant::now();
+            let result = metrics::observe_solve(|| {
+                run_scheduler_with_progress(activities, rooms, &options, &mut |progress| {
+                    update(&blocking_job_id, |job| {
+                        job.progress = Some(progress);
+                        let _ = job.events.send(JobEvent::Progress(progress));
+                    });
+                })
+            });
+            audit.record(&result);
+            (result, started_at.elapsed().as_millis() as u64)
+        })
+        .await;
+
+        update(&task_job_id, |job| {
+            job.finished_at = Some(chrono::Utc::now().to_rfc3339());
+            job.finished = Some(Instant::now());
+
+            match outcome {
+                Ok((Ok(schedule), duration_ms)) => {
+                    job.status = JobStatus::Done;
+                    job.duration_ms = Some(duration_ms);
+                    job.result = Some(schedule);
+                }
+                Ok((Err(err), duration_ms)) => {
+                    job.status = JobStatus::Failed;
+                    job.duration_ms = Some(duration_ms);
+                    job.error = Some(scheduler_error_details(&err));
+                }
+                Err(err) => {
+                    job.status = JobStatus::Failed;
+                    job.error = Some(Value::String(format!("Scheduling task failed: {}", err)));
+                }
+            }
+
+            let _ = job.events.send(JobEvent::Finished(Box::new(job.clone())));
+
+            if let Some(callback) = callback {
+                webhooks::notify(callback, job);
+            }
+        });
+    }.instrument(span));
+
+    job_id
+}
+
+/// Jobs queued or running, failing when the job queue is unusable.
+pub fn active_count() -> Result<usize, String> {
+    let jobs = JOBS.lock().map_err(|_| "A job update panicked while holding the job queue.".to_string())?;
+    Ok(jobs.values().filter(|job| job.finished.is_none()).count())
+}
+
+/// Waits until no job is queued or running, for at most `grace`. Returns
+/// how many jobs were still unfinished.
+pub async fn drain(grace: Duration) -> usize {
+    let deadline = Instant::now() + grace;
+
+    loop {
+        let active = active_count().unwrap_or(0);
+
+        if active == 0 || Instant::now() >= deadline {
+            return active;
+        }
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+/// The job with `job_id`, only when it was submitted by `user_id`.
+pub fn find_for_user(job_id: &str, user_id: i64) -> Option<Job> {
+    let mut jobs = JOBS.lock().unwrap();
+    prune_finished(&mut jobs);
+
+    jobs.get(job_id).filter(|job| job.user_id == user_id).cloned()
+}
+
+/// The job with `job_id` as it is now, only when it was submitted by
+/// `user_id`, and a receiver for its later events.
+pub fn subscribe(job_id: &str, user_id: i64) -> Option<(Job, broadcast::Receiver<JobEvent>)> {
+    let mut jobs = JOBS.lock().unwrap();
+    prune_finished(&mut jobs);
+
+    jobs.get(job_id)
+        .filter(|job| job.user_id == user_id)
+        .map(|job| (job.clone(), job.events.subscribe()))
+}
+
+fn update(job_id: &str, apply: impl FnOnce(&mut Job)) {
+    if let Some(job) = JOBS.lock().unwrap().get_mut(job_id) {
+        apply(job);
+    }
+}
+
+/// Finished jobs are kept for `JOB_TTL_SECONDS` so clients can collect them.
+fn prune_finished(jobs: &mut HashMap<String, Job>) {
+    let ttl = Duration::from_secs(config::load_env().job_ttl_seconds);
+    jobs.retain(|_, job| job.finished.is_none_or(|finished| finished.elapsed() < ttl));
+}
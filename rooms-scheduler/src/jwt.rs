@@ -1,12 +1,19 @@
-use jsonwebtoken::{decode, DecodingKey, Validation, Algorithm};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation, Algorithm};
+use lazy_static::lazy_static;
+use serde::Serialize;
 
-use crate::{config, models::Claims};
+use rooms_scheduler_core::models::{Audience, Claims};
+
+use crate::config::{self, Config};
+use crate::repositories::users::User;
 
 #[derive(Debug)]
 pub enum JwtVerificationError {
     UnsupportedAlgorithm(String),
     InvalidTokenType,
     MissingClaims,
+    /// No public key is configured for the token's algorithm.
+    UnknownKey,
     InvalidToken(jsonwebtoken::errors::Error),
 }
 
@@ -16,25 +23,132 @@ impl From<jsonwebtoken::errors::Error> for JwtVerificationError {
     }
 }
 
+/// An access token and the refresh token that renews it.
+#[derive(Debug, Serialize)]
+pub struct TokenPair {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub token_type: &'static str,
+    /// Seconds until the access token expires.
+    pub expires_in: u64,
+    /// Seconds until the refresh token expires.
+    pub refresh_expires_in: u64,
+}
+
+lazy_static! {
+    /// `JWT_PUBLIC_KEY`, parsed once.
+    static ref PUBLIC_KEY: Option<DecodingKey> = load_public_key();
+}
+
+fn algorithm(config: &Config) -> Result<Algorithm, JwtVerificationError> {
+    match config.jwt_algorithm.as_str() {
+        "HS256" => Ok(Algorithm::HS256),
+        "HS384" => Ok(Algorithm::HS384),
+        "HS512" => Ok(Algorithm::HS512),
+        "RS256" => Ok(Algorithm::RS256),
+        "RS384" => Ok(Algorithm::RS384),
+        "RS512" => Ok(Algorithm::RS512),
+        "PS256" => Ok(Algorithm::PS256),
+        "PS384" => Ok(Algorithm::PS384),
+        "PS512" => Ok(Algorithm::PS512),
+        "ES256" => Ok(Algorithm::ES256),
+        "ES384" => Ok(Algorithm::ES384),
+        "EdDSA" => Ok(Algorithm::EdDSA),
+        other => Err(JwtVerificationError::UnsupportedAlgorithm(other.to_string())),
+    }
+}
+
+/// The algorithm tokens are signed with here. Only shared secrets can sign;
+/// with an asymmetric algorithm the identity provider issues every token.
+fn signing_algorithm(config: &Config) -> Result<Algorithm, JwtVerificationError> {
+    match algorithm(config)? {
+        algorithm @ (Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512) => Ok(algorithm),
+        _ => Err(JwtVerificationError::UnsupportedAlgorithm(config.jwt_algorithm.to_string())),
+    }
+}
+
+fn load_public_key() -> Option<DecodingKey> {
+    let config = config::load_env();
+    let setting = config.jwt_public_key.as_deref()?;
+
+    // Either the PEM itself or the path of a file holding it
+    let pem = match setting.contains("-----BEGIN") {
+        true => setting.replace("\\n", "\n"),
+        false => match std::fs::read_to_string(setting) {
+            Ok(pem) => pem,
+            Err(err) => {
+                tracing::error!(path = %setting, error = %err, "Could not read JWT_PUBLIC_KEY");
+                return None;
+            }
+        },
+    };
+
+    let key = match algorithm(&config) {
+        Ok(Algorithm::ES256 | Algorithm::ES384) => DecodingKey::from_ec_pem(pem.as_bytes()),
+        Ok(Algorithm::EdDSA) => DecodingKey::from_ed_pem(pem.as_bytes()),
+        _ => DecodingKey::from_rsa_pem(pem.as_bytes()),
+    };
+
+    key.map_err(|err| tracing::error!(error = %err, "JWT_PUBLIC_KEY is not a valid {} public key", config.jwt_algorithm))
+        .ok()
+}
+
+/// Verifies an access token: with `JWT_SECRET` for HS* algorithms, and
+/// otherwise with `JWT_PUBLIC_KEY`.
 pub fn verify_token(token: &str) -> Result<Claims, JwtVerificationError> {
     let config = config::load_env();
+    let algorithm = algorithm(&config)?;
 
-    let algorithm = match config.jwt_algorithm {
-        "HS256" => Algorithm::HS256,
-        "HS384" => Algorithm::HS384,
-        "HS512" => Algorithm::HS512,
-        other => return Err(JwtVerificationError::UnsupportedAlgorithm(other.to_string())),
+    let key = match algorithm {
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => DecodingKey::from_secret(config.jwt_secret.as_bytes()),
+        _ => PUBLIC_KEY.clone().ok_or(JwtVerificationError::UnknownKey)?,
     };
 
+    verify(token, &key, algorithm, "access")
+}
+
+/// Verifies a token issued by `issue_tokens` for renewal, signed with
+/// `JWT_REFRESH_SECRET_KEY`.
+pub fn verify_refresh_token(token: &str) -> Result<Claims, JwtVerificationError> {
+    let config = config::load_env();
+    let key = DecodingKey::from_secret(config.jwt_refresh_secret_key.as_bytes());
+
+    verify(token, &key, signing_algorithm(&config)?, "refresh")
+}
+
+fn verify(token: &str, key: &DecodingKey, algorithm: Algorithm, token_type: &str) -> Result<Claims, JwtVerificationError> {
+    let config = config::load_env();
+
     let mut validation = Validation::new(algorithm);
-    validation.validate_exp = true;
+    validation.validate_exp = config.jwt_validate_exp;
+    validation.leeway = config.jwt_leeway_seconds;
+
+    let mut required_claims = Vec::new();
+
+    if config.jwt_validate_exp {
+        required_claims.push("exp");
+    }
+
+    if let Some(issuer) = &config.jwt_issuer {
+        validation.set_issuer(&[issuer]);
+        required_claims.push("iss");
+    }
+
+    match &config.jwt_audience {
+        Some(audience) => {
+            validation.set_audience(&[audience]);
+            required_claims.push("aud");
+        }
+        None => validation.validate_aud = false,
+    }
+
+    validation.set_required_spec_claims(&required_claims);
 
-    let decoding_key = DecodingKey::from_secret(config.jwt_secret.as_bytes());
-    let claims = decode::<Claims>(token, &decoding_key, &validation)
+    let claims = decode::<Claims>(token, key, &validation)
         .map_err(JwtVerificationError::from)?
         .claims;
 
-    if claims.token_type != "access" {
+    if claims.token_type != token_type {
         return Err(JwtVerificationError::InvalidTokenType);
     }
 
@@ -44,3 +158,39 @@ pub fn verify_token(token: &str) -> Result<Claims, JwtVerificationError> {
 
     Ok(claims)
 }
+
+/// Signs a new access token with `JWT_SECRET` and refresh token with
+/// `JWT_REFRESH_SECRET_KEY` for `user`, valid for `JWT_EXPIRE_MINUTES` and
+/// `JWT_REFRESH_EXPIRE_DAYS`. Fails with an asymmetric `JWT_ALGORITHM`.
+pub fn issue_tokens(user: &User) -> Result<TokenPair, JwtVerificationError> {
+    let config = config::load_env();
+    let header = Header::new(signing_algorithm(&config)?);
+
+    let expires_in = config.jwt_expire_minutes as u64 * 60;
+    let refresh_expires_in = config.jwt_refresh_expire_days as u64 * 24 * 60 * 60;
+
+    let claims = |token_type: &str, lifetime: u64| Claims {
+        sub: user.email.clone(),
+        exp: (chrono::Utc::now().timestamp() as u64 + lifetime) as usize,
+        user_id: user.user_id,
+        rol: user.rol.clone(),
+        token_type: token_type.to_string(),
+        iss: config.jwt_issuer.clone(),
+        aud: config.jwt_audience.clone().map(Audience::Single),
+        permissions: user.permissions.clone(),
+    };
+
+    let access_token = encode(
+        &header,
+        &claims("access", expires_in),
+        &EncodingKey::from_secret(config.jwt_secret.as_bytes()),
+    )?;
+
+    let refresh_token = encode(
+        &header,
+        &claims("refresh", refresh_expires_in),
+        &EncodingKey::from_secret(config.jwt_refresh_secret_key.as_bytes()),
+    )?;
+
+    Ok(TokenPair { access_token, refresh_token, token_type: "Bearer", expires_in, refresh_expires_in })
+}
@@ -0,0 +1,29 @@
+//! HTTP service around the `rooms_scheduler_core` algorithm. The binary
+//! serves `router::create_router`; tests drive the same router in process.
+
+pub mod config;
+pub mod router;
+mod controllers;
+mod middlewares;
+mod jwt;
+pub mod logging;
+mod extractors;
+mod errors;
+mod graphql;
+mod audit;
+mod blocking;
+pub mod cli;
+mod config_file;
+mod idempotency;
+mod ids;
+pub mod jobs;
+mod metrics;
+mod passwords;
+mod permissions;
+mod postgres;
+mod rate_limit;
+mod repositories;
+mod schedule_cache;
+pub mod telemetry;
+mod webhooks;
+mod websocket;
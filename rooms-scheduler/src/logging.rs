@@ -0,0 +1,325 @@
+//! Writes `tracing` events to stdout, one line each, carrying the fields of
+//! every span the event happened in (such as the request id). A span
+//! created inside another keeps it as its parent, so work handed to another
+//! task still logs the request it came from. Closed spans are handed to
+//! `telemetry` for export when tracing is enabled.
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::Debug,
+    io::Write,
+    sync::{Mutex, atomic::{AtomicU64, Ordering}},
+    time::SystemTime,
+};
+
+use chrono::SecondsFormat;
+use serde_json::{Map, Value};
+use tracing::{
+    Event, Metadata, Subscriber,
+    field::{Field, Visit},
+    level_filters::LevelFilter,
+    span,
+};
+
+use crate::config;
+use crate::telemetry::{self, FinishedSpan, SpanContext};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Text,
+    Json,
+}
+
+struct SpanData {
+    metadata: &'static Metadata<'static>,
+    parent: Option<u64>,
+    fields: Vec<(&'static str, Value)>,
+    references: usize,
+    context: SpanContext,
+    parent_span_id: Option<u64>,
+    started: SystemTime,
+}
+
+struct Logger {
+    level: LevelFilter,
+    format: Format,
+    next_id: AtomicU64,
+    spans: Mutex<HashMap<u64, SpanData>>,
+}
+
+thread_local! {
+    /// Spans entered on this thread, innermost last.
+    static ENTERED: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Installs the logger as the global subscriber, configured by `LOG_LEVEL`
+/// and `LOG_FORMAT`.
+pub fn init() {
+    let config = config::load_env();
+
+    let level = match config.log_level.to_ascii_lowercase().as_str() {
+        "error" => LevelFilter::ERROR,
+        "warn" => LevelFilter::WARN,
+        "debug" => LevelFilter::DEBUG,
+        "trace" => LevelFilter::TRACE,
+        "off" => LevelFilter::OFF,
+        _ => LevelFilter::INFO,
+    };
+
+    let format = match config.log_format.eq_ignore_ascii_case("json") {
+        true => Format::Json,
+        false => Format::Text,
+    };
+
+    let logger = Logger { level, format, next_id: AtomicU64::new(1), spans: Mutex::new(HashMap::new()) };
+
+    if tracing::subscriber::set_global_default(logger).is_err() {
+        eprintln!("A tracing subscriber was already installed; keeping it.");
+    }
+}
+
+/// Collects the fields of a span or event.
+#[derive(Default)]
+struct Fields(Vec<(&'static str, Value)>);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.push((field.name(), Value::String(format!("{:?}", value))));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push((field.name(), Value::String(value.to_string())));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.push((field.name(), Value::from(value)));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.push((field.name(), Value::from(value)));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.push((field.name(), Value::from(value)));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.push((field.name(), Value::from(value)));
+    }
+}
+
+impl Subscriber for Logger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.level >= *metadata.level()
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(self.level)
+    }
+
+    fn new_span(&self, attributes: &span::Attributes<'_>) -> span::Id {
+        let mut fields = Fields::default();
+        attributes.record(&mut fields);
+
+        let remote_parent = fields
+            .0
+            .iter()
+            .position(|(name, _)| *name == telemetry::TRACEPARENT_HEADER)
+            .and_then(|index| fields.0.remove(index).1.as_str().and_then(SpanContext::from_traceparent));
+
+        let parent = match attributes.parent() {
+            Some(parent) => Some(parent.into_u64()),
+            None if attributes.is_contextual() => current_span(),
+            None => None,
+        };
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut spans = self.spans.lock().unwrap();
+
+        // A child keeps its parent open
+        let local_parent = parent.and_then(|parent| spans.get_mut(&parent)).map(|data| {
+            data.references += 1;
+            data.context
+        });
+
+        let (context, parent_span_id) = match remote_parent.or(local_parent) {
+            Some(parent) => (SpanContext::child_of(parent), Some(parent.span_id)),
+            None => (SpanContext::root(), None),
+        };
+
+        spans.insert(id, SpanData {
+            metadata: attributes.metadata(),
+            parent,
+            fields: fields.0,
+            references: 1,
+            context,
+            parent_span_id,
+            started: SystemTime::now(),
+        });
+
+        span::Id::from_u64(id)
+    }
+
+    fn record(&self, span: &span::Id, values: &span::Record<'_>) {
+        let mut fields = Fields::default();
+        values.record(&mut fields);
+
+        if let Some(data) = self.spans.lock().unwrap().get_mut(&span.into_u64()) {
+            data.fields.extend(fields.0);
+        }
+    }
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+
+        let message = fields
+            .0
+            .iter()
+            .position(|(name, _)| *name == "message")
+            .map(|index| fields.0.remove(index).1)
+            .and_then(|message| message.as_str().map(str::to_string))
+            .unwrap_or_default();
+
+        let leaf = match event.parent() {
+            Some(parent) => Some(parent.into_u64()),
+            None if event.is_contextual() => current_span(),
+            None => None,
+        };
+
+        // Outermost span first
+        let spans = self.spans.lock().unwrap();
+        let mut entered: Vec<&SpanData> = std::iter::successors(leaf.and_then(|id| spans.get(&id)), |span| {
+            span.parent.and_then(|parent| spans.get(&parent))
+        })
+        .collect();
+        entered.reverse();
+
+        let metadata = event.metadata();
+        let timestamp = chrono::Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+
+        let line = match self.format {
+            Format::Json => {
+                let mut object = Map::new();
+                object.insert("timestamp".to_string(), Value::String(timestamp));
+                object.insert("level".to_string(), Value::String(metadata.level().to_string()));
+                object.insert("target".to_string(), Value::String(metadata.target().to_string()));
+                for (name, value) in entered.iter().flat_map(|span| &span.fields).chain(&fields.0) {
+                    if !name.starts_with("otel.") {
+                        object.insert(name.to_string(), value.clone());
+                    }
+                }
+                object.insert("message".to_string(), Value::String(message));
+                Value::Object(object).to_string()
+            }
+            Format::Text => {
+                let mut line = format!("{} {:>5} ", timestamp, metadata.level());
+                for span in &entered {
+                    line.push_str(&format!("{}{{{}}}:", span.metadata.name(), text_fields(&span.fields)));
+                }
+                if !entered.is_empty() {
+                    line.push(' ');
+                }
+                line.push_str(&message);
+                if !fields.0.is_empty() {
+                    line.push(' ');
+                    line.push_str(&text_fields(&fields.0));
+                }
+                line
+            }
+        };
+
+        drop(spans);
+
+        let mut stdout = std::io::stdout().lock();
+        let _ = writeln!(stdout, "{}", line);
+    }
+
+    fn enter(&self, span: &span::Id) {
+        ENTERED.with(|entered| entered.borrow_mut().push(span.into_u64()));
+    }
+
+    fn exit(&self, span: &span::Id) {
+        ENTERED.with(|entered| {
+            let mut entered = entered.borrow_mut();
+            if let Some(index) = entered.iter().rposition(|id| *id == span.into_u64()) {
+                entered.remove(index);
+            }
+        });
+    }
+
+    fn current_span(&self) -> tracing_core::span::Current {
+        let spans = self.spans.lock().unwrap();
+
+        match current_span().and_then(|id| spans.get(&id).map(|data| (id, data.metadata))) {
+            Some((id, metadata)) => tracing_core::span::Current::new(span::Id::from_u64(id), metadata),
+            None => tracing_core::span::Current::none(),
+        }
+    }
+
+    fn clone_span(&self, span: &span::Id) -> span::Id {
+        if let Some(data) = self.spans.lock().unwrap().get_mut(&span.into_u64()) {
+            data.references += 1;
+        }
+        span.clone()
+    }
+
+    fn try_close(&self, span: span::Id) -> bool {
+        let mut spans = self.spans.lock().unwrap();
+        let mut closing = Some(span.into_u64());
+        let mut closed = false;
+
+        // Closing a span releases its hold on its parent
+        while let Some(id) = closing {
+            let Some(data) = spans.get_mut(&id) else {
+                break;
+            };
+
+            data.references -= 1;
+            if data.references > 0 {
+                break;
+            }
+
+            let Some(data) = spans.remove(&id) else {
+                break;
+            };
+
+            if telemetry::is_enabled() {
+                telemetry::record(FinishedSpan {
+                    context: data.context,
+                    parent_span_id: data.parent_span_id,
+                    name: data.metadata.name(),
+                    start: data.started,
+                    end: SystemTime::now(),
+                    fields: data.fields,
+                });
+            }
+
+            closing = data.parent;
+            closed |= id == span.into_u64();
+        }
+
+        closed
+    }
+}
+
+fn current_span() -> Option<u64> {
+    ENTERED.with(|entered| entered.borrow().last().copied())
+}
+
+/// `name=value` pairs separated by spaces, with strings unquoted. Export
+/// hints (`otel.*`) are left out.
+fn text_fields(fields: &[(&'static str, Value)]) -> String {
+    fields
+        .iter()
+        .filter(|(name, _)| !name.starts_with("otel."))
+        .map(|(name, value)| match value {
+            Value::String(value) => format!("{}={}", name, value),
+            value => format!("{}={}", name, value),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -1,23 +1,80 @@
-mod config;
-mod router;
-mod controllers;
-mod models;
-mod middlewares;
-mod scheduler;
-mod jwt;
+use std::{net::SocketAddr, time::Duration};
 
-use crate::router::create_router;
+use rooms_scheduler::router::create_router;
+use rooms_scheduler::{cli, config, jobs, logging, telemetry};
 
 #[tokio::main]
 async fn main() {
+    if let Some(code) = cli::run(std::env::args().skip(1).collect()) {
+        std::process::exit(code);
+    }
 
     let config = config::load_env();
+    logging::init();
+    telemetry::start_exporter();
+    reload_on_hangup();
 
     let address = format!("0.0.0.0:{}", config.port);
     let listener = tokio::net::TcpListener::bind(address).await.unwrap();
 
     let app = create_router();
 
-    println!("Iniciando servicio asignador de salas en: 0.0.0.0:{} ...", config.port);
-    axum::serve(listener, app).await.unwrap();
+    tracing::info!(port = config.port, "Iniciando servicio asignador de salas");
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(shutdown_signal())
+        .await
+        .unwrap();
+
+    // Requests are done; async jobs may still be running
+    let grace = Duration::from_secs(config.shutdown_grace_seconds);
+    let active = jobs::active_count().unwrap_or(0);
+    if active > 0 {
+        tracing::info!(jobs = active, grace_seconds = grace.as_secs(), "Waiting for scheduling jobs to finish");
+    }
+
+    match jobs::drain(grace).await {
+        0 => tracing::info!("Shutdown complete"),
+        abandoned => tracing::warn!(jobs = abandoned, "Shutting down with unfinished scheduling jobs"),
+    }
+
+    telemetry::flush().await;
+}
+
+/// Reloads the configuration on every SIGHUP.
+fn reload_on_hangup() {
+    #[cfg(unix)]
+    tokio::spawn(async {
+        let mut hangups = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+            .expect("SIGHUP handler can be installed");
+
+        while hangups.recv().await.is_some() {
+            if let Err(err) = config::reload() {
+                tracing::error!(error = %err, "Configuration not reloaded");
+            }
+        }
+    });
+}
+
+/// Resolves on SIGINT or SIGTERM, after which the server stops accepting
+/// connections and finishes the requests in flight.
+async fn shutdown_signal() {
+    let interrupt = async {
+        tokio::signal::ctrl_c().await.expect("SIGINT handler can be installed");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("SIGTERM handler can be installed")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => tracing::info!(signal = "SIGINT", "Shutting down"),
+        _ = terminate => tracing::info!(signal = "SIGTERM", "Shutting down"),
+    }
 }
\ No newline at end of file
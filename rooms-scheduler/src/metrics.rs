@@ -0,0 +1,153 @@
+//! Process-wide counters and histograms, rendered in the Prometheus text
+//! exposition format by `GET /metrics`.
+
+use std::{collections::BTreeMap, sync::Mutex, time::Instant};
+
+use axum::{extract::{MatchedPath, Request}, middleware::Next, response::Response};
+use lazy_static::lazy_static;
+use rooms_scheduler_core::Schedule;
+
+/// Upper bounds, in seconds, of the solve duration buckets.
+const DURATION_BUCKETS: [f64; 12] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+/// Upper bounds of the room utilization buckets, as a share of room slots.
+const RATIO_BUCKETS: [f64; 10] = [0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0];
+
+struct Family {
+    help: &'static str,
+    kind: &'static str,
+}
+
+const FAMILIES: [(&str, Family); 5] = [
+    ("rooms_scheduler_scheduling_requests_total", Family { help: "Scheduling requests handled, by route and status code.", kind: "counter" }),
+    ("rooms_scheduler_solve_duration_seconds", Family { help: "Time spent in the scheduler per run.", kind: "histogram" }),
+    ("rooms_scheduler_activities_total", Family { help: "Activities placed by scheduler runs, by outcome.", kind: "counter" }),
+    ("rooms_scheduler_room_utilization_ratio", Family { help: "Share of room slots occupied in each computed schedule.", kind: "histogram" }),
+    ("rooms_scheduler_jwt_verification_failures_total", Family { help: "Rejected access tokens, by reason.", kind: "counter" }),
+];
+
+struct Histogram {
+    bounds: &'static [f64],
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+#[derive(Default)]
+struct Registry {
+    counters: BTreeMap<(&'static str, String), u64>,
+    histograms: BTreeMap<&'static str, Histogram>,
+}
+
+lazy_static! {
+    static ref REGISTRY: Mutex<Registry> = Mutex::new(Registry::default());
+}
+
+fn labels(pairs: &[(&str, &str)]) -> String {
+    pairs
+        .iter()
+        .map(|(name, value)| format!("{}=\"{}\"", name, value.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn increment(name: &'static str, pairs: &[(&str, &str)], by: u64) {
+    *REGISTRY.lock().unwrap().counters.entry((name, labels(pairs))).or_default() += by;
+}
+
+fn observe(name: &'static str, bounds: &'static [f64], value: f64) {
+    let mut registry = REGISTRY.lock().unwrap();
+    let histogram = registry.histograms.entry(name).or_insert_with(|| Histogram {
+        bounds,
+        counts: vec![0; bounds.len()],
+        sum: 0.0,
+        count: 0,
+    });
+
+    for (bound, count) in histogram.bounds.iter().zip(histogram.counts.iter_mut()) {
+        if value <= *bound {
+            *count += 1;
+        }
+    }
+    histogram.sum += value;
+    histogram.count += 1;
+}
+
+/// Counts every request to the routes it wraps by matched route and
+/// response status.
+pub async fn track_scheduling_requests(req: Request, next: Next) -> Response {
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+
+    let response = next.run(req).await;
+
+    increment(
+        "rooms_scheduler_scheduling_requests_total",
+        &[("route", &route), ("status", response.status().as_str())],
+        1,
+    );
+
+    response
+}
+
+/// Runs the scheduler through `run`, recording how long it took and, when
+/// it succeeds, what the schedule placed.
+pub fn observe_solve<E>(run: impl FnOnce() -> Result<Schedule, E>) -> Result<Schedule, E> {
+    let started_at = Instant::now();
+    let result = run();
+
+    observe("rooms_scheduler_solve_duration_seconds", &DURATION_BUCKETS, started_at.elapsed().as_secs_f64());
+
+    if let Ok(schedule) = &result {
+        record_schedule(schedule);
+    }
+
+    result
+}
+
+fn record_schedule(schedule: &Schedule) {
+    increment("rooms_scheduler_activities_total", &[("outcome", "scheduled")], schedule.scheduled_activities.len() as u64);
+    increment("rooms_scheduler_activities_total", &[("outcome", "unscheduled")], schedule.unscheduled_activities.len() as u64);
+
+    let slots = schedule.room_timelines.iter().flat_map(|timeline| &timeline.slots);
+    let (occupied, total) = slots.fold((0usize, 0usize), |(occupied, total), slot| {
+        (occupied + usize::from(slot.activity_id.is_some()), total + 1)
+    });
+
+    if total > 0 {
+        observe("rooms_scheduler_room_utilization_ratio", &RATIO_BUCKETS, occupied as f64 / total as f64);
+    }
+}
+
+pub fn record_jwt_failure(reason: &str) {
+    increment("rooms_scheduler_jwt_verification_failures_total", &[("reason", reason)], 1);
+}
+
+/// Every metric in the Prometheus text format (version 0.0.4).
+pub fn render() -> String {
+    let registry = REGISTRY.lock().unwrap();
+    let mut output = String::new();
+
+    for (name, family) in FAMILIES {
+        output.push_str(&format!("# HELP {} {}\n# TYPE {} {}\n", name, family.help, name, family.kind));
+
+        for ((_, labels), value) in registry.counters.iter().filter(|((family, _), _)| *family == name) {
+            match labels.is_empty() {
+                true => output.push_str(&format!("{} {}\n", name, value)),
+                false => output.push_str(&format!("{}{{{}}} {}\n", name, labels, value)),
+            }
+        }
+
+        if let Some(histogram) = registry.histograms.get(name) {
+            for (bound, count) in histogram.bounds.iter().zip(&histogram.counts) {
+                output.push_str(&format!("{}_bucket{{le=\"{}\"}} {}\n", name, bound, count));
+            }
+            output.push_str(&format!("{}_bucket{{le=\"+Inf\"}} {}\n", name, histogram.count));
+            output.push_str(&format!("{}_sum {}\n{}_count {}\n", name, histogram.sum, name, histogram.count));
+        }
+    }
+
+    output
+}
@@ -1,48 +1,290 @@
 
+use std::{future::Future, net::SocketAddr, pin::Pin, time::{Duration, Instant}};
+
 use axum_responses::http::HttpResponse;
 use axum::{
-    http::header,
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
     middleware::Next,
     response::Response,
-    extract::Request
+    extract::{ConnectInfo, Request},
 };
+use tracing::Instrument;
+
+use crate::repositories::api_keys::api_key_repository;
+use crate::{config, ids::new_id, jwt::{JwtVerificationError, verify_token}, metrics, permissions, rate_limit, telemetry::TRACEPARENT_HEADER};
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Tags the request with an id (the client's `X-Request-Id` or a new one)
+/// that is echoed in the response and logged with everything the request
+/// does, followed by a line with its status and latency. The request's span
+/// continues the trace of an incoming `traceparent` header.
+pub async fn request_id(req: Request, next: Next) -> Response {
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(new_id);
+
+    let traceparent = req
+        .headers()
+        .get(TRACEPARENT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("");
+
+    let span = tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        method = %req.method(),
+        path = %req.uri().path(),
+        traceparent = traceparent,
+        status = tracing::field::Empty,
+        otel.kind = "server",
+        otel.status_code = tracing::field::Empty,
+    );
+    let started_at = Instant::now();
+
+    let mut response = next.run(req).instrument(span.clone()).await;
+    let status = response.status();
+
+    span.in_scope(|| {
+        tracing::info!(
+            status = status.as_u16(),
+            latency_ms = started_at.elapsed().as_micros() as f64 / 1000.0,
+            "Request completed"
+        );
+    });
+
+    // Recorded after the log line so it is not repeated there
+    span.record("status", status.as_u16());
+    if status.is_server_error() {
+        span.record("otel.status_code", "error");
+    }
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+
+    response
+}
+
+/// Fails requests that take longer than `REQUEST_TIMEOUT_MS` with 408. The
+/// limit covers producing the response head, so streamed bodies (server-sent
+/// events, WebSockets) may outlive it.
+pub async fn request_timeout(req: Request, next: Next) -> Result<Response, HttpResponse> {
+    let timeout = match config::load_env().request_timeout_ms {
+        0 => return Ok(next.run(req).await),
+        ms => Duration::from_millis(ms),
+    };
 
-use crate::jwt::verify_token;
+    tokio::time::timeout(timeout, next.run(req)).await.map_err(|_| {
+        tracing::warn!(timeout_ms = timeout.as_millis() as u64, "Request timed out");
+        HttpResponse::RequestTimeout().error(format!("Request did not complete within {} ms.", timeout.as_millis()))
+    })
+}
+
+/// Answers CORS preflights and lets allowed origins read responses, per the
+/// `CORS_*` settings. Requests from other origins are served without CORS
+/// headers, so browsers keep blocking them.
+pub async fn cors(req: Request, next: Next) -> Response {
+    let config = config::load_env();
+
+    let origin = req
+        .headers()
+        .get(header::ORIGIN)
+        .and_then(|value| value.to_str().ok())
+        .filter(|origin| {
+            config
+                .cors_allowed_origins
+                .iter()
+                .any(|allowed| *allowed == "*" || allowed.eq_ignore_ascii_case(origin))
+        })
+        .map(str::to_string);
+
+    let preflight = req.method() == Method::OPTIONS && req.headers().contains_key(header::ACCESS_CONTROL_REQUEST_METHOD);
+
+    let mut response = match preflight {
+        true => Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(axum::body::Body::empty())
+            .expect("preflight response is valid"),
+        false => next.run(req).await,
+    };
+
+    let Some(origin) = origin else {
+        return response;
+    };
+
+    let headers = response.headers_mut();
+
+    // A wildcard cannot be combined with credentials
+    let any_origin = config.cors_allowed_origins.iter().any(|origin| origin == "*") && !config.cors_allow_credentials;
+    insert_header(headers, header::ACCESS_CONTROL_ALLOW_ORIGIN, if any_origin { "*" } else { &origin });
+    if !any_origin {
+        headers.append(header::VARY, HeaderValue::from_static("origin"));
+    }
+
+    if config.cors_allow_credentials {
+        insert_header(headers, header::ACCESS_CONTROL_ALLOW_CREDENTIALS, "true");
+    }
+
+    match preflight {
+        true => {
+            insert_header(headers, header::ACCESS_CONTROL_ALLOW_METHODS, &config.cors_allowed_methods);
+            insert_header(headers, header::ACCESS_CONTROL_ALLOW_HEADERS, &config.cors_allowed_headers);
+            insert_header(headers, header::ACCESS_CONTROL_MAX_AGE, &config.cors_max_age_seconds.to_string());
+        }
+        false => insert_header(headers, header::ACCESS_CONTROL_EXPOSE_HEADERS, &config.cors_exposed_headers),
+    }
+
+    response
+}
+
+fn insert_header(headers: &mut HeaderMap, name: header::HeaderName, value: &str) {
+    if let Ok(value) = HeaderValue::from_str(value) {
+        headers.insert(name, value);
+    }
+}
 
+/// Authenticates the request with a bearer token or access token cookie, or
+/// with an `X-Api-Key` for service-to-service calls, and adds its claims to
+/// the request.
 pub async fn require_access_token(mut req: Request, next: Next) -> Result<Response, HttpResponse> {
+    if let Some(key) = req.headers().get(API_KEY_HEADER) {
+        let Some(api_key) = key.to_str().ok().and_then(|key| api_key_repository().authenticate(key.trim())) else {
+            tracing::warn!("Rejected API key");
+            return Err(HttpResponse::Unauthorized());
+        };
+
+        tracing::debug!(key_id = api_key.key_id, service = %api_key.name, "Authenticated with API key");
+        req.extensions_mut().insert(api_key.claims());
+        return Ok(next.run(req).await);
+    }
+
+    let cookie_name = config::load_env().access_token_cookie.clone();
+
     let token_encoded = req
         .headers()
         .get(header::AUTHORIZATION)
         .and_then(|value| value.to_str().ok())
-        .and_then(extract_bearer_token);
+        .and_then(extract_bearer_token)
+        .or_else(|| {
+            req.headers()
+                .get_all(header::COOKIE)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .find_map(|value| extract_cookie_token(value, &cookie_name))
+        });
 
     let token = match token_encoded {
             Some(token) => token,
-            None => return Err(HttpResponse::Unauthorized()),
+            None => {
+                metrics::record_jwt_failure("missing");
+                return Err(HttpResponse::Unauthorized());
+            }
         };
 
-    let claims = match verify_token(token) {
+    let claims = match tracing::info_span!("verify_token").in_scope(|| verify_token(token)) {
             Ok(claims) => claims,
-            Err(_) => return Err(HttpResponse::Unauthorized()),
+            Err(err) => {
+                metrics::record_jwt_failure(failure_reason(&err));
+                return Err(HttpResponse::Unauthorized());
+            }
         };
 
     req.extensions_mut().insert(claims);
     Ok(next.run(req).await)
 }
 
-pub async fn require_administrator_role(req: Request, next: Next) -> Result<Response, HttpResponse> {
+/// Limits requests per client address, before authentication.
+pub async fn limit_per_ip(req: Request, next: Next) -> Result<Response, HttpResponse> {
+    let config = config::load_env();
+
+    let forwarded = req
+        .headers()
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next())
+        .map(|address| address.trim().to_string())
+        .filter(|address| config.rate_limit_trust_forwarded_for && !address.is_empty());
+
+    let address = forwarded.or_else(|| {
+        req.extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(address)| address.ip().to_string())
+    });
+
+    if let Some(address) = address {
+        rate_limit::check(format!("ip:{}", address), config.rate_limit_per_ip_per_minute).map_err(too_many_requests)?;
+    }
+
+    Ok(next.run(req).await)
+}
+
+/// Limits requests per authenticated user.
+pub async fn limit_per_user(req: Request, next: Next) -> Result<Response, HttpResponse> {
+    let claims = req
+        .extensions()
+        .get::<rooms_scheduler_core::models::Claims>()
+        .ok_or(HttpResponse::Unauthorized())?;
+
+    rate_limit::check(format!("user:{}", claims.user_id), config::load_env().rate_limit_per_user_per_minute)
+        .map_err(too_many_requests)?;
+
+    Ok(next.run(req).await)
+}
+
+fn too_many_requests(retry_after: Duration) -> HttpResponse {
+    let seconds = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+
+    HttpResponse::TooManyRequests()
+        .error(format!("Too many requests; retry in {} seconds.", seconds))
+        .add_header("retry-after", &seconds.to_string())
+}
+
+type MiddlewareFuture = Pin<Box<dyn Future<Output = Result<Response, HttpResponse>> + Send>>;
+
+/// Rejects with 403 tokens that lack `permission`; see `permissions`.
+pub fn require_permission(
+    permission: &'static str,
+) -> impl Fn(Request, Next) -> MiddlewareFuture + Clone + Send + Sync + 'static {
+    move |req, next| Box::pin(check_permission(permission, req, next))
+}
+
+async fn check_permission(permission: &'static str, req: Request, next: Next) -> Result<Response, HttpResponse> {
     let claims = req
         .extensions()
-        .get::<crate::models::Claims>()
+        .get::<rooms_scheduler_core::models::Claims>()
         .ok_or(HttpResponse::Unauthorized())?;
 
-    if !claims.rol.eq_ignore_ascii_case("administrator") {
-        return Err(HttpResponse::Unauthorized());
+    if !permissions::has_permission(claims, permission) {
+        return Err(HttpResponse::Forbidden().error(format!("This token lacks the `{}` permission.", permission)));
     }
 
     Ok(next.run(req).await)
 }
 
+fn failure_reason(err: &JwtVerificationError) -> &'static str {
+    use jsonwebtoken::errors::ErrorKind;
+
+    match err {
+        JwtVerificationError::UnsupportedAlgorithm(_) => "unsupported_algorithm",
+        JwtVerificationError::InvalidTokenType => "invalid_token_type",
+        JwtVerificationError::MissingClaims => "missing_claims",
+        JwtVerificationError::UnknownKey => "unknown_key",
+        JwtVerificationError::InvalidToken(err) => match err.kind() {
+            ErrorKind::ExpiredSignature => "expired",
+            ErrorKind::InvalidSignature => "invalid_signature",
+            ErrorKind::InvalidIssuer | ErrorKind::InvalidAudience | ErrorKind::MissingRequiredClaim(_) => "invalid_claims",
+            _ => "malformed",
+        },
+    }
+}
+
 fn extract_bearer_token(header_value: &str) -> Option<&str> {
     header_value
         .split_once(' ')
@@ -50,3 +292,12 @@ fn extract_bearer_token(header_value: &str) -> Option<&str> {
         .map(|(_, token)| token.trim())
         .filter(|token| !token.is_empty())
 }
+
+fn extract_cookie_token<'a>(header_value: &'a str, cookie_name: &str) -> Option<&'a str> {
+    header_value
+        .split(';')
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == cookie_name)
+        .map(|(_, token)| token.trim().trim_matches('"'))
+        .filter(|token| !token.is_empty())
+}
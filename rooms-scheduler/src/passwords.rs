@@ -0,0 +1,58 @@
+use base64::{Engine, engine::general_purpose::STANDARD};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+
+/// Checked instead of a real hash when the account does not exist, so
+/// unknown emails take as long to reject as wrong passwords.
+const DUMMY_HASH: &str = "pbkdf2_sha256$600000$dummysalt$BASOJ8fyEZY4l8d5rHO3aJgWNMWRvpC2jQbcQJiWji8=";
+
+/// Checks `password` against a `pbkdf2_sha256$<iterations>$<salt>$<hash>`
+/// string. Malformed hashes never match.
+pub fn verify(password: &str, encoded: &str) -> bool {
+    let mut parts = encoded.split('$');
+
+    let (Some("pbkdf2_sha256"), Some(iterations), Some(salt), Some(hash), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return false;
+    };
+
+    let (Ok(iterations), Ok(expected)) = (iterations.parse::<u32>(), STANDARD.decode(hash)) else {
+        return false;
+    };
+
+    if iterations == 0 {
+        return false;
+    }
+
+    pbkdf2_sha256(password.as_bytes(), salt.as_bytes(), iterations).ct_eq(&expected).into()
+}
+
+/// Spends the time a real check would, for accounts that do not exist.
+pub fn verify_dummy(password: &str) {
+    let _ = verify(password, DUMMY_HASH);
+}
+
+/// PBKDF2 (RFC 8018) with HMAC-SHA256, producing one 32-byte block.
+fn pbkdf2_sha256(password: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+    let key = Hmac::<Sha256>::new_from_slice(password).expect("HMAC accepts keys of any length");
+
+    let mut mac = key.clone();
+    mac.update(salt);
+    mac.update(&1u32.to_be_bytes());
+    let mut block: [u8; 32] = mac.finalize().into_bytes().into();
+
+    let mut result = block;
+    for _ in 1..iterations {
+        let mut mac = key.clone();
+        mac.update(&block);
+        block = mac.finalize().into_bytes().into();
+
+        for (byte, next) in result.iter_mut().zip(block) {
+            *byte ^= next;
+        }
+    }
+
+    result
+}
@@ -0,0 +1,52 @@
+//! Permissions routes require, written `resource:action`. A token holds the
+//! permissions of its role plus those in its `permissions` claim, where a
+//! bare resource (such as the original `schedule`) or `resource:*` grants
+//! every action on it and `*` grants everything.
+
+use rooms_scheduler_core::models::Claims;
+
+/// Reading stored schedules and jobs, and checking proposals against them.
+pub const SCHEDULE_READ: &str = "schedule:read";
+/// Running the scheduler.
+pub const SCHEDULE_WRITE: &str = "schedule:write";
+pub const ROOMS_READ: &str = "rooms:read";
+pub const ROOMS_WRITE: &str = "rooms:write";
+pub const ACTIVITIES_READ: &str = "activities:read";
+pub const ACTIVITIES_WRITE: &str = "activities:write";
+/// Reading the audit log of scheduling runs.
+pub const AUDIT_READ: &str = "audit:read";
+/// Reloading the configuration without a restart.
+pub const CONFIG_RELOAD: &str = "config:reload";
+
+/// Permissions every token of `rol` holds. Administrators keep the
+/// scheduling and inventory access the role alone used to give, read the
+/// audit log and reload the configuration.
+fn role_permissions(rol: &str) -> &'static [&'static str] {
+    match rol.to_ascii_lowercase().as_str() {
+        "administrator" => &[
+            SCHEDULE_READ,
+            SCHEDULE_WRITE,
+            ROOMS_READ,
+            ROOMS_WRITE,
+            ACTIVITIES_READ,
+            ACTIVITIES_WRITE,
+            AUDIT_READ,
+            CONFIG_RELOAD,
+        ],
+        _ => &[],
+    }
+}
+
+pub fn has_permission(claims: &Claims, permission: &str) -> bool {
+    role_permissions(&claims.rol)
+        .iter()
+        .copied()
+        .chain(claims.permissions.iter().map(String::as_str))
+        .any(|granted| grants(granted, permission))
+}
+
+fn grants(granted: &str, required: &str) -> bool {
+    let resource = required.split_once(':').map_or(required, |(resource, _)| resource);
+
+    granted == "*" || granted == required || granted == resource || granted.strip_suffix(":*") == Some(resource)
+}
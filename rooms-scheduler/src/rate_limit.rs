@@ -0,0 +1,169 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use lazy_static::lazy_static;
+
+/// Buckets kept before full ones are forgotten.
+const MAX_TRACKED_BUCKETS: usize = 10_000;
+
+/// Buckets left once the least recently used ones are dropped, so a flood
+/// of new keys does not sort the map on every request.
+const EVICTION_TARGET: usize = MAX_TRACKED_BUCKETS * 9 / 10;
+
+/// Token bucket holding up to a minute's worth of requests, refilled
+/// continuously. Each bucket keeps the limit it is refilled at, since the
+/// per-user and per-IP limits differ.
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+    capacity: f64,
+    per_second: f64,
+}
+
+impl Bucket {
+    fn tokens_at(&self, now: Instant) -> f64 {
+        (self.tokens + now.duration_since(self.refilled_at).as_secs_f64() * self.per_second).min(self.capacity)
+    }
+}
+
+#[derive(Default)]
+struct Buckets(HashMap<String, Bucket>);
+
+impl Buckets {
+    fn check(&mut self, key: String, per_minute: u32, now: Instant) -> Result<(), Duration> {
+        if per_minute == 0 {
+            return Ok(());
+        }
+
+        let capacity = per_minute as f64;
+        let per_second = capacity / 60.0;
+
+        if self.0.len() >= MAX_TRACKED_BUCKETS && !self.0.contains_key(&key) {
+            self.evict(now);
+        }
+
+        let bucket = self.0.entry(key).or_insert(Bucket { tokens: capacity, refilled_at: now, capacity, per_second });
+
+        // Refill at the old rate up to now, then follow a reloaded limit
+        bucket.tokens = bucket.tokens_at(now).min(capacity);
+        bucket.refilled_at = now;
+        bucket.capacity = capacity;
+        bucket.per_second = per_second;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+
+        Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_second))
+    }
+
+    /// Forgets the buckets that refilled completely, which behave as new
+    /// ones would, then the least recently used ones if too many are left.
+    fn evict(&mut self, now: Instant) {
+        self.0.retain(|_, bucket| bucket.tokens_at(now) < bucket.capacity);
+
+        if self.0.len() >= MAX_TRACKED_BUCKETS {
+            let excess = self.0.len() - EVICTION_TARGET + 1;
+            let mut by_last_use: Vec<(Instant, String)> =
+                self.0.iter().map(|(key, bucket)| (bucket.refilled_at, key.clone())).collect();
+            by_last_use.select_nth_unstable(excess - 1);
+
+            for (_, key) in &by_last_use[..excess] {
+                self.0.remove(key);
+            }
+        }
+    }
+}
+
+lazy_static! {
+    static ref BUCKETS: Mutex<Buckets> = Mutex::new(Buckets::default());
+}
+
+/// Takes one request from the bucket of `key`, allowing `per_minute`
+/// requests a minute. Fails with how long the caller should wait when the
+/// bucket is empty. A limit of 0 allows everything.
+pub fn check(key: String, per_minute: u32) -> Result<(), Duration> {
+    BUCKETS.lock().unwrap().check(key, per_minute, Instant::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_allows_its_limit_then_asks_to_wait() {
+        let mut buckets = Buckets::default();
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            assert!(buckets.check("user:1".to_string(), 3, now).is_ok());
+        }
+
+        let wait = buckets.check("user:1".to_string(), 3, now).unwrap_err();
+        assert_eq!(wait.as_secs(), 20);
+        assert!(buckets.check("user:1".to_string(), 3, now + Duration::from_secs(20)).is_ok());
+    }
+
+    #[test]
+    fn buckets_refill_at_their_own_limit() {
+        let mut buckets = Buckets::default();
+        let now = Instant::now();
+
+        buckets.check("user:1".to_string(), 1, now).unwrap();
+        buckets.check("ip:10.0.0.1".to_string(), 600, now).unwrap();
+
+        let bucket = |key: &str, buckets: &Buckets| buckets.0[key].tokens_at(now + Duration::from_secs(1));
+        assert!(bucket("user:1", &buckets) < 1.0);
+        assert_eq!(bucket("ip:10.0.0.1", &buckets), 600.0);
+    }
+
+    #[test]
+    fn full_buckets_are_evicted_by_their_own_rate() {
+        let mut buckets = Buckets::default();
+        let now = Instant::now();
+
+        // A slow per-user bucket is still refilling when a fast per-IP one is full again
+        buckets.check("user:1".to_string(), 1, now).unwrap();
+        buckets.check("ip:10.0.0.1".to_string(), 600, now).unwrap();
+        buckets.evict(now + Duration::from_secs(1));
+
+        assert!(buckets.0.contains_key("user:1"));
+        assert!(!buckets.0.contains_key("ip:10.0.0.1"));
+    }
+
+    #[test]
+    fn least_recently_used_buckets_go_first_when_none_is_full() {
+        let mut buckets = Buckets::default();
+        let start = Instant::now();
+
+        for index in 0..MAX_TRACKED_BUCKETS {
+            let key = format!("ip:{}", index);
+            buckets.check(key, 1, start + Duration::from_millis(index as u64)).unwrap();
+        }
+
+        let now = start + Duration::from_millis(MAX_TRACKED_BUCKETS as u64);
+        buckets.check("ip:new".to_string(), 1, now).unwrap();
+
+        assert_eq!(buckets.0.len(), EVICTION_TARGET);
+        assert!(buckets.0.contains_key("ip:new"));
+        assert!(buckets.0.contains_key(&format!("ip:{}", MAX_TRACKED_BUCKETS - 1)));
+        assert!(!buckets.0.contains_key("ip:0"));
+    }
+
+    #[test]
+    fn a_tracked_key_is_never_evicted_by_its_own_request() {
+        let mut buckets = Buckets::default();
+        let now = Instant::now();
+
+        for index in 0..MAX_TRACKED_BUCKETS {
+            buckets.check(format!("ip:{}", index), 60, now).unwrap();
+        }
+
+        buckets.check("ip:0".to_string(), 60, now).unwrap();
+        assert_eq!(buckets.0.len(), MAX_TRACKED_BUCKETS);
+    }
+}
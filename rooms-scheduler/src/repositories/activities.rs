@@ -0,0 +1,64 @@
+use std::{collections::BTreeMap, sync::{Arc, RwLock}};
+
+use lazy_static::lazy_static;
+use rooms_scheduler_core::{ActivitiesRequest, Activity, SchedulerError};
+
+/// Activities managed through `/api/v1/activities`, so scheduling requests
+/// can reference them by id instead of embedding them.
+pub trait ActivityRepository: Send + Sync {
+    fn list(&self) -> Vec<Activity>;
+    fn find(&self, activity_id: u32) -> Option<Activity>;
+    /// Inserts or replaces the activity with the same id, returning the previous one.
+    fn save(&self, activity: Activity) -> Option<Activity>;
+    fn delete(&self, activity_id: u32) -> Option<Activity>;
+}
+
+#[derive(Default)]
+pub struct InMemoryActivityRepository {
+    activities: RwLock<BTreeMap<u32, Activity>>,
+}
+
+impl ActivityRepository for InMemoryActivityRepository {
+    fn list(&self) -> Vec<Activity> {
+        self.activities.read().unwrap().values().cloned().collect()
+    }
+
+    fn find(&self, activity_id: u32) -> Option<Activity> {
+        self.activities.read().unwrap().get(&activity_id).cloned()
+    }
+
+    fn save(&self, activity: Activity) -> Option<Activity> {
+        self.activities.write().unwrap().insert(activity.id, activity)
+    }
+
+    fn delete(&self, activity_id: u32) -> Option<Activity> {
+        self.activities.write().unwrap().remove(&activity_id)
+    }
+}
+
+lazy_static! {
+    static ref REPOSITORY: Arc<dyn ActivityRepository> = Arc::new(InMemoryActivityRepository::default());
+}
+
+pub fn activity_repository() -> Arc<dyn ActivityRepository> {
+    REPOSITORY.clone()
+}
+
+/// Appends the stored activities listed in `activity_ids` to the request's inline activities.
+pub fn with_stored_activities(mut request: ActivitiesRequest) -> Result<ActivitiesRequest, SchedulerError> {
+    if request.activity_ids.is_empty() {
+        return Ok(request);
+    }
+
+    let repository = activity_repository();
+
+    for activity_id in std::mem::take(&mut request.activity_ids) {
+        let activity = repository.find(activity_id).ok_or_else(|| {
+            SchedulerError::InvalidInput(format!("Activity {} is not among the stored activities.", activity_id))
+        })?;
+
+        request.activities.push(activity);
+    }
+
+    Ok(request)
+}
@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use lazy_static::lazy_static;
+use rooms_scheduler_core::models::Claims;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+use crate::config;
+
+/// Role of the claims an API key authenticates as.
+pub const SERVICE_ROLE: &str = "service";
+
+/// A key another service sends in `X-Api-Key` instead of a user token.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiKey {
+    pub key_id: i64,
+    /// The calling service, for logs.
+    pub name: String,
+    /// Hex SHA-256 of the key, e.g. from `printf %s "$KEY" | sha256sum`.
+    pub key_hash: String,
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+impl ApiKey {
+    /// Claims for requests made with this key. The user id is the negated
+    /// key id, so schedules a service stores never mix with a user's.
+    pub fn claims(&self) -> Claims {
+        Claims {
+            sub: format!("service:{}", self.name),
+            exp: 0,
+            user_id: -self.key_id,
+            rol: SERVICE_ROLE.to_string(),
+            token_type: "api_key".to_string(),
+            iss: None,
+            aud: None,
+            permissions: self.permissions.clone(),
+        }
+    }
+}
+
+pub trait ApiKeyRepository: Send + Sync {
+    /// The key whose hash matches `key`.
+    fn authenticate(&self, key: &str) -> Option<ApiKey>;
+}
+
+#[derive(Default)]
+pub struct InMemoryApiKeyRepository {
+    keys: Vec<([u8; 32], ApiKey)>,
+}
+
+impl ApiKeyRepository for InMemoryApiKeyRepository {
+    fn authenticate(&self, key: &str) -> Option<ApiKey> {
+        let hash: [u8; 32] = Sha256::digest(key.as_bytes()).into();
+
+        // Every stored hash is compared, in constant time
+        let mut found = None;
+        for (stored, api_key) in &self.keys {
+            if bool::from(stored.ct_eq(&hash)) {
+                found = Some(api_key);
+            }
+        }
+
+        found.cloned()
+    }
+}
+
+lazy_static! {
+    static ref REPOSITORY: Arc<dyn ApiKeyRepository> = build_repository();
+}
+
+/// Reads the keys from `API_KEYS`, a JSON array of keys. Keys with a
+/// malformed hash or a key id below 1 are skipped.
+fn build_repository() -> Arc<dyn ApiKeyRepository> {
+    let keys: Vec<ApiKey> = match config::load_env().api_keys.as_deref() {
+        Some(json) => serde_json::from_str(json).unwrap_or_else(|err| {
+            tracing::error!(error = %err, "API_KEYS is not a valid key list; API keys are disabled");
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+
+    let keys = keys
+        .into_iter()
+        .filter_map(|key| match decode_hash(&key.key_hash) {
+            Some(hash) if key.key_id > 0 => Some((hash, key)),
+            _ => {
+                tracing::error!(key_id = key.key_id, name = %key.name, "Skipping API key with an invalid key_id or key_hash");
+                None
+            }
+        })
+        .collect();
+
+    Arc::new(InMemoryApiKeyRepository { keys })
+}
+
+fn decode_hash(hex: &str) -> Option<[u8; 32]> {
+    let hex = hex.trim();
+    if hex.len() != 64 || !hex.is_ascii() {
+        return None;
+    }
+
+    let mut hash = [0; 32];
+    for (byte, pair) in hash.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+
+    Some(hash)
+}
+
+pub fn api_key_repository() -> Arc<dyn ApiKeyRepository> {
+    REPOSITORY.clone()
+}
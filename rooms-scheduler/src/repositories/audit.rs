@@ -0,0 +1,108 @@
+use std::{collections::VecDeque, sync::{Arc, RwLock}};
+
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Completed,
+    Failed,
+}
+
+/// One scheduling run: who asked for it, how large it was and how it ended.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub audit_id: String,
+    /// When the request arrived.
+    pub occurred_at: DateTime<Utc>,
+    pub user_id: i64,
+    pub sub: String,
+    /// Route of the request, e.g. `/api/v1/rooms/schedule/batch`.
+    pub operation: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    pub activities: usize,
+    pub rooms: usize,
+    pub outcome: AuditOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheduled: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unscheduled: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// From the request to the end of the run, including time queued.
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct AuditFilter {
+    pub user_id: Option<i64>,
+    pub operation: Option<String>,
+    pub outcome: Option<AuditOutcome>,
+    /// Bounds of `occurred_at`, inclusive.
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl AuditFilter {
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        self.user_id.is_none_or(|user_id| entry.user_id == user_id)
+            && self.operation.as_ref().is_none_or(|operation| entry.operation == *operation)
+            && self.outcome.is_none_or(|outcome| entry.outcome == outcome)
+            && self.from.is_none_or(|from| entry.occurred_at >= from)
+            && self.to.is_none_or(|to| entry.occurred_at <= to)
+    }
+}
+
+/// Append-only storage for audit entries; entries are never changed or
+/// removed through it. `migrations/0002_create_audit_log.sql` holds the
+/// matching Postgres schema.
+pub trait AuditRepository: Send + Sync {
+    fn append(&self, entry: AuditEntry);
+    /// Entries matching `filter`, newest first, at most `limit` of them.
+    fn list(&self, filter: &AuditFilter, limit: usize) -> Vec<AuditEntry>;
+}
+
+/// Keeps the latest `capacity` entries; older ones only survive in a
+/// database-backed store.
+pub struct InMemoryAuditRepository {
+    entries: RwLock<VecDeque<AuditEntry>>,
+    capacity: usize,
+}
+
+impl AuditRepository for InMemoryAuditRepository {
+    fn append(&self, entry: AuditEntry) {
+        let mut entries = self.entries.write().unwrap();
+        if entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    fn list(&self, filter: &AuditFilter, limit: usize) -> Vec<AuditEntry> {
+        self.entries
+            .read()
+            .unwrap()
+            .iter()
+            .rev()
+            .filter(|entry| filter.matches(entry))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+lazy_static! {
+    static ref REPOSITORY: Arc<dyn AuditRepository> = Arc::new(InMemoryAuditRepository {
+        entries: RwLock::new(VecDeque::new()),
+        capacity: config::load_env().audit_log_capacity.max(1),
+    });
+}
+
+pub fn audit_repository() -> Arc<dyn AuditRepository> {
+    REPOSITORY.clone()
+}
@@ -0,0 +1,48 @@
+use std::{collections::BTreeMap, sync::{Arc, RwLock}};
+
+use lazy_static::lazy_static;
+use rooms_scheduler_core::{Room, RoomBlackout};
+
+/// Room closures managed through `/api/v1/rooms/{room_id}/blackouts`, added
+/// to the rooms of every scheduling run. Rooms are matched by id, whether
+/// they are stored or sent inline.
+pub trait BlackoutRepository: Send + Sync {
+    fn list(&self, room_id: u32) -> Vec<RoomBlackout>;
+    fn add(&self, room_id: u32, blackout: RoomBlackout);
+}
+
+#[derive(Default)]
+pub struct InMemoryBlackoutRepository {
+    blackouts: RwLock<BTreeMap<u32, Vec<RoomBlackout>>>,
+}
+
+impl BlackoutRepository for InMemoryBlackoutRepository {
+    fn list(&self, room_id: u32) -> Vec<RoomBlackout> {
+        self.blackouts.read().unwrap().get(&room_id).cloned().unwrap_or_default()
+    }
+
+    fn add(&self, room_id: u32, blackout: RoomBlackout) {
+        self.blackouts.write().unwrap().entry(room_id).or_default().push(blackout);
+    }
+}
+
+lazy_static! {
+    static ref REPOSITORY: Arc<dyn BlackoutRepository> = Arc::new(InMemoryBlackoutRepository::default());
+}
+
+pub fn blackout_repository() -> Arc<dyn BlackoutRepository> {
+    REPOSITORY.clone()
+}
+
+/// Adds the stored blackouts of every room to its own.
+pub fn with_room_blackouts(rooms: &mut [Room]) {
+    let repository = blackout_repository();
+
+    for room in rooms {
+        for blackout in repository.list(room.id) {
+            if !room.blackouts.contains(&blackout) {
+                room.blackouts.push(blackout);
+            }
+        }
+    }
+}
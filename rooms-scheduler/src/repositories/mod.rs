@@ -0,0 +1,17 @@
+pub mod activities;
+pub mod api_keys;
+pub mod audit;
+pub mod blackouts;
+pub mod rooms;
+pub mod schedules;
+pub mod users;
+
+use rooms_scheduler_core::{ActivitiesRequest, SchedulerError};
+
+/// Resolves the stored rooms and activities a scheduling request references
+/// by id, and adds the stored blackouts of its rooms.
+pub fn with_stored_records(request: ActivitiesRequest) -> Result<ActivitiesRequest, SchedulerError> {
+    let mut request = rooms::with_stored_rooms(request).and_then(activities::with_stored_activities)?;
+    blackouts::with_room_blackouts(&mut request.rooms);
+    Ok(request)
+}
@@ -0,0 +1,64 @@
+use std::{collections::BTreeMap, sync::{Arc, RwLock}};
+
+use lazy_static::lazy_static;
+use rooms_scheduler_core::{ActivitiesRequest, Room, SchedulerError};
+
+/// Room inventory managed through `/api/v1/rooms`, so scheduling requests
+/// can reference rooms by id instead of embedding them.
+pub trait RoomRepository: Send + Sync {
+    fn list(&self) -> Vec<Room>;
+    fn find(&self, room_id: u32) -> Option<Room>;
+    /// Inserts or replaces the room with the same id, returning the previous one.
+    fn save(&self, room: Room) -> Option<Room>;
+    fn delete(&self, room_id: u32) -> Option<Room>;
+}
+
+#[derive(Default)]
+pub struct InMemoryRoomRepository {
+    rooms: RwLock<BTreeMap<u32, Room>>,
+}
+
+impl RoomRepository for InMemoryRoomRepository {
+    fn list(&self) -> Vec<Room> {
+        self.rooms.read().unwrap().values().cloned().collect()
+    }
+
+    fn find(&self, room_id: u32) -> Option<Room> {
+        self.rooms.read().unwrap().get(&room_id).cloned()
+    }
+
+    fn save(&self, room: Room) -> Option<Room> {
+        self.rooms.write().unwrap().insert(room.id, room)
+    }
+
+    fn delete(&self, room_id: u32) -> Option<Room> {
+        self.rooms.write().unwrap().remove(&room_id)
+    }
+}
+
+lazy_static! {
+    static ref REPOSITORY: Arc<dyn RoomRepository> = Arc::new(InMemoryRoomRepository::default());
+}
+
+pub fn room_repository() -> Arc<dyn RoomRepository> {
+    REPOSITORY.clone()
+}
+
+/// Appends the stored rooms listed in `room_ids` to the request's inline rooms.
+pub fn with_stored_rooms(mut request: ActivitiesRequest) -> Result<ActivitiesRequest, SchedulerError> {
+    if request.room_ids.is_empty() {
+        return Ok(request);
+    }
+
+    let repository = room_repository();
+
+    for room_id in std::mem::take(&mut request.room_ids) {
+        let room = repository.find(room_id).ok_or_else(|| {
+            SchedulerError::InvalidInput(format!("Room {} is not in the stored room inventory.", room_id))
+        })?;
+
+        request.rooms.push(room);
+    }
+
+    Ok(request)
+}
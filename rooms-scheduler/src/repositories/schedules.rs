@@ -0,0 +1,227 @@
+use std::{collections::HashMap, sync::{Arc, Mutex, RwLock}};
+
+use lazy_static::lazy_static;
+use rooms_scheduler_core::{ActivitiesRequest, Activity, UnscheduledActivity};
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::postgres::{ConnectOptions, Connection, Row};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredSchedule {
+    pub schedule_id: String,
+    pub user_id: i64,
+    pub created_at: String,
+    pub request: ActivitiesRequest,
+    pub scheduled_activities: Vec<Activity>,
+    pub unscheduled_activities: Vec<UnscheduledActivity>,
+}
+
+/// Storage for finished scheduling runs, in memory or in the Postgres
+/// `schedules` table of `migrations/0001_create_schedules.sql`. Every call
+/// fails when the store cannot serve it, so callers can tell an outage from
+/// a missing schedule. Calls may block on the network; run them with
+/// `blocking::run_store`.
+pub trait ScheduleRepository: Send + Sync {
+    fn save(&self, schedule: StoredSchedule) -> Result<(), String>;
+    fn find(&self, schedule_id: &str) -> Result<Option<StoredSchedule>, String>;
+    /// Every schedule created by `user_id`, oldest first.
+    fn list_for_user(&self, user_id: i64) -> Result<Vec<StoredSchedule>, String>;
+    /// Fails when the store cannot serve requests, for readiness probes.
+    fn ping(&self) -> Result<(), String>;
+
+    /// Like `find`, but only returns schedules created by `user_id`.
+    fn find_for_user(&self, schedule_id: &str, user_id: i64) -> Result<Option<StoredSchedule>, String> {
+        Ok(self.find(schedule_id)?.filter(|schedule| schedule.user_id == user_id))
+    }
+}
+
+#[derive(Default)]
+pub struct InMemoryScheduleRepository {
+    schedules: RwLock<HashMap<String, StoredSchedule>>,
+}
+
+impl ScheduleRepository for InMemoryScheduleRepository {
+    fn save(&self, schedule: StoredSchedule) -> Result<(), String> {
+        self.schedules.write().unwrap().insert(schedule.schedule_id.clone(), schedule);
+        Ok(())
+    }
+
+    fn find(&self, schedule_id: &str) -> Result<Option<StoredSchedule>, String> {
+        Ok(self.schedules.read().unwrap().get(schedule_id).cloned())
+    }
+
+    fn list_for_user(&self, user_id: i64) -> Result<Vec<StoredSchedule>, String> {
+        let mut schedules: Vec<StoredSchedule> = self
+            .schedules
+            .read()
+            .unwrap()
+            .values()
+            .filter(|schedule| schedule.user_id == user_id)
+            .cloned()
+            .collect();
+
+        schedules.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        Ok(schedules)
+    }
+
+    fn ping(&self) -> Result<(), String> {
+        self.schedules
+            .read()
+            .map(|_| ())
+            .map_err(|_| "A writer panicked while holding the schedule store.".to_string())
+    }
+}
+
+/// Migrations applied, in order, whenever a connection opens. Each one can
+/// run again without effect.
+const MIGRATIONS: [&str; 1] = [include_str!("../../migrations/0001_create_schedules.sql")];
+
+const COLUMNS: &str = "schedule_id::text, user_id::text, \
+    to_char(created_at, 'YYYY-MM-DD\"T\"HH24:MI:SS.US\"+00:00\"'), \
+    request::text, scheduled_activities::text, unscheduled_activities::text";
+
+/// Schedules kept in Postgres. The connection opens on first use and again
+/// after any failure, so the service starts while the database is down and
+/// readiness probes report it.
+pub struct PostgresScheduleRepository {
+    options: ConnectOptions,
+    connection: Mutex<Option<Connection>>,
+}
+
+impl PostgresScheduleRepository {
+    pub fn new(options: ConnectOptions) -> Self {
+        Self { options, connection: Mutex::new(None) }
+    }
+
+    fn with_connection<T>(&self, run: impl FnOnce(&mut Connection) -> Result<T, String>) -> Result<T, String> {
+        let mut connection = self.connection.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if connection.is_none() {
+            let mut fresh = Connection::connect(&self.options)?;
+            for migration in MIGRATIONS {
+                fresh.execute(migration)?;
+            }
+            *connection = Some(fresh);
+        }
+
+        let result = run(connection.as_mut().expect("connected above"));
+        if result.is_err() {
+            *connection = None;
+        }
+        result
+    }
+
+    fn select(&self, filter: &str, params: &[Option<&str>]) -> Result<Vec<StoredSchedule>, String> {
+        let sql = format!("SELECT {} FROM schedules WHERE {}", COLUMNS, filter);
+        let rows = self.with_connection(|connection| connection.query(&sql, params))?;
+        rows.into_iter().map(stored_schedule).collect()
+    }
+
+    fn find_where(&self, filter: &str, params: &[Option<&str>]) -> Result<Option<StoredSchedule>, String> {
+        // Ids that are not UUIDs would fail the cast, not just miss
+        if !is_uuid(params[0].unwrap_or_default()) {
+            return Ok(None);
+        }
+
+        Ok(self.select(filter, params)?.pop())
+    }
+}
+
+impl ScheduleRepository for PostgresScheduleRepository {
+    fn save(&self, schedule: StoredSchedule) -> Result<(), String> {
+        let json = |value: serde_json::Result<String>| value.expect("models serialize to JSON");
+        let request = json(serde_json::to_string(&schedule.request));
+        let scheduled = json(serde_json::to_string(&schedule.scheduled_activities));
+        let unscheduled = json(serde_json::to_string(&schedule.unscheduled_activities));
+        let user_id = schedule.user_id.to_string();
+
+        self.with_connection(|connection| {
+            connection.query(
+                "INSERT INTO schedules \
+                    (schedule_id, user_id, created_at, request, scheduled_activities, unscheduled_activities) \
+                 VALUES ($1, $2, $3, $4, $5, $6) \
+                 ON CONFLICT (schedule_id) DO UPDATE SET \
+                    user_id = EXCLUDED.user_id, created_at = EXCLUDED.created_at, request = EXCLUDED.request, \
+                    scheduled_activities = EXCLUDED.scheduled_activities, \
+                    unscheduled_activities = EXCLUDED.unscheduled_activities",
+                &[
+                    Some(&schedule.schedule_id),
+                    Some(&user_id),
+                    Some(&schedule.created_at),
+                    Some(&request),
+                    Some(&scheduled),
+                    Some(&unscheduled),
+                ],
+            )
+        })
+        .map(|_| ())
+    }
+
+    fn find(&self, schedule_id: &str) -> Result<Option<StoredSchedule>, String> {
+        self.find_where("schedule_id = $1", &[Some(schedule_id)])
+    }
+
+    fn find_for_user(&self, schedule_id: &str, user_id: i64) -> Result<Option<StoredSchedule>, String> {
+        self.find_where("schedule_id = $1 AND user_id = $2", &[Some(schedule_id), Some(&user_id.to_string())])
+    }
+
+    fn list_for_user(&self, user_id: i64) -> Result<Vec<StoredSchedule>, String> {
+        self.select("user_id = $1 ORDER BY created_at, schedule_id", &[Some(&user_id.to_string())])
+    }
+
+    fn ping(&self) -> Result<(), String> {
+        self.with_connection(|connection| connection.query("SELECT 1", &[]).map(|_| ()))
+    }
+}
+
+fn stored_schedule(row: Row) -> Result<StoredSchedule, String> {
+    let [schedule_id, user_id, created_at, request, scheduled, unscheduled]: [Option<String>; 6] =
+        row.try_into().map_err(|_| "unexpected columns in schedules".to_string())?;
+    let column = |value: Option<String>| value.ok_or_else(|| "unexpected NULL in schedules".to_string());
+    let json_err = |err: serde_json::Error| format!("stored schedule does not match the models: {}", err);
+
+    Ok(StoredSchedule {
+        schedule_id: column(schedule_id)?,
+        user_id: column(user_id)?.parse().map_err(|_| "user_id is not a number".to_string())?,
+        created_at: column(created_at)?,
+        request: serde_json::from_str(&column(request)?).map_err(json_err)?,
+        scheduled_activities: serde_json::from_str(&column(scheduled)?).map_err(json_err)?,
+        unscheduled_activities: serde_json::from_str(&column(unscheduled)?).map_err(json_err)?,
+    })
+}
+
+/// Whether `id` has the UUID textual layout `ids::new_id` writes.
+fn is_uuid(id: &str) -> bool {
+    id.len() == 36
+        && id.char_indices().all(|(index, c)| match index {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+lazy_static! {
+    static ref REPOSITORY: Option<Arc<dyn ScheduleRepository>> = build_repository();
+}
+
+/// Postgres when `DATABASE_URL` is set, otherwise memory, which loses the
+/// schedules on restart.
+fn build_repository() -> Option<Arc<dyn ScheduleRepository>> {
+    let config = config::load_env();
+    if !config.persist_schedules {
+        return None;
+    }
+
+    match config.database_url.as_deref() {
+        Some(url) => {
+            let options = ConnectOptions::parse(url).expect("DATABASE_URL is checked when the configuration loads");
+            Some(Arc::new(PostgresScheduleRepository::new(options)))
+        }
+        None => Some(Arc::new(InMemoryScheduleRepository::default())),
+    }
+}
+
+/// The configured schedule store, or `None` when persistence is disabled.
+pub fn schedule_repository() -> Option<Arc<dyn ScheduleRepository>> {
+    REPOSITORY.clone()
+}
@@ -0,0 +1,65 @@
+use std::{collections::HashMap, sync::Arc};
+
+use lazy_static::lazy_static;
+use serde::Deserialize;
+
+use crate::config;
+
+/// An account that can obtain tokens from `/api/v1/auth/token`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct User {
+    pub user_id: i64,
+    pub email: String,
+    /// `pbkdf2_sha256$<iterations>$<salt>$<base64 hash>`, as written by
+    /// Django's default hasher.
+    pub password_hash: String,
+    pub rol: String,
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+/// Accounts for deployments that run without the main backend.
+pub trait UserRepository: Send + Sync {
+    /// Emails match case-insensitively.
+    fn find_by_email(&self, email: &str) -> Option<User>;
+    fn find(&self, user_id: i64) -> Option<User>;
+}
+
+#[derive(Default)]
+pub struct InMemoryUserRepository {
+    users: HashMap<i64, User>,
+}
+
+impl UserRepository for InMemoryUserRepository {
+    fn find_by_email(&self, email: &str) -> Option<User> {
+        self.users.values().find(|user| user.email.eq_ignore_ascii_case(email)).cloned()
+    }
+
+    fn find(&self, user_id: i64) -> Option<User> {
+        self.users.get(&user_id).cloned()
+    }
+}
+
+lazy_static! {
+    static ref REPOSITORY: Arc<dyn UserRepository> = build_repository();
+}
+
+/// Reads the accounts from `AUTH_USERS`, a JSON array of users. No one can
+/// sign in when it is unset or invalid.
+fn build_repository() -> Arc<dyn UserRepository> {
+    let users: Vec<User> = match config::load_env().auth_users.as_deref() {
+        Some(json) => serde_json::from_str(json).unwrap_or_else(|err| {
+            tracing::error!(error = %err, "AUTH_USERS is not a valid user list; token issuance is disabled");
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+
+    Arc::new(InMemoryUserRepository {
+        users: users.into_iter().map(|user| (user.user_id, user)).collect(),
+    })
+}
+
+pub fn user_repository() -> Arc<dyn UserRepository> {
+    REPOSITORY.clone()
+}
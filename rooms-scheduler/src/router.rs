@@ -1,16 +1,117 @@
 use axum::{
+    extract::DefaultBodyLimit,
     middleware::from_fn,
-    routing::post,
+    routing::{MethodRouter, get, post, put},
     Router,
 };
 
-use crate::controllers::rooms_scheduler::rooms_scheduler_controller;
-use crate::middlewares::{require_access_token, require_administrator_role};
+use crate::config;
+use crate::controllers::activities::{create_activity_controller, delete_activity_controller, get_activity_controller, list_activities_controller, update_activity_controller};
+use crate::controllers::audit::audit_controller;
+use crate::controllers::auth::{refresh_controller, token_controller};
+use crate::controllers::config::reload_config_controller;
+use crate::controllers::docs::{docs_controller, openapi_controller};
+use crate::controllers::feasibility::feasibility_controller;
+use crate::controllers::graphql::graphql_controller;
+use crate::controllers::health::{health_controller, ready_controller, version_controller};
+use crate::controllers::import::import_controller;
+use crate::controllers::info::info_controller;
+use crate::controllers::metrics::metrics_controller;
+use crate::controllers::rooms::{create_room_blackout_controller, create_room_controller, delete_room_controller, get_room_controller, list_room_blackouts_controller, list_rooms_controller, update_room_controller};
+use crate::controllers::rooms_scheduler::{get_schedule_controller, rooms_scheduler_controller};
+use crate::controllers::schedule_batch::schedule_batch_controller;
+use crate::controllers::schedule_calendar::{schedule_calendar_controller, stored_schedule_calendar_controller};
+use crate::controllers::schedule_jobs::{get_schedule_job_controller, schedule_job_socket_controller};
+use crate::controllers::schedule_stream::{schedule_socket_controller, schedule_stream_controller};
+use crate::controllers::schedule_validation::schedule_validation_controller;
+use crate::metrics::track_scheduling_requests;
+use crate::middlewares::{cors, limit_per_ip, limit_per_user, request_id, request_timeout, require_access_token, require_permission};
+use crate::permissions::{ACTIVITIES_READ, ACTIVITIES_WRITE, AUDIT_READ, CONFIG_RELOAD, ROOMS_READ, ROOMS_WRITE, SCHEDULE_READ, SCHEDULE_WRITE};
 
 pub fn create_router() -> Router {
-    Router::new()
-        .route("/api/v1/rooms/schedule", post(rooms_scheduler_controller)
-            .route_layer(from_fn(require_access_token))
-            .route_layer(from_fn(require_administrator_role))
+    let scheduling_routes = Router::new()
+        .route("/api/v1/rooms/schedule", guarded(SCHEDULE_WRITE, post(rooms_scheduler_controller)))
+        .route("/api/v1/rooms/schedule/batch", guarded(SCHEDULE_WRITE, post(schedule_batch_controller)))
+        .route("/api/v1/rooms/schedule/ics", guarded(SCHEDULE_WRITE, post(schedule_calendar_controller)))
+        .route("/api/v1/rooms/schedule/stream", guarded(SCHEDULE_WRITE, post(schedule_stream_controller)))
+        .route("/api/v1/rooms/schedule/ws", guarded(SCHEDULE_WRITE, get(schedule_socket_controller)))
+        .route("/api/v1/rooms/schedule/validate", guarded(SCHEDULE_READ, post(schedule_validation_controller)))
+        .route("/api/v1/rooms/schedule/jobs/{job_id}", guarded(SCHEDULE_READ, get(get_schedule_job_controller)))
+        .route("/api/v1/rooms/schedule/jobs/{job_id}/ws", guarded(SCHEDULE_READ, get(schedule_job_socket_controller)))
+        .route("/api/v1/rooms/schedule/{schedule_id}", guarded(SCHEDULE_READ, get(get_schedule_controller)))
+        .route("/api/v1/rooms/schedule/{schedule_id}/ics", guarded(SCHEDULE_READ, get(stored_schedule_calendar_controller)))
+        .route("/api/v1/rooms/feasible", guarded(SCHEDULE_READ, post(feasibility_controller)))
+        // Checks the permission of each root field it resolves
+        .route("/api/v1/graphql", post(graphql_controller))
+        .route_layer(from_fn(limit_per_user))
+        .route_layer(from_fn(require_access_token))
+        .route_layer(from_fn(limit_per_ip))
+        .route_layer(from_fn(track_scheduling_requests));
+
+    let public_routes = Router::new()
+        .route("/api/v1/info", get(info_controller))
+        .route("/api/v1/openapi.json", get(openapi_controller))
+        .route("/api/v1/docs", get(docs_controller));
+
+    let auth_routes = Router::new()
+        .route("/api/v1/auth/token", post(token_controller))
+        .route("/api/v1/auth/refresh", post(refresh_controller))
+        .route_layer(from_fn(limit_per_ip));
+
+    let inventory_routes = Router::new()
+        .route(
+            "/api/v1/rooms",
+            guarded(ROOMS_WRITE, post(create_room_controller)).merge(guarded(ROOMS_READ, get(list_rooms_controller))),
+        )
+        .route(
+            "/api/v1/rooms/{room_id}",
+            guarded(ROOMS_READ, get(get_room_controller))
+                .merge(guarded(ROOMS_WRITE, put(update_room_controller).delete(delete_room_controller))),
+        )
+        .route(
+            "/api/v1/rooms/{room_id}/blackouts",
+            guarded(ROOMS_WRITE, post(create_room_blackout_controller)).merge(guarded(ROOMS_READ, get(list_room_blackouts_controller))),
+        )
+        .route(
+            "/api/v1/activities",
+            guarded(ACTIVITIES_WRITE, post(create_activity_controller)).merge(guarded(ACTIVITIES_READ, get(list_activities_controller))),
+        )
+        .route(
+            "/api/v1/activities/{activity_id}",
+            guarded(ACTIVITIES_READ, get(get_activity_controller))
+                .merge(guarded(ACTIVITIES_WRITE, put(update_activity_controller).delete(delete_activity_controller))),
         )
+        .route("/api/v1/import", guarded(ROOMS_WRITE, guarded(ACTIVITIES_WRITE, post(import_controller))))
+        .route("/api/v1/audit", guarded(AUDIT_READ, get(audit_controller)))
+        .route("/api/v1/config/reload", guarded(CONFIG_RELOAD, post(reload_config_controller)))
+        .route_layer(from_fn(require_access_token));
+
+    let api_routes = Router::new()
+        .merge(public_routes)
+        .merge(auth_routes)
+        .merge(scheduling_routes)
+        .merge(inventory_routes);
+
+    let app = match config::load_env().base_path.as_deref() {
+        Some(base_path) => Router::new().nest(base_path, api_routes),
+        None => api_routes,
+    };
+
+    // Outside the base path, where scrapers and probes look by default
+    let app = app
+        .route("/metrics", get(metrics_controller))
+        .route("/healthz", get(health_controller))
+        .route("/readyz", get(ready_controller))
+        .route("/version", get(version_controller));
+
+    // CORS runs first so preflights never reach authentication
+    app.layer(DefaultBodyLimit::max(config::load_env().max_body_bytes))
+        .layer(from_fn(request_timeout))
+        .layer(from_fn(request_id))
+        .layer(from_fn(cors))
+}
+
+/// Requires `permission` for every method of `route`.
+fn guarded(permission: &'static str, route: MethodRouter) -> MethodRouter {
+    route.route_layer(from_fn(require_permission(permission)))
 }
@@ -0,0 +1,65 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use lazy_static::lazy_static;
+use rooms_scheduler_core::Schedule;
+
+use crate::config;
+
+struct CachedSchedule {
+    stored_at: Instant,
+    last_used: Instant,
+    schedule: Schedule,
+}
+
+lazy_static! {
+    static ref CACHE: Mutex<HashMap<u64, CachedSchedule>> = Mutex::new(HashMap::new());
+}
+
+fn is_enabled() -> bool {
+    let config = config::load_env();
+    config.schedule_cache_ttl_seconds > 0 && config.schedule_cache_capacity > 0
+}
+
+pub fn get(request_hash: u64) -> Option<Schedule> {
+    if !is_enabled() {
+        return None;
+    }
+
+    let ttl = Duration::from_secs(config::load_env().schedule_cache_ttl_seconds);
+    let mut cache = CACHE.lock().unwrap();
+
+    cache.retain(|_, entry| entry.stored_at.elapsed() < ttl);
+
+    cache.get_mut(&request_hash).map(|entry| {
+        entry.last_used = Instant::now();
+        entry.schedule.clone()
+    })
+}
+
+pub fn insert(request_hash: u64, schedule: Schedule) {
+    if !is_enabled() {
+        return;
+    }
+
+    let capacity = config::load_env().schedule_cache_capacity;
+    let mut cache = CACHE.lock().unwrap();
+
+    while cache.len() >= capacity && !cache.contains_key(&request_hash) {
+        let least_recently_used = cache
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(key, _)| *key);
+
+        match least_recently_used {
+            Some(key) => cache.remove(&key),
+            None => break,
+        };
+    }
+
+    let now = Instant::now();
+    cache.insert(request_hash, CachedSchedule { stored_at: now, last_used: now, schedule });
+}
@@ -1 +0,0 @@
-pub mod algorithm;
\ No newline at end of file
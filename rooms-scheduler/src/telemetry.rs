@@ -0,0 +1,218 @@
+//! OpenTelemetry trace export. The logger hands over every span it closes;
+//! they are batched and posted to `OTEL_EXPORTER_OTLP_ENDPOINT` as OTLP/HTTP
+//! JSON. A `traceparent` span field (W3C Trace Context) makes the span a
+//! child of the caller's, so requests from the gateway join its trace.
+
+use std::{
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use lazy_static::lazy_static;
+use serde_json::{Value, json};
+
+use crate::config;
+use crate::webhooks::{self, Callback};
+
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+/// Finished spans kept while the collector is slow or down; newer spans are
+/// dropped beyond this.
+const MAX_QUEUED_SPANS: usize = 2048;
+
+/// OTLP span kinds.
+const KIND_INTERNAL: u8 = 1;
+const KIND_SERVER: u8 = 2;
+
+/// OTLP status codes.
+const STATUS_UNSET: u8 = 0;
+const STATUS_ERROR: u8 = 2;
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpanContext {
+    pub trace_id: u128,
+    pub span_id: u64,
+}
+
+impl SpanContext {
+    /// The first span of a new trace.
+    pub fn root() -> Self {
+        Self { trace_id: rand::random::<u128>().max(1), span_id: new_span_id() }
+    }
+
+    pub fn child_of(parent: SpanContext) -> Self {
+        Self { trace_id: parent.trace_id, span_id: new_span_id() }
+    }
+
+    /// Reads a `traceparent` header: `00-<trace id>-<parent span id>-<flags>`.
+    pub fn from_traceparent(value: &str) -> Option<Self> {
+        let mut parts = value.trim().split('-');
+
+        let (version, trace_id, span_id) = (parts.next()?, parts.next()?, parts.next()?);
+        parts.next().filter(|flags| flags.len() == 2)?;
+
+        if version.len() != 2 || version == "ff" || trace_id.len() != 32 || span_id.len() != 16 {
+            return None;
+        }
+
+        let trace_id = u128::from_str_radix(trace_id, 16).ok().filter(|id| *id != 0)?;
+        let span_id = u64::from_str_radix(span_id, 16).ok().filter(|id| *id != 0)?;
+
+        Some(Self { trace_id, span_id })
+    }
+}
+
+fn new_span_id() -> u64 {
+    rand::random::<u64>().max(1)
+}
+
+pub struct FinishedSpan {
+    pub context: SpanContext,
+    pub parent_span_id: Option<u64>,
+    pub name: &'static str,
+    pub start: SystemTime,
+    pub end: SystemTime,
+    /// Span fields. `otel.kind` and `otel.status_code` set the span kind
+    /// (`server`) and status (`error`) instead of becoming attributes.
+    pub fields: Vec<(&'static str, Value)>,
+}
+
+lazy_static! {
+    static ref QUEUE: Mutex<Vec<FinishedSpan>> = Mutex::new(Vec::new());
+    static ref COLLECTOR: Option<Callback> = build_collector();
+}
+
+fn build_collector() -> Option<Callback> {
+    let endpoint = config::load_env().otel_exporter_otlp_endpoint.clone()?;
+    let url = format!("{}/v1/traces", endpoint.trim_end_matches('/'));
+
+    match Callback::parse_as("OTEL_EXPORTER_OTLP_ENDPOINT", &url) {
+        Ok(collector) => {
+            tracing::info!(url = %url, "Exporting traces");
+            Some(collector)
+        }
+        Err(err) => {
+            tracing::error!(error = %err, "Trace export disabled");
+            None
+        }
+    }
+}
+
+pub fn is_enabled() -> bool {
+    config::load_env().otel_exporter_otlp_endpoint.is_some()
+}
+
+pub fn record(span: FinishedSpan) {
+    let mut queue = QUEUE.lock().unwrap();
+    if queue.len() < MAX_QUEUED_SPANS {
+        queue.push(span);
+    }
+}
+
+/// Starts the background export to the configured collector, if any.
+pub fn start_exporter() {
+    let Some(collector) = COLLECTOR.as_ref() else {
+        return;
+    };
+
+    let interval = Duration::from_millis(config::load_env().otel_export_interval_ms.max(100));
+
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            export(collector).await;
+        }
+    });
+}
+
+/// Exports the spans still queued, for shutdown.
+pub async fn flush() {
+    if let Some(collector) = COLLECTOR.as_ref() {
+        export(collector).await;
+    }
+}
+
+/// Posts every queued span to the collector. Spans that fail to export are
+/// dropped.
+async fn export(collector: &Callback) {
+    let spans = std::mem::take(&mut *QUEUE.lock().unwrap());
+    if spans.is_empty() {
+        return;
+    }
+
+    let count = spans.len();
+    let body = export_request(spans).to_string();
+
+    let result = tokio::time::timeout(Duration::from_secs(10), webhooks::post(collector, &body, &[])).await;
+
+    match result {
+        Ok(Ok(status)) if (200..300).contains(&status) => {}
+        Ok(Ok(status)) => tracing::warn!(spans = count, status, "Collector rejected spans"),
+        Ok(Err(err)) => tracing::warn!(spans = count, error = %err, "Span export failed"),
+        Err(_) => tracing::warn!(spans = count, "Span export timed out"),
+    }
+}
+
+/// An OTLP `ExportTraceServiceRequest` in its JSON encoding.
+fn export_request(spans: Vec<FinishedSpan>) -> Value {
+    let config = config::load_env();
+
+    json!({
+        "resourceSpans": [{
+            "resource": { "attributes": [attribute("service.name", &Value::String(config.otel_service_name.to_string()))] },
+            "scopeSpans": [{
+                "scope": { "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") },
+                "spans": spans.iter().map(otlp_span).collect::<Vec<_>>(),
+            }],
+        }],
+    })
+}
+
+fn otlp_span(span: &FinishedSpan) -> Value {
+    let mut kind = KIND_INTERNAL;
+    let mut status = STATUS_UNSET;
+    let mut attributes = Vec::new();
+
+    for (name, value) in &span.fields {
+        match (*name, value.as_str()) {
+            ("otel.kind", Some(value)) if value.eq_ignore_ascii_case("server") => kind = KIND_SERVER,
+            ("otel.status_code", Some(value)) if value.eq_ignore_ascii_case("error") => status = STATUS_ERROR,
+            (name, _) if name.starts_with("otel.") => {}
+            (name, _) => attributes.push(attribute(name, value)),
+        }
+    }
+
+    let mut otlp = json!({
+        "traceId": format!("{:032x}", span.context.trace_id),
+        "spanId": format!("{:016x}", span.context.span_id),
+        "name": span.name,
+        "kind": kind,
+        "startTimeUnixNano": unix_nanos(span.start),
+        "endTimeUnixNano": unix_nanos(span.end),
+        "attributes": attributes,
+        "status": { "code": status },
+    });
+
+    if let Some(parent_span_id) = span.parent_span_id {
+        otlp["parentSpanId"] = Value::String(format!("{:016x}", parent_span_id));
+    }
+
+    otlp
+}
+
+fn attribute(key: &str, value: &Value) -> Value {
+    let value = match value {
+        Value::Bool(value) => json!({ "boolValue": value }),
+        Value::Number(number) if number.is_f64() => json!({ "doubleValue": number }),
+        Value::Number(number) => json!({ "intValue": number.to_string() }),
+        Value::String(value) => json!({ "stringValue": value }),
+        value => json!({ "stringValue": value.to_string() }),
+    };
+
+    json!({ "key": key, "value": value })
+}
+
+/// Nanoseconds since the epoch, as the string OTLP JSON expects.
+fn unix_nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos().to_string()
+}
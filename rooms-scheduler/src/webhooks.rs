@@ -0,0 +1,330 @@
+use std::{net::IpAddr, time::Duration};
+
+use hmac::{Hmac, Mac};
+use serde_json::json;
+use sha2::Sha256;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpStream, lookup_host},
+};
+
+use crate::config;
+use crate::jobs::Job;
+
+pub const SIGNATURE_HEADER: &str = "x-signature-256";
+
+/// Longest a single delivery attempt may take.
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A parsed `callback_url`. Only plain `http://` URLs can be delivered to,
+/// since the service is built without TLS.
+#[derive(Debug, Clone)]
+pub struct Callback {
+    url: String,
+    host: String,
+    port: u16,
+    path: String,
+    /// Whether deliveries must skip loopback, link-local and private
+    /// addresses the host resolves to.
+    public_only: bool,
+}
+
+impl Callback {
+    /// A callback URL from a request. When `WEBHOOK_ALLOWED_HOSTS` is set
+    /// only those hosts are accepted; otherwise any host but the loopback,
+    /// link-local and private ones.
+    pub fn parse(url: &str) -> Result<Self, String> {
+        let mut callback = Self::parse_as("callback_url", url)?;
+        let allowed_hosts = &config::load_env().webhook_allowed_hosts;
+
+        if !allowed_hosts.is_empty() {
+            if !allowed_hosts.iter().any(|allowed| allowed.eq_ignore_ascii_case(&callback.host)) {
+                return Err(format!("callback_url host `{}` is not in WEBHOOK_ALLOWED_HOSTS.", callback.host));
+            }
+            return Ok(callback);
+        }
+
+        let internal = match callback.host.parse::<IpAddr>() {
+            Ok(address) => is_internal(address),
+            Err(_) => callback.host.eq_ignore_ascii_case("localhost") || callback.host.to_ascii_lowercase().ends_with(".localhost"),
+        };
+
+        if internal {
+            return Err("callback_url must not point at a loopback, link-local or private address.".to_string());
+        }
+
+        callback.public_only = true;
+        Ok(callback)
+    }
+
+    /// Same as `parse` for URLs the operator configures, naming `setting` in
+    /// the errors. Any host is accepted.
+    pub fn parse_as(setting: &str, url: &str) -> Result<Self, String> {
+        // They would end up in the request line and split it
+        if url.chars().any(|ch| ch.is_whitespace() || ch.is_control()) {
+            return Err(format!("{} must not contain whitespace or control characters.", setting));
+        }
+
+        let rest = match url.split_once("://") {
+            Some((scheme, rest)) if scheme.eq_ignore_ascii_case("http") => rest,
+            Some((scheme, _)) if scheme.eq_ignore_ascii_case("https") => {
+                return Err(format!("{} must use http://; this build cannot deliver over TLS.", setting));
+            }
+            _ => return Err(format!("{} `{}` is not an http:// URL.", setting, url)),
+        };
+
+        let (authority, path) = match rest.find('/') {
+            Some(index) => (&rest[..index], &rest[index..]),
+            None => (rest, "/"),
+        };
+
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().map_err(|_| format!("{} port `{}` is not a number.", setting, port))?),
+            None => (authority, 80),
+        };
+
+        if host.is_empty() || !host.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '.' || ch == '-') {
+            return Err(format!("{} `{}` has no usable host.", setting, url));
+        }
+
+        Ok(Self {
+            url: url.to_string(),
+            host: host.to_string(),
+            port,
+            path: path.split('#').next().unwrap_or("/").to_string(),
+            public_only: false,
+        })
+    }
+}
+
+/// Loopback, link-local, private and unspecified addresses, which a
+/// callback from a request must not make the service reach.
+fn is_internal(address: IpAddr) -> bool {
+    match address {
+        IpAddr::V4(address) => {
+            address.is_loopback()
+                || address.is_private()
+                || address.is_link_local()
+                || address.is_unspecified()
+                || address.is_broadcast()
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (address.octets()[0] == 100 && address.octets()[1] & 0xc0 == 64)
+        }
+        IpAddr::V6(address) => {
+            address.is_loopback()
+                || address.is_unspecified()
+                || address.is_unique_local()
+                || address.is_unicast_link_local()
+                || address.to_ipv4_mapped().is_some_and(|mapped| is_internal(IpAddr::V4(mapped)))
+        }
+    }
+}
+
+/// Connects to the callback's host, skipping the addresses it must not
+/// reach even when a name resolves to them.
+async fn connect(callback: &Callback) -> Result<TcpStream, String> {
+    let addresses: Vec<_> = lookup_host((callback.host.as_str(), callback.port))
+        .await
+        .map_err(|err| err.to_string())?
+        .filter(|address| !callback.public_only || !is_internal(address.ip()))
+        .collect();
+
+    if addresses.is_empty() {
+        return Err(format!("{} resolves to no address the service may reach", callback.host));
+    }
+
+    TcpStream::connect(addresses.as_slice()).await.map_err(|err| err.to_string())
+}
+
+/// Posts a summary of the finished job to its callback in the background,
+/// retrying with exponential backoff until it answers with a 2xx status.
+pub fn notify(callback: Callback, job: &Job) {
+    let (scheduled, unscheduled) = job
+        .result
+        .as_ref()
+        .map(|schedule| (schedule.scheduled_activities.len(), schedule.unscheduled_activities.len()))
+        .unwrap_or_default();
+
+    let payload = json!({
+        "job_id": job.job_id,
+        "status": job.status,
+        "scheduled": scheduled,
+        "unscheduled": unscheduled,
+        "duration_ms": job.duration_ms,
+        "finished_at": job.finished_at,
+        "error": job.error,
+    })
+    .to_string();
+
+    let job_id = job.job_id.clone();
+
+    tokio::spawn(async move {
+        let config = config::load_env();
+        let signature = config.webhook_secret.as_deref().map(|secret| sign(secret, payload.as_bytes()));
+        let mut backoff = Duration::from_millis(config.webhook_backoff_ms);
+
+        for attempt in 1..=config.webhook_max_attempts.max(1) {
+            let headers: Vec<(&str, &str)> = signature.iter().map(|signature| (SIGNATURE_HEADER, signature.as_str())).collect();
+            let delivery = tokio::time::timeout(ATTEMPT_TIMEOUT, post(&callback, &payload, &headers)).await;
+
+            let failure = match delivery {
+                Ok(Ok(status)) if (200..300).contains(&status) => {
+                    tracing::info!(job_id = %job_id, url = %callback.url, attempt, "Delivered job webhook");
+                    return;
+                }
+                Ok(Ok(status)) => format!("status {}", status),
+                Ok(Err(err)) => err,
+                Err(_) => "timed out".to_string(),
+            };
+
+            tracing::warn!(job_id = %job_id, url = %callback.url, attempt, error = %failure, "Job webhook delivery failed");
+
+            if attempt < config.webhook_max_attempts {
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+        }
+
+        tracing::error!(job_id = %job_id, url = %callback.url, "Giving up on job webhook");
+    });
+}
+
+/// `sha256=` followed by the hex HMAC-SHA256 of the body.
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+
+    let digest = mac.finalize().into_bytes();
+    format!("sha256={}", digest.iter().map(|byte| format!("{:02x}", byte)).collect::<String>())
+}
+
+/// Sends one HTTP/1.1 POST of a JSON body and returns the response status.
+pub async fn post(callback: &Callback, body: &str, headers: &[(&str, &str)]) -> Result<u16, String> {
+    if headers.iter().any(|(name, value)| name.contains(['\r', '\n']) || value.contains(['\r', '\n'])) {
+        return Err("header names and values must not contain CR or LF".to_string());
+    }
+
+    let mut stream = connect(callback).await?;
+
+    let mut request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\nUser-Agent: rooms-scheduler\r\n",
+        callback.path, callback.host, callback.port, body.len()
+    );
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+    request.push_str(body);
+
+    stream.write_all(request.as_bytes()).await.map_err(|err| err.to_string())?;
+
+    // Only the status line is needed
+    let mut response = Vec::new();
+    let mut chunk = [0; 512];
+    while !response.windows(2).any(|window| window == b"\r\n") {
+        match stream.read(&mut chunk).await.map_err(|err| err.to_string())? {
+            0 => break,
+            read => response.extend_from_slice(&chunk[..read]),
+        }
+    }
+
+    // Status line: HTTP/1.1 200 OK
+    String::from_utf8_lossy(&response)
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| "malformed response".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn public_http_url_is_parsed() {
+        let callback = Callback::parse("http://hooks.example.edu:8080/jobs?team=3#done").unwrap();
+
+        assert_eq!((callback.host.as_str(), callback.port, callback.path.as_str()), ("hooks.example.edu", 8080, "/jobs?team=3"));
+        assert!(callback.public_only);
+    }
+
+    #[test]
+    fn line_breaks_whitespace_and_control_characters_are_rejected() {
+        for url in [
+            "http://hooks.example.edu/jobs\r\nX-Injected: 1",
+            "http://hooks.example.edu/jobs\nX-Injected: 1",
+            "http://hooks.example.edu/a b",
+            "http://hooks.example.edu/\tjobs",
+            "http://hooks.example.edu/\u{0}",
+            " http://hooks.example.edu/",
+        ] {
+            let err = Callback::parse(url).unwrap_err();
+            assert!(err.contains("whitespace or control characters"), "{:?}: {}", url, err);
+        }
+    }
+
+    #[test]
+    fn hosts_with_unexpected_characters_are_rejected() {
+        for url in ["http://user@hooks.example.edu/", "http://hooks_example!/", "http://:8080/", "http:///jobs"] {
+            assert!(Callback::parse(url).is_err(), "{:?}", url);
+        }
+    }
+
+    #[test]
+    fn internal_addresses_are_rejected_in_requests() {
+        for url in [
+            "http://127.0.0.1/",
+            "http://10.1.2.3:9000/",
+            "http://172.16.0.1/",
+            "http://192.168.1.20/",
+            "http://169.254.169.254/latest/meta-data",
+            "http://0.0.0.0/",
+            "http://100.64.0.1/",
+            "http://localhost:3000/",
+            "http://api.LOCALHOST/",
+        ] {
+            let err = Callback::parse(url).unwrap_err();
+            assert!(err.contains("loopback, link-local or private"), "{:?}: {}", url, err);
+        }
+    }
+
+    #[test]
+    fn configured_urls_may_be_internal() {
+        let collector = Callback::parse_as("OTEL_EXPORTER_OTLP_ENDPOINT", "http://127.0.0.1:4318/v1/traces").unwrap();
+
+        assert!(!collector.public_only);
+    }
+
+    #[test]
+    fn internal_ranges_cover_both_address_families() {
+        for internal in ["::1", "::", "fe80::1", "fd12:3456::1", "::ffff:192.168.0.1", "255.255.255.255"] {
+            assert!(is_internal(internal.parse().unwrap()), "{}", internal);
+        }
+
+        for public in ["8.8.8.8", "100.128.0.1", "2001:4860:4860::8888", "::ffff:8.8.4.4"] {
+            assert!(!is_internal(public.parse().unwrap()), "{}", public);
+        }
+    }
+
+    #[tokio::test]
+    async fn names_resolving_to_internal_addresses_are_not_connected_to() {
+        let callback = Callback {
+            url: "http://localhost/".to_string(),
+            host: "localhost".to_string(),
+            port: 80,
+            path: "/".to_string(),
+            public_only: true,
+        };
+
+        let err = connect(&callback).await.unwrap_err();
+        assert!(err.contains("no address the service may reach"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn headers_with_line_breaks_are_not_sent() {
+        let callback = Callback::parse("http://hooks.example.edu/jobs").unwrap();
+
+        let err = post(&callback, "{}", &[(SIGNATURE_HEADER, "sha256=00\r\nX-Injected: 1")]).await.unwrap_err();
+        assert!(err.contains("CR or LF"), "{}", err);
+    }
+}
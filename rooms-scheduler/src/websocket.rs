@@ -0,0 +1,239 @@
+//! WebSockets (RFC 6455) carrying text messages. The service mostly pushes
+//! messages; routes that take their input over the socket read one first.
+
+use axum::{
+    body::Body,
+    extract::FromRequestParts,
+    http::{HeaderMap, StatusCode, header, request::Parts},
+    response::Response,
+};
+use axum_responses::http::HttpResponse;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use hyper::upgrade::{OnUpgrade, Upgraded};
+use hyper_util::rt::TokioIo;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Appended to the client key to prove the server speaks WebSocket.
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// A WebSocket handshake, answered with `on_upgrade`.
+pub struct WebSocketUpgrade {
+    accept: String,
+    on_upgrade: OnUpgrade,
+}
+
+impl<S> FromRequestParts<S> for WebSocketUpgrade
+where
+    S: Send + Sync,
+{
+    type Rejection = HttpResponse;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let key = handshake_key(&parts.headers)
+            .ok_or_else(|| HttpResponse::BadRequest().error("Expected a WebSocket (version 13) upgrade request."))?;
+
+        let on_upgrade = parts
+            .extensions
+            .remove::<OnUpgrade>()
+            .ok_or_else(|| HttpResponse::BadRequest().error("The connection cannot be upgraded."))?;
+
+        Ok(Self {
+            accept: STANDARD.encode(sha1(format!("{}{}", key, ACCEPT_GUID).as_bytes())),
+            on_upgrade,
+        })
+    }
+}
+
+impl WebSocketUpgrade {
+    /// Switches protocols and hands the socket to `callback` once the
+    /// client has received the handshake response.
+    pub fn on_upgrade<F, Fut>(self, callback: F) -> Response
+    where
+        F: FnOnce(WebSocket) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        tokio::spawn(async move {
+            match self.on_upgrade.await {
+                Ok(upgraded) => callback(WebSocket { io: TokioIo::new(upgraded) }).await,
+                Err(err) => tracing::warn!(error = %err, "WebSocket upgrade failed"),
+            }
+        });
+
+        Response::builder()
+            .status(StatusCode::SWITCHING_PROTOCOLS)
+            .header(header::CONNECTION, "upgrade")
+            .header(header::UPGRADE, "websocket")
+            .header(header::SEC_WEBSOCKET_ACCEPT, self.accept)
+            .body(Body::empty())
+            .expect("handshake response is valid")
+    }
+}
+
+fn handshake_key(headers: &HeaderMap) -> Option<&str> {
+    let has_token = |name: header::HeaderName, token: &str| {
+        headers
+            .get_all(name)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .any(|value| value.trim().eq_ignore_ascii_case(token))
+    };
+
+    if !has_token(header::CONNECTION, "upgrade") || !has_token(header::UPGRADE, "websocket") {
+        return None;
+    }
+
+    if headers.get(header::SEC_WEBSOCKET_VERSION)?.as_bytes() != b"13" {
+        return None;
+    }
+
+    headers.get(header::SEC_WEBSOCKET_KEY)?.to_str().ok()
+}
+
+pub struct WebSocket {
+    io: TokioIo<Upgraded>,
+}
+
+impl WebSocket {
+    pub async fn send_text(&mut self, text: &str) -> std::io::Result<()> {
+        self.send_frame(0x1, text.as_bytes()).await
+    }
+
+    /// Reads the next text message of at most `max_bytes`, answering pings
+    /// on the way. `None` when the client closes the connection first.
+    pub async fn receive_text(&mut self, max_bytes: usize) -> std::io::Result<Option<String>> {
+        let mut message = Vec::new();
+
+        loop {
+            let mut head = [0u8; 2];
+
+            match self.io.read_exact(&mut head).await {
+                Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+                result => result?,
+            };
+
+            let (fin, opcode, masked) = (head[0] & 0x80 != 0, head[0] & 0x0F, head[1] & 0x80 != 0);
+
+            let length = match head[1] & 0x7F {
+                126 => self.io.read_u16().await? as u64,
+                127 => self.io.read_u64().await?,
+                length => length as u64,
+            };
+
+            if !masked {
+                return Err(invalid_data("Client frames must be masked."));
+            }
+
+            if length > max_bytes.saturating_sub(message.len()) as u64 {
+                return Err(invalid_data("The message is larger than the request body limit."));
+            }
+
+            let mut mask = [0u8; 4];
+            self.io.read_exact(&mut mask).await?;
+
+            let mut payload = vec![0u8; length as usize];
+            self.io.read_exact(&mut payload).await?;
+
+            for (index, byte) in payload.iter_mut().enumerate() {
+                *byte ^= mask[index % 4];
+            }
+
+            match opcode {
+                0x0 | 0x1 => {
+                    message.extend_from_slice(&payload);
+
+                    if fin {
+                        return String::from_utf8(message)
+                            .map(Some)
+                            .map_err(|_| invalid_data("Text messages must be valid UTF-8."));
+                    }
+                }
+                0x8 => return Ok(None),
+                0x9 => self.send_frame(0xA, &payload).await?,
+                0xA => {}
+                _ => return Err(invalid_data("Only text messages are supported.")),
+            }
+        }
+    }
+
+    /// Sends a normal closure and shuts the connection down.
+    pub async fn close(mut self) -> std::io::Result<()> {
+        self.send_frame(0x8, &1000u16.to_be_bytes()).await?;
+        self.io.shutdown().await
+    }
+
+    /// Writes one unmasked, unfragmented frame.
+    async fn send_frame(&mut self, opcode: u8, payload: &[u8]) -> std::io::Result<()> {
+        let mut frame = vec![0x80 | opcode];
+
+        match payload.len() {
+            length if length < 126 => frame.push(length as u8),
+            length if length <= u16::MAX as usize => {
+                frame.push(126);
+                frame.extend_from_slice(&(length as u16).to_be_bytes());
+            }
+            length => {
+                frame.push(127);
+                frame.extend_from_slice(&(length as u64).to_be_bytes());
+            }
+        }
+
+        frame.extend_from_slice(payload);
+        self.io.write_all(&frame).await?;
+        self.io.flush().await
+    }
+}
+
+fn invalid_data(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+/// SHA-1 (RFC 3174), which the handshake requires.
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x6745_2301, 0xEFCD_AB89, 0x98BA_DCFE, 0x1032_5476, 0xC3D2_E1F0];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut words = [0u32; 80];
+        for (word, bytes) in words.iter_mut().zip(block.chunks(4)) {
+            *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        for i in 16..80 {
+            words[i] = (words[i - 3] ^ words[i - 8] ^ words[i - 14] ^ words[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+
+        for (i, word) in words.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ED9_EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1B_BCDC),
+                _ => (b ^ c ^ d, 0xCA62_C1D6),
+            };
+
+            let next = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = next;
+        }
+
+        for (value, add) in state.iter_mut().zip([a, b, c, d, e]) {
+            *value = value.wrapping_add(add);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (bytes, value) in digest.chunks_mut(4).zip(state) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
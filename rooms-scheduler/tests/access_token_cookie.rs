@@ -0,0 +1,58 @@
+mod common;
+
+use axum::{body::Body, http::{Method, StatusCode, header}};
+use common::{admin_token, app, request, send, token_signed_with};
+use serde_json::json;
+
+const ROOMS: &str = "/api/v1/rooms";
+const COOKIE_NAME: &str = "planner_session";
+
+fn configured() -> axum::Router {
+    app(&[("ACCESS_TOKEN_COOKIE", COOKIE_NAME)])
+}
+
+fn forged_token() -> String {
+    token_signed_with("not-the-secret", json!({}))
+}
+
+async fn list_rooms(authorization: Option<&str>, cookie: Option<&str>) -> StatusCode {
+    let mut builder = request(Method::GET, ROOMS, authorization);
+    if let Some(cookie) = cookie {
+        builder = builder.header(header::COOKIE, cookie);
+    }
+
+    send(&configured(), builder.body(Body::empty()).unwrap()).await.status
+}
+
+#[tokio::test]
+async fn header_only_is_accepted() {
+    assert_eq!(list_rooms(Some(&admin_token()), None).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn cookie_only_is_accepted() {
+    let cookie = format!("theme=dark; {}={}", COOKIE_NAME, admin_token());
+
+    assert_eq!(list_rooms(None, Some(&cookie)).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn cookie_under_another_name_is_ignored() {
+    let cookie = format!("access_token={}", admin_token());
+
+    assert_eq!(list_rooms(None, Some(&cookie)).await, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn header_takes_precedence_over_the_cookie() {
+    let valid_cookie = format!("{}={}", COOKIE_NAME, admin_token());
+    let invalid_cookie = format!("{}={}", COOKIE_NAME, forged_token());
+
+    assert_eq!(list_rooms(Some(&admin_token()), Some(&invalid_cookie)).await, StatusCode::OK);
+    assert_eq!(list_rooms(Some(&forged_token()), Some(&valid_cookie)).await, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn neither_is_401() {
+    assert_eq!(list_rooms(None, None).await, StatusCode::UNAUTHORIZED);
+}
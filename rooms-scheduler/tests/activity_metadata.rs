@@ -0,0 +1,22 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{activity, admin_token, app, post_json, room};
+use serde_json::json;
+
+#[tokio::test]
+async fn metadata_survives_a_scheduling_run() {
+    let metadata = json!({ "course_code": "MAT-021", "department": "Mathematics", "ui": { "color": "#3366ff", "pinned": true } });
+    let mut tagged = activity(1, 20, &[0]);
+    tagged["metadata"] = metadata.clone();
+
+    let body = json!({ "rooms": [room(1, "A-101", 30)], "activities": [tagged, activity(2, 10, &[1])] });
+    let response = post_json(&app(&[]), "/api/v1/rooms/schedule", Some(&admin_token()), &body).await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let scheduled = response.body["data"]["scheduled_activities"].as_array().unwrap();
+    let find = |id: u32| scheduled.iter().find(|a| a["id"] == id).unwrap();
+
+    assert_eq!(find(1)["metadata"], metadata);
+    assert!(find(2).get("metadata").is_none());
+}
@@ -0,0 +1,29 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{admin_token, app, get, post_json, small_request};
+
+const VARS: &[(&str, &str)] = &[("BASE_PATH", "/scheduler/")];
+
+#[tokio::test]
+async fn routes_answer_under_the_base_path() {
+    let response = post_json(&app(VARS), "/scheduler/api/v1/rooms/schedule", Some(&admin_token()), &small_request()).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["data"]["scheduled_activities"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn routes_are_404_without_the_base_path() {
+    let response = post_json(&app(VARS), "/api/v1/rooms/schedule", Some(&admin_token()), &small_request()).await;
+
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn probes_stay_at_the_root() {
+    let app = app(VARS);
+
+    assert_eq!(get(&app, "/healthz", None).await.status, StatusCode::OK);
+    assert_eq!(get(&app, "/scheduler/healthz", None).await.status, StatusCode::NOT_FOUND);
+}
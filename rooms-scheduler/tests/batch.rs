@@ -0,0 +1,43 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{activity, admin_token, app, post_json, room, small_request};
+use serde_json::json;
+
+#[tokio::test]
+async fn feasible_and_infeasible_scenarios_both_come_back_in_order() {
+    let mut feasible = small_request();
+    feasible["label"] = json!("two rooms");
+    let infeasible = json!({
+        "label": "one room",
+        "rooms": [room(1, "A-101", 40)],
+        "activities": [activity(1, 10, &[0]), activity(2, 10, &[0])],
+    });
+    let invalid = json!({ "rooms": [room(1, "A-101", 0)], "activities": [activity(1, 10, &[0])] });
+
+    let response = post_json(&app(&[]), "/api/v1/rooms/schedule/batch", Some(&admin_token()), &json!([feasible, infeasible, invalid])).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+
+    let results = response.body["data"].as_array().unwrap();
+    assert_eq!(results.len(), 3);
+
+    assert_eq!(results[0]["label"], "two rooms");
+    assert_eq!(results[0]["success"], true);
+    assert_eq!(results[0]["data"]["scheduled_activities"].as_array().unwrap().len(), 2);
+
+    assert_eq!(results[1]["label"], "one room");
+    assert_eq!(results[1]["success"], false);
+    assert_eq!(results[1]["code"], 409);
+    assert_eq!(results[1]["error"]["conflicts"][0]["shortfall"], 1);
+
+    assert!(results[2].get("label").is_none());
+    assert_eq!(results[2]["code"], 422);
+}
+
+#[tokio::test]
+async fn batch_must_be_an_array() {
+    let response = post_json(&app(&[]), "/api/v1/rooms/schedule/batch", Some(&admin_token()), &small_request()).await;
+
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+}
@@ -0,0 +1,30 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{admin_token, app, post_json, small_request};
+use serde_json::json;
+
+#[tokio::test]
+async fn ics_has_one_event_per_scheduled_activity() {
+    let mut body = small_request();
+    body["calendar"] = json!({ "start_date": "2025-03-03", "day_start": "09:00" });
+
+    let response = post_json(&app(&[]), "/api/v1/rooms/schedule/ics", Some(&admin_token()), &body).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.header("content-type"), Some("text/calendar; charset=utf-8"));
+    assert_eq!(response.text.matches("BEGIN:VEVENT").count(), 2);
+    assert!(response.text.contains("DTSTART:20250303T090000\r\nDTEND:20250303T110000\r\nSUMMARY:Subject 1\r\nLOCATION:A-101\r\n"), "{}", response.text);
+    assert!(response.text.contains("LOCATION:B-201\r\n"), "{}", response.text);
+}
+
+#[tokio::test]
+async fn malformed_start_date_is_400() {
+    let mut body = small_request();
+    body["calendar"] = json!({ "start_date": "03/03/2025", "day_start": "09:00" });
+
+    let response = post_json(&app(&[]), "/api/v1/rooms/schedule/ics", Some(&admin_token()), &body).await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert!(response.text.contains("YYYY-MM-DD"), "{}", response.text);
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use std::time::Duration;
+
+use axum::{body::Body, http::{Method, StatusCode, header}};
+use common::{TestResponse, admin_token, app, request, send, small_request};
+use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::{TcpListener, TcpStream}};
+
+const VARS: &[(&str, &str)] = &[("WEBHOOK_ALLOWED_HOSTS", "127.0.0.1, hooks.example.edu")];
+
+async fn schedule_async(callback_url: &str) -> TestResponse {
+    let mut body = small_request();
+    body["callback_url"] = callback_url.into();
+
+    let submit = request(Method::POST, "/api/v1/rooms/schedule", Some(&admin_token()))
+        .header(header::CONTENT_TYPE, "application/json")
+        .header("prefer", "respond-async")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+
+    send(&app(VARS), submit).await
+}
+
+/// Head and body of one request, read up to its `Content-Length`.
+async fn read_request(stream: &mut TcpStream) -> String {
+    let mut request = Vec::new();
+
+    loop {
+        request.push(stream.read_u8().await.unwrap());
+        let text = String::from_utf8_lossy(&request);
+
+        if let Some((head, body)) = text.split_once("\r\n\r\n") {
+            let length: usize = head
+                .lines()
+                .find_map(|line| line.strip_prefix("Content-Length: "))
+                .map_or(0, |length| length.parse().unwrap());
+
+            if body.len() >= length {
+                return text.into_owned();
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn host_outside_the_allowlist_is_400() {
+    let response = schedule_async("http://hooks.other.edu/jobs").await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert!(response.text.contains("WEBHOOK_ALLOWED_HOSTS"), "{}", response.text);
+}
+
+#[tokio::test]
+async fn allowlisted_internal_host_gets_the_webhook() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let response = schedule_async(&format!("http://127.0.0.1:{}/jobs", port)).await;
+    assert_eq!(response.status, StatusCode::ACCEPTED);
+
+    let (mut stream, _) = tokio::time::timeout(Duration::from_secs(10), listener.accept()).await.unwrap().unwrap();
+    let request = read_request(&mut stream).await;
+    stream.write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n").await.unwrap();
+
+    assert!(request.starts_with("POST /jobs HTTP/1.1\r\n"), "{}", request);
+    assert!(request.contains(response.body["data"]["job_id"].as_str().unwrap()), "{}", request);
+}
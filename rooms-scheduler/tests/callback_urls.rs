@@ -0,0 +1,40 @@
+mod common;
+
+use axum::{body::Body, http::{Method, StatusCode, header}};
+use common::{TestResponse, admin_token, app, request, send, small_request};
+
+async fn schedule_async(callback_url: &str) -> TestResponse {
+    let mut body = small_request();
+    body["callback_url"] = callback_url.into();
+
+    let submit = request(Method::POST, "/api/v1/rooms/schedule", Some(&admin_token()))
+        .header(header::CONTENT_TYPE, "application/json")
+        .header("prefer", "respond-async")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+
+    send(&app(&[]), submit).await
+}
+
+#[tokio::test]
+async fn public_callback_is_accepted() {
+    assert_eq!(schedule_async("http://hooks.example.edu/jobs").await.status, StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn callback_with_a_line_break_is_400() {
+    let response = schedule_async("http://hooks.example.edu/jobs\r\nX-Injected: 1").await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert!(response.text.contains("whitespace or control characters"), "{}", response.text);
+}
+
+#[tokio::test]
+async fn callback_to_an_internal_address_is_400() {
+    for url in ["http://127.0.0.1:9000/", "http://169.254.169.254/latest/meta-data", "http://10.0.0.8/", "http://localhost/"] {
+        let response = schedule_async(url).await;
+
+        assert_eq!(response.status, StatusCode::BAD_REQUEST, "{}", url);
+        assert!(response.text.contains("loopback, link-local or private"), "{}", response.text);
+    }
+}
@@ -0,0 +1,32 @@
+mod common;
+
+use axum::{body::Body, http::{Method, StatusCode, header}};
+use common::{admin_token, app, get, post_json, request, send, small_request, token};
+use serde_json::json;
+
+#[tokio::test]
+async fn async_runs_belong_to_the_user_who_started_them() {
+    let app = app(&[]);
+    let submit = request(Method::POST, "/api/v1/rooms/schedule", Some(&admin_token()))
+        .header(header::CONTENT_TYPE, "application/json")
+        .header("prefer", "respond-async")
+        .body(Body::from(small_request().to_string()))
+        .unwrap();
+
+    let accepted = send(&app, submit).await;
+    assert_eq!(accepted.status, StatusCode::ACCEPTED);
+
+    let job = format!("/api/v1/rooms/schedule/jobs/{}", accepted.body["data"]["job_id"].as_str().unwrap());
+    let other_user = token(json!({ "user_id": 2 }));
+
+    assert_eq!(get(&app, &job, Some(&admin_token())).await.status, StatusCode::OK);
+    assert_eq!(get(&app, &job, Some(&other_user)).await.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn token_without_a_user_id_is_401() {
+    let anonymous = token(json!({ "user_id": null }));
+    let response = post_json(&app(&[]), "/api/v1/rooms/schedule", Some(&anonymous), &small_request()).await;
+
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,143 @@
+//! Helpers shared by the router tests. Each file under `tests/` is its own
+//! process, so each can configure the service through the environment.
+
+// Every test binary uses a different subset of the helpers
+#![allow(dead_code)]
+
+use std::sync::Once;
+
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{HeaderMap, Method, Request, StatusCode, header},
+};
+use jsonwebtoken::{EncodingKey, Header, encode};
+use serde_json::{Value, json};
+use tower::ServiceExt;
+
+/// Secret the service signs and checks tokens with when `JWT_SECRET` is unset.
+pub const DEFAULT_SECRET: &str = "default_secret";
+
+static CONFIGURE: Once = Once::new();
+
+/// The service's router, configured with `vars` on top of the defaults.
+/// The configuration is read once per process, so every test of a file
+/// must pass the same `vars`.
+pub fn app(vars: &[(&str, &str)]) -> Router {
+    CONFIGURE.call_once(|| {
+        for (key, value) in vars {
+            // SAFETY: runs before any test reads the environment; the others
+            // wait on the `Once`
+            unsafe { std::env::set_var(key, value) };
+        }
+    });
+
+    rooms_scheduler::router::create_router()
+}
+
+/// An access token signed with `secret`, with `overrides` replacing or
+/// adding claims to those of an administrator allowed to schedule.
+pub fn token_signed_with(secret: &str, overrides: Value) -> String {
+    let mut claims = json!({
+        "sub": "planner@example.edu",
+        "exp": chrono::Utc::now().timestamp() + 3600,
+        "user_id": 1,
+        "rol": "administrator",
+        "type": "access",
+        "permissions": ["schedule"],
+    });
+
+    if let (Value::Object(claims), Value::Object(overrides)) = (&mut claims, overrides) {
+        claims.extend(overrides);
+    }
+
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_bytes())).unwrap()
+}
+
+pub fn token(overrides: Value) -> String {
+    token_signed_with(DEFAULT_SECRET, overrides)
+}
+
+pub fn admin_token() -> String {
+    token(json!({}))
+}
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub text: String,
+    /// The body parsed as JSON; `Null` when it is not JSON.
+    pub body: Value,
+}
+
+impl TestResponse {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|value| value.to_str().ok())
+    }
+}
+
+pub async fn send(app: &Router, request: Request<Body>) -> TestResponse {
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let headers = response.headers().clone();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let text = String::from_utf8_lossy(&bytes).into_owned();
+
+    TestResponse {
+        status,
+        headers,
+        body: serde_json::from_str(&text).unwrap_or(Value::Null),
+        text,
+    }
+}
+
+/// A request with the bearer `token`, when given.
+pub fn request(method: Method, path: &str, token: Option<&str>) -> axum::http::request::Builder {
+    let builder = Request::builder().method(method).uri(path);
+
+    match token {
+        Some(token) => builder.header(header::AUTHORIZATION, format!("Bearer {}", token)),
+        None => builder,
+    }
+}
+
+pub async fn get(app: &Router, path: &str, token: Option<&str>) -> TestResponse {
+    send(app, request(Method::GET, path, token).body(Body::empty()).unwrap()).await
+}
+
+pub async fn post_json(app: &Router, path: &str, token: Option<&str>, body: &Value) -> TestResponse {
+    post_raw(app, path, token, &body.to_string()).await
+}
+
+pub async fn post_raw(app: &Router, path: &str, token: Option<&str>, body: &str) -> TestResponse {
+    let request = request(Method::POST, path, token)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+
+    send(app, request).await
+}
+
+pub fn room(id: u32, name: &str, capacity: u32) -> Value {
+    json!({ "id": id, "name": name, "capacity": capacity })
+}
+
+/// An activity of `students_count` students in `time_slots`, with the
+/// placeholder room requests carry before scheduling.
+pub fn activity(id: u32, students_count: u32, time_slots: &[u32]) -> Value {
+    json!({
+        "id": id,
+        "subject": format!("Subject {}", id),
+        "room": room(0, "", 0),
+        "time_slots": time_slots,
+        "students_count": students_count,
+    })
+}
+
+/// A request every activity of which fits.
+pub fn small_request() -> Value {
+    json!({
+        "rooms": [room(1, "A-101", 40), room(2, "B-201", 80)],
+        "activities": [activity(1, 35, &[0, 1]), activity(2, 60, &[0])],
+    })
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{activity, admin_token, app, post_json, room};
+use serde_json::{Value, json};
+
+async fn feasible(students_count: u32) -> Value {
+    let body = json!({
+        "activity": activity(1, students_count, &[0]),
+        "rooms": [room(1, "A-101", 20), room(2, "B-201", 40), room(3, "Hall", 120)],
+    });
+
+    let response = post_json(&app(&[]), "/api/v1/rooms/feasible", Some(&admin_token()), &body).await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    response.body["data"].clone()
+}
+
+fn ids(rooms: &Value) -> Vec<u64> {
+    rooms.as_array().unwrap().iter().map(|room| room["id"].as_u64().unwrap()).collect()
+}
+
+#[tokio::test]
+async fn activity_lists_every_room_it_fits() {
+    assert_eq!(ids(&feasible(30).await), vec![2, 3]);
+}
+
+#[tokio::test]
+async fn activity_too_large_for_every_room_gets_none() {
+    assert!(ids(&feasible(500).await).is_empty());
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{TestResponse, admin_token, app, post_json};
+use serde_json::json;
+
+async fn query(query: &str) -> TestResponse {
+    post_json(&app(&[]), "/api/v1/graphql", Some(&admin_token()), &json!({ "query": query })).await
+}
+
+/// `{ rooms { a { a ... } } }` with `levels` selection sets in all.
+fn nested_selections(levels: usize) -> String {
+    format!("{{ rooms {}{}}}", "{ a ".repeat(levels - 1), "} ".repeat(levels - 1))
+}
+
+#[tokio::test]
+async fn deeply_nested_selections_are_400() {
+    let response = query(&nested_selections(20_000)).await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.body["error"]["errors"][0]["message"], "Query is nested too deeply.");
+}
+
+#[tokio::test]
+async fn deeply_nested_argument_values_are_400() {
+    let response = query(&format!("{{ rooms(id: {}1{}) {{ id }} }}", "[".repeat(20_000), "]".repeat(20_000))).await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.body["error"]["errors"][0]["message"], "Query is nested too deeply.");
+}
+
+#[tokio::test]
+async fn selections_up_to_the_limit_are_executed() {
+    assert_eq!(query(&nested_selections(32)).await.status, StatusCode::OK);
+    assert_eq!(query(&nested_selections(33)).await.status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use axum::{body::Body, http::{Method, StatusCode, header}};
+use common::{TestResponse, admin_token, app, request, send, small_request};
+use serde_json::Value;
+
+const SCHEDULE: &str = "/api/v1/rooms/schedule";
+
+/// Persisting schedules gives every computed run its own id, so a repeated
+/// id means the run was served from the idempotency store.
+fn vars() -> [(&'static str, &'static str); 1] {
+    [("PERSIST_SCHEDULES", "true")]
+}
+
+async fn schedule(key: Option<&str>, body: &Value) -> TestResponse {
+    send_schedule(key, body, false).await
+}
+
+async fn schedule_async(key: Option<&str>, body: &Value) -> TestResponse {
+    send_schedule(key, body, true).await
+}
+
+async fn send_schedule(key: Option<&str>, body: &Value, respond_async: bool) -> TestResponse {
+    let mut builder = request(Method::POST, SCHEDULE, Some(&admin_token())).header(header::CONTENT_TYPE, "application/json");
+
+    if respond_async {
+        builder = builder.header("prefer", "respond-async");
+    }
+
+    if let Some(key) = key {
+        builder = builder.header("idempotency-key", key);
+    }
+
+    send(&app(&vars()), builder.body(Body::from(body.to_string())).unwrap()).await
+}
+
+#[tokio::test]
+async fn repeated_key_returns_the_cached_run() {
+    let first = schedule(Some("retry-1"), &small_request()).await;
+    let retried = schedule(Some("retry-1"), &small_request()).await;
+
+    assert_eq!(first.status, StatusCode::OK);
+    assert_eq!(retried.status, StatusCode::OK);
+    assert!(first.header("x-schedule-id").is_some());
+    assert_eq!(first.header("x-schedule-id"), retried.header("x-schedule-id"));
+    assert_eq!(first.body["data"], retried.body["data"]);
+}
+
+#[tokio::test]
+async fn requests_without_a_key_are_computed_again() {
+    let first = schedule(None, &small_request()).await;
+    let second = schedule(None, &small_request()).await;
+
+    assert_ne!(first.header("x-schedule-id"), second.header("x-schedule-id"));
+}
+
+#[tokio::test]
+async fn key_reused_with_another_body_is_409() {
+    let mut changed = small_request();
+    changed["activities"][0]["students_count"] = 30.into();
+
+    let first = schedule(Some("retry-2"), &small_request()).await;
+    let conflicting = schedule(Some("retry-2"), &changed).await;
+
+    assert_eq!(first.status, StatusCode::OK);
+    assert_eq!(conflicting.status, StatusCode::CONFLICT);
+    assert!(conflicting.text.contains("Idempotency-Key"), "{}", conflicting.text);
+}
+
+#[tokio::test]
+async fn repeated_key_returns_the_same_async_job() {
+    let first = schedule_async(Some("async-1"), &small_request()).await;
+    let retried = schedule_async(Some("async-1"), &small_request()).await;
+
+    assert_eq!(first.status, StatusCode::ACCEPTED);
+    assert_eq!(retried.status, StatusCode::ACCEPTED);
+    assert_eq!(first.body["data"]["job_id"], retried.body["data"]["job_id"]);
+    assert_eq!(first.header("location"), retried.header("location"));
+}
+
+#[tokio::test]
+async fn async_runs_without_a_key_start_new_jobs() {
+    let first = schedule_async(None, &small_request()).await;
+    let second = schedule_async(None, &small_request()).await;
+
+    assert_ne!(first.body["data"]["job_id"], second.body["data"]["job_id"]);
+}
+
+#[tokio::test]
+async fn async_key_reused_with_another_body_is_409() {
+    let mut changed = small_request();
+    changed["activities"][0]["students_count"] = 30.into();
+
+    let first = schedule_async(Some("async-2"), &small_request()).await;
+    let conflicting = schedule_async(Some("async-2"), &changed).await;
+
+    assert_eq!(first.status, StatusCode::ACCEPTED);
+    assert_eq!(conflicting.status, StatusCode::CONFLICT);
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{TestResponse, activity, admin_token, app, post_json, room, small_request};
+use serde_json::{Value, json};
+
+async fn schedule(body: &Value) -> TestResponse {
+    post_json(&app(&[]), "/api/v1/rooms/schedule", Some(&admin_token()), body).await
+}
+
+/// The fields of every reported violation.
+fn fields(response: &TestResponse) -> Vec<&str> {
+    response.body["error"]["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|error| error["field"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn valid_payload_passes_through() {
+    let response = schedule(&small_request()).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn zero_capacity_is_rejected_naming_the_room() {
+    let response = schedule(&json!({ "rooms": [room(1, "A-101", 0)], "activities": [activity(1, 10, &[0])] })).await;
+
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(fields(&response), vec!["rooms[0].capacity"]);
+    assert_eq!(response.body["error"]["errors"][0]["message"], "Room 1 has a capacity of zero.");
+}
+
+#[tokio::test]
+async fn zero_students_is_rejected_naming_the_activity() {
+    let response = schedule(&json!({ "rooms": [room(1, "A-101", 30)], "activities": [activity(4, 0, &[0])] })).await;
+
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(fields(&response), vec!["activities[0].students_count"]);
+}
+
+#[tokio::test]
+async fn duplicate_activity_ids_are_rejected() {
+    let activities = [activity(4, 10, &[0]), activity(4, 10, &[1])];
+    let response = schedule(&json!({ "rooms": [room(1, "A-101", 30)], "activities": activities })).await;
+
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(fields(&response), vec!["activities[1].id"]);
+    assert_eq!(response.body["error"]["errors"][0]["message"], "Activity id 4 is duplicated.");
+}
+
+#[tokio::test]
+async fn duplicate_room_ids_are_rejected() {
+    let rooms = [room(1, "A-101", 30), room(1, "A-101", 30)];
+    let response = schedule(&json!({ "rooms": rooms, "activities": [activity(1, 10, &[0])] })).await;
+
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(fields(&response), vec!["rooms[1].id"]);
+    assert_eq!(response.body["error"]["errors"][0]["message"], "Room id 1 is duplicated.");
+}
+
+#[tokio::test]
+async fn distinct_rooms_are_scheduled() {
+    let rooms = [room(1, "A-101", 30), room(2, "A-102", 30)];
+    let response = schedule(&json!({ "rooms": rooms, "activities": [activity(1, 10, &[0]), activity(2, 10, &[0])] })).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["data"]["stats"]["rooms_used"], 2);
+}
+
+#[tokio::test]
+async fn oversubscribed_slot_is_409_with_the_shortfall() {
+    let activities = [activity(1, 10, &[3]), activity(2, 10, &[3]), activity(3, 10, &[3])];
+    let response = schedule(&json!({ "rooms": [room(1, "A-101", 30), room(2, "A-102", 30)], "activities": activities })).await;
+
+    assert_eq!(response.status, StatusCode::CONFLICT);
+    assert_eq!(
+        response.body["error"]["conflicts"],
+        json!([{ "day": 0, "slot": 3, "activities": 3, "rooms": 2, "shortfall": 1 }])
+    );
+}
+
+#[tokio::test]
+async fn partial_mode_returns_the_overflow_instead_of_409() {
+    let activities = [activity(1, 10, &[3]), activity(2, 10, &[3]), activity(3, 10, &[3])];
+    let rooms = [room(1, "A-101", 30), room(2, "A-102", 30)];
+    let response = schedule(&json!({ "rooms": rooms, "activities": activities, "allow_partial": true })).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["data"]["stats"]["scheduled"], 2);
+    assert_eq!(response.body["data"]["unscheduled_activities"][0]["reason"], "all_rooms_busy");
+}
+
+#[tokio::test]
+async fn duplicated_time_slot_is_rejected() {
+    let response = schedule(&json!({ "rooms": [room(1, "A-101", 30)], "activities": [activity(5, 10, &[2, 2, 3])] })).await;
+
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(fields(&response), vec!["activities[0].time_slots"]);
+    assert_eq!(response.body["error"]["errors"][0]["message"], "Activity 5 lists time slot 2 more than once.");
+}
+
+#[tokio::test]
+async fn every_violation_is_reported_in_one_response() {
+    let rooms = [room(1, "A-101", 0), room(1, "A-101", 30)];
+    let activities = [activity(4, 0, &[0]), activity(4, 10, &[1, 1])];
+    let response = schedule(&json!({ "rooms": rooms, "activities": activities })).await;
+
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        fields(&response),
+        vec!["rooms[0].capacity", "rooms[1].id", "activities[0].students_count", "activities[1].id", "activities[1].time_slots"]
+    );
+}
+
+#[tokio::test]
+async fn pinned_activities_sharing_an_instructor_are_409() {
+    let mut first = activity(1, 10, &[0, 1]);
+    first["pinned_room"] = 1.into();
+    first["instructor_id"] = 7.into();
+    let mut second = activity(2, 10, &[1]);
+    second["pinned_room"] = 2.into();
+    second["instructor_id"] = 7.into();
+
+    let body = json!({
+        "rooms": [room(1, "A-101", 30), room(2, "A-102", 30)],
+        "activities": [first, second],
+        "instructors": [{ "id": 7, "name": "Dr. Rivas", "unavailable_slots": [0] }],
+    });
+    let response = schedule(&body).await;
+
+    assert_eq!(response.status, StatusCode::CONFLICT);
+    assert_eq!(
+        response.body["error"]["conflicts"],
+        json!([
+            { "day": 0, "slot": 0, "kind": "instructor_unavailable", "instructor_id": 7, "activity_ids": [1] },
+            { "day": 0, "slot": 1, "kind": "instructor", "instructor_id": 7, "activity_ids": [1, 2] },
+        ])
+    );
+}
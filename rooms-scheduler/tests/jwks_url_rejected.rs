@@ -0,0 +1,36 @@
+//! JWKS support was removed: a deployment still pointing at a key set must
+//! fail at startup instead of rejecting every token at runtime.
+
+use std::process::{Command, Output};
+
+const MESSAGE: &str = "Invalid configuration: JWT_JWKS_URL is not supported; set JWT_PUBLIC_KEY to the identity provider's public key.";
+
+fn start(vars: &[(&str, &str)]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rooms-scheduler"))
+        .env_remove("JWT_JWKS_URL")
+        .env_remove("CONFIG_FILE")
+        .env("JWT_SECRET", "test-secret")
+        .envs(vars.iter().copied())
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn jwks_url_variable_stops_startup() {
+    let output = start(&[("JWT_JWKS_URL", "http://auth:8080/.well-known/jwks.json")]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains(MESSAGE));
+}
+
+#[test]
+fn jwks_url_setting_stops_startup() {
+    let path = std::env::temp_dir().join(format!("jwks-url-{}.toml", std::process::id()));
+    std::fs::write(&path, "[jwt]\njwks_url = \"http://auth:8080/.well-known/jwks.json\"\n").unwrap();
+
+    let output = start(&[("CONFIG_FILE", path.to_str().unwrap())]);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains(MESSAGE));
+}
@@ -0,0 +1,25 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{app, get, token};
+use serde_json::json;
+
+const JOB: &str = "/api/v1/rooms/schedule/jobs/missing";
+
+fn vars() -> [(&'static str, &'static str); 1] {
+    [("JWT_VALIDATE_EXP", "false")]
+}
+
+#[tokio::test]
+async fn long_expired_token_is_accepted() {
+    let expired = token(json!({ "exp": chrono::Utc::now().timestamp() - 86_400 }));
+
+    assert_eq!(get(&app(&vars()), JOB, Some(&expired)).await.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn signature_is_still_checked() {
+    let forged = common::token_signed_with("another_secret", json!({}));
+
+    assert_eq!(get(&app(&vars()), JOB, Some(&forged)).await.status, StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{app, get, token};
+use serde_json::{Value, json};
+
+const ISSUER: &str = "https://auth.example.edu";
+const AUDIENCE: &str = "rooms-scheduler";
+
+async fn list_rooms_with(claims: Value) -> StatusCode {
+    let app = app(&[("JWT_ISSUER", ISSUER), ("JWT_AUDIENCE", AUDIENCE)]);
+
+    get(&app, "/api/v1/rooms", Some(&token(claims))).await.status
+}
+
+#[tokio::test]
+async fn matching_issuer_and_audience_are_accepted() {
+    assert_eq!(list_rooms_with(json!({ "iss": ISSUER, "aud": AUDIENCE })).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn audience_list_containing_the_service_is_accepted() {
+    let claims = json!({ "iss": ISSUER, "aud": ["dashboard", AUDIENCE] });
+
+    assert_eq!(list_rooms_with(claims).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn mismatching_issuer_is_rejected() {
+    let claims = json!({ "iss": "https://elsewhere.example.com", "aud": AUDIENCE });
+
+    assert_eq!(list_rooms_with(claims).await, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn mismatching_audience_is_rejected() {
+    assert_eq!(list_rooms_with(json!({ "iss": ISSUER, "aud": "billing" })).await, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn missing_claims_are_rejected_when_configured() {
+    assert_eq!(list_rooms_with(json!({ "aud": AUDIENCE })).await, StatusCode::UNAUTHORIZED);
+    assert_eq!(list_rooms_with(json!({ "iss": ISSUER })).await, StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{app, get, token};
+use serde_json::json;
+
+const JOB: &str = "/api/v1/rooms/schedule/jobs/missing";
+
+/// A token that expired `seconds` ago.
+fn expired(seconds: i64) -> String {
+    token(json!({ "exp": chrono::Utc::now().timestamp() - seconds }))
+}
+
+fn vars() -> [(&'static str, &'static str); 1] {
+    [("JWT_LEEWAY_SECONDS", "120")]
+}
+
+#[tokio::test]
+async fn token_expired_within_the_leeway_is_accepted() {
+    let response = get(&app(&vars()), JOB, Some(&expired(60))).await;
+
+    // Past authentication, the job itself is unknown
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn token_expired_beyond_the_leeway_is_401() {
+    let response = get(&app(&vars()), JOB, Some(&expired(600))).await;
+
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+}
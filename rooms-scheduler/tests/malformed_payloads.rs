@@ -0,0 +1,77 @@
+mod common;
+
+use axum::{body::Body, http::{Method, StatusCode}};
+use common::{admin_token, app, post_json, post_raw, request, send, small_request};
+use serde_json::json;
+
+const SCHEDULE: &str = "/api/v1/rooms/schedule";
+
+#[tokio::test]
+async fn well_formed_payload_is_scheduled() {
+    let response = post_json(&app(&[]), SCHEDULE, Some(&admin_token()), &small_request()).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["data"]["scheduled_activities"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn wrong_type_is_422_with_the_field_path() {
+    let body = json!({ "rooms": [{ "id": "A-101", "name": "A-101", "capacity": 40 }], "activities": [] });
+
+    let response = post_json(&app(&[]), SCHEDULE, Some(&admin_token()), &body).await;
+
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.body["success"], false);
+    assert_eq!(response.body["error"]["path"], "rooms[0].id");
+    assert!(response.body["error"]["message"].as_str().unwrap().contains("invalid type"));
+}
+
+#[tokio::test]
+async fn missing_field_is_422_naming_the_field() {
+    let body = json!({ "rooms": [], "activities": [{ "id": 1, "room": { "id": 0, "name": "", "capacity": 0 }, "students_count": 3 }] });
+
+    let response = post_json(&app(&[]), SCHEDULE, Some(&admin_token()), &body).await;
+
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.body["error"]["path"], "activities[0]");
+    assert!(response.body["error"]["message"].as_str().unwrap().contains("missing field `subject`"));
+}
+
+#[tokio::test]
+async fn invalid_json_is_400_with_the_position() {
+    let response = post_raw(&app(&[]), SCHEDULE, Some(&admin_token()), r#"{"rooms": ["#).await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.body["code"], 400);
+    assert_eq!(response.body["error"]["path"], "rooms");
+    assert!(response.body["error"]["message"].as_str().unwrap().contains("EOF"));
+}
+
+#[tokio::test]
+async fn trailing_characters_are_400() {
+    let response = post_raw(&app(&[]), SCHEDULE, Some(&admin_token()), r#"{"rooms": [], "activities": []} x"#).await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.body["error"]["path"], ".");
+}
+
+#[tokio::test]
+async fn non_json_content_type_is_415() {
+    let request = request(Method::POST, SCHEDULE, Some(&admin_token()))
+        .header("content-type", "text/plain")
+        .body(Body::from(small_request().to_string()))
+        .unwrap();
+
+    let response = send(&app(&[]), request).await;
+
+    assert_eq!(response.status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert_eq!(response.body["success"], false);
+}
+
+#[tokio::test]
+async fn schedule_id_is_omitted_without_persistence() {
+    let response = post_json(&app(&[]), SCHEDULE, Some(&admin_token()), &small_request()).await;
+
+    assert!(response.body["data"].get("schedule_id").is_none());
+    assert!(response.header("x-schedule-id").is_none());
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{admin_token, app, post_json, room};
+use serde_json::json;
+
+const SCHEDULE: &str = "/api/v1/rooms/schedule";
+
+fn in_minutes(start_minute: u32, duration_minutes: u32) -> serde_json::Value {
+    json!({
+        "rooms": [room(1, "A-101", 40)],
+        "slot_length_minutes": 30,
+        "activities": [{
+            "id": 1,
+            "subject": "Algebra",
+            "room": room(0, "", 0),
+            "students_count": 20,
+            "start_minute": start_minute,
+            "duration_minutes": duration_minutes,
+        }],
+    })
+}
+
+#[tokio::test]
+async fn minutes_are_scheduled_as_slots() {
+    let response = post_json(&app(&[]), SCHEDULE, Some(&admin_token()), &in_minutes(480, 90)).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["data"]["scheduled_activities"][0]["time_slots"], json!([16, 17, 18]));
+}
+
+#[tokio::test]
+async fn non_divisible_duration_is_400() {
+    let response = post_json(&app(&[]), SCHEDULE, Some(&admin_token()), &in_minutes(480, 50)).await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert!(response.text.contains("not a multiple of the 30 minute slot length"), "{}", response.text);
+}
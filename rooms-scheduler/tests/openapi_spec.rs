@@ -0,0 +1,335 @@
+//! Keeps the hand-written `src/openapi.json` in step with the router and
+//! the models it describes.
+
+mod common;
+
+use std::collections::BTreeSet;
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Method, StatusCode, header},
+};
+use common::{TestResponse, app, request, send, token};
+use rooms_scheduler_core::{ActivitiesRequest, Activity, BatchScenario, CalendarRequest, FeasibilityRequest, Room, RoomBlackout};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+
+const SPEC: &str = include_str!("../src/openapi.json");
+const ROUTER: &str = include_str!("../src/router.rs");
+
+const METHODS: [&str; 4] = ["get", "post", "put", "delete"];
+
+fn spec() -> Value {
+    serde_json::from_str(SPEC).unwrap()
+}
+
+/// Every `(path, method)` the spec documents.
+fn documented_operations(spec: &Value) -> BTreeSet<(String, String)> {
+    spec["paths"]
+        .as_object()
+        .unwrap()
+        .iter()
+        .flat_map(|(path, item)| {
+            METHODS
+                .iter()
+                .filter(|method| item.get(**method).is_some())
+                .map(|method| (path.clone(), method.to_string()))
+        })
+        .collect()
+}
+
+/// Every path `create_router` registers, read from its source.
+fn registered_paths() -> BTreeSet<String> {
+    ROUTER
+        .split(".route(\"")
+        .skip(1)
+        .map(|rest| rest.split('"').next().unwrap().to_string())
+        .collect()
+}
+
+/// The path with every `{param}` filled in.
+fn concrete(path: &str) -> String {
+    path.split('/')
+        .map(|segment| if segment.starts_with('{') { "1" } else { segment })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Probes send hundreds of requests as one user.
+const VARS: &[(&str, &str)] = &[("RATE_LIMIT_PER_USER_PER_MINUTE", "0"), ("RATE_LIMIT_PER_IP_PER_MINUTE", "0")];
+
+fn every_permission() -> String {
+    token(json!({ "permissions": ["*"] }))
+}
+
+/// Whether the router has a handler for the method and path. Probes carry
+/// no body, so handlers reject them before doing any work.
+async fn is_routed(app: &Router, method: &str, path: &str) -> bool {
+    let method = Method::from_bytes(method.to_uppercase().as_bytes()).unwrap();
+    let probe = request(method, &concrete(path), Some(&every_permission())).body(Body::empty()).unwrap();
+    let response = send(app, probe).await;
+
+    // Unmatched routes get axum's empty 404; handlers answer with a body
+    match response.status {
+        StatusCode::METHOD_NOT_ALLOWED => false,
+        StatusCode::NOT_FOUND => !response.text.is_empty(),
+        _ => true,
+    }
+}
+
+#[tokio::test]
+async fn unknown_route_is_not_routed() {
+    let app = app(VARS);
+
+    assert!(!is_routed(&app, "get", "/api/v1/nowhere").await);
+    assert!(!is_routed(&app, "delete", "/api/v1/rooms/schedule").await);
+}
+
+#[tokio::test]
+async fn every_documented_operation_is_routed() {
+    let app = app(VARS);
+    let mut missing = Vec::new();
+
+    for (path, method) in documented_operations(&spec()) {
+        if !is_routed(&app, &method, &path).await {
+            missing.push(format!("{} {}", method.to_uppercase(), path));
+        }
+    }
+
+    assert!(missing.is_empty(), "documented but not routed: {:?}", missing);
+}
+
+#[tokio::test]
+async fn every_route_is_documented() {
+    let app = app(VARS);
+    let documented = documented_operations(&spec());
+    let mut undocumented = Vec::new();
+
+    for path in registered_paths() {
+        for method in METHODS {
+            if is_routed(&app, method, &path).await && !documented.contains(&(path.clone(), method.to_string())) {
+                undocumented.push(format!("{} {}", method.to_uppercase(), path));
+            }
+        }
+    }
+
+    assert!(!registered_paths().is_empty());
+    assert!(undocumented.is_empty(), "routed but not documented: {:?}", undocumented);
+}
+
+/// Follows a `#/components/...` reference.
+fn resolve<'a>(spec: &'a Value, schema: &'a Value) -> &'a Value {
+    match schema["$ref"].as_str() {
+        Some(reference) => resolve(spec, spec.pointer(reference.trim_start_matches('#')).unwrap()),
+        None => schema,
+    }
+}
+
+/// Properties an object schema declares, through `allOf`; `None` when it
+/// accepts any property.
+fn declared_properties(spec: &Value, schema: &Value) -> Option<BTreeSet<String>> {
+    let schema = resolve(spec, schema);
+
+    if let Some(members) = schema["allOf"].as_array() {
+        return members.iter().try_fold(BTreeSet::new(), |mut properties, member| {
+            properties.extend(declared_properties(spec, member)?);
+            Some(properties)
+        });
+    }
+
+    if schema.get("additionalProperties").is_some() {
+        return None;
+    }
+
+    schema["properties"].as_object().map(|properties| properties.keys().cloned().collect())
+}
+
+/// Where `value` breaks `schema`: wrong types, missing required properties,
+/// values out of range, and properties the schema does not declare.
+fn violations(spec: &Value, schema: &Value, value: &Value, at: &str) -> Vec<String> {
+    let schema = resolve(spec, schema);
+    let mut found = Vec::new();
+
+    if let Some(alternatives) = schema["oneOf"].as_array().or(schema["anyOf"].as_array()) {
+        if !alternatives.iter().any(|alternative| violations(spec, alternative, value, at).is_empty()) {
+            found.push(format!("{}: matches none of the alternatives", at));
+        }
+        return found;
+    }
+
+    for member in schema["allOf"].as_array().into_iter().flatten() {
+        found.extend(violations(spec, member, value, at).into_iter().filter(|v| !v.contains("undeclared property")));
+    }
+
+    let type_matches = match schema["type"].as_str() {
+        Some("object") => value.is_object(),
+        Some("array") => value.is_array(),
+        Some("string") => value.is_string(),
+        Some("integer") => value.is_i64() || value.is_u64(),
+        Some("number") => value.is_number(),
+        Some("boolean") => value.is_boolean(),
+        Some("null") => value.is_null(),
+        _ => true,
+    };
+
+    if !type_matches {
+        found.push(format!("{}: expected {}, got {}", at, schema["type"], value));
+        return found;
+    }
+
+    if let (Some(minimum), Some(number)) = (schema["minimum"].as_f64(), value.as_f64())
+        && number < minimum
+    {
+        found.push(format!("{}: {} is below the minimum {}", at, number, minimum));
+    }
+
+    if let Some(allowed) = schema["enum"].as_array()
+        && !allowed.contains(value)
+    {
+        found.push(format!("{}: {} is not one of {:?}", at, value, allowed));
+    }
+
+    if let (Some(items), Some(elements)) = (schema.get("items"), value.as_array()) {
+        for (index, element) in elements.iter().enumerate() {
+            found.extend(violations(spec, items, element, &format!("{}[{}]", at, index)));
+        }
+    }
+
+    let Some(object) = value.as_object() else {
+        return found;
+    };
+
+    for required in schema["required"].as_array().into_iter().flatten() {
+        if !object.contains_key(required.as_str().unwrap()) {
+            found.push(format!("{}: missing required property {}", at, required));
+        }
+    }
+
+    if let Some(properties) = schema["properties"].as_object() {
+        for (name, property) in properties {
+            if let Some(field) = object.get(name) {
+                found.extend(violations(spec, property, field, &format!("{}.{}", at, name)));
+            }
+        }
+    }
+
+    if let Some(declared) = declared_properties(spec, schema) {
+        for name in object.keys().filter(|name| !declared.contains(*name)) {
+            found.push(format!("{}: undeclared property {}", at, name));
+        }
+    }
+
+    found
+}
+
+/// `(path, method, example)` for every example request body.
+fn request_examples(spec: &Value) -> Vec<(String, String, Value)> {
+    documented_operations(spec)
+        .into_iter()
+        .flat_map(|(path, method)| {
+            let media = &spec["paths"][&path][&method]["requestBody"]["content"]["application/json"];
+            let examples: Vec<Value> = media["examples"]
+                .as_object()
+                .into_iter()
+                .flat_map(|examples| examples.values())
+                .map(|example| resolve(spec, example)["value"].clone())
+                .collect();
+
+            examples.into_iter().map(move |example| (path.clone(), method.clone(), example))
+        })
+        .collect()
+}
+
+#[test]
+fn every_json_request_body_has_an_example() {
+    let spec = spec();
+    let with_examples: BTreeSet<(String, String)> =
+        request_examples(&spec).into_iter().map(|(path, method, _)| (path, method)).collect();
+
+    for (path, method) in documented_operations(&spec) {
+        if spec["paths"][&path][&method]["requestBody"]["content"].get("application/json").is_some() {
+            assert!(with_examples.contains(&(path.clone(), method.clone())), "{} {} has no example", method, path);
+        }
+    }
+}
+
+#[test]
+fn request_examples_match_their_schemas() {
+    let spec = spec();
+
+    for (path, method, example) in request_examples(&spec) {
+        let schema = &spec["paths"][&path][&method]["requestBody"]["content"]["application/json"]["schema"];
+        let found = violations(&spec, schema, &example, "body");
+
+        assert!(found.is_empty(), "{} {}: {:?}", method, path, found);
+    }
+}
+
+/// Deserializes the example into the model the handler reads, and checks
+/// the spec declares every property the model writes back.
+fn check_model<T: DeserializeOwned + Serialize>(spec: &Value, schema: &str, example: &Value) {
+    let model: T = serde_json::from_value(example.clone()).unwrap_or_else(|err| panic!("{}: {}", schema, err));
+    let schema = json!({ "$ref": format!("#/components/schemas/{}", schema) });
+
+    let written = serde_json::to_value(model).unwrap();
+    let undeclared: Vec<String> = violations(spec, &schema, &written, "model")
+        .into_iter()
+        .filter(|violation| violation.contains("undeclared property"))
+        .collect();
+
+    assert!(undeclared.is_empty(), "{:?}", undeclared);
+}
+
+#[test]
+fn request_examples_deserialize_into_the_models() {
+    let spec = spec();
+
+    for (path, method, example) in request_examples(&spec) {
+        match (path.as_str(), method.as_str()) {
+            ("/api/v1/rooms/schedule" | "/api/v1/rooms/schedule/stream" | "/api/v1/rooms/schedule/validate", _) => {
+                check_model::<ActivitiesRequest>(&spec, "ActivitiesRequest", &example)
+            }
+            ("/api/v1/rooms/schedule/batch", _) => {
+                for scenario in example.as_array().unwrap() {
+                    check_model::<BatchScenario>(&spec, "BatchScenario", scenario);
+                }
+            }
+            ("/api/v1/rooms/schedule/ics", _) => check_model::<CalendarRequest>(&spec, "CalendarRequest", &example),
+            ("/api/v1/rooms/feasible", _) => check_model::<FeasibilityRequest>(&spec, "FeasibilityRequest", &example),
+            ("/api/v1/rooms" | "/api/v1/rooms/{room_id}", _) => check_model::<Room>(&spec, "Room", &example),
+            ("/api/v1/activities" | "/api/v1/activities/{activity_id}", _) => check_model::<Activity>(&spec, "Activity", &example),
+            ("/api/v1/rooms/{room_id}/blackouts", _) => check_model::<RoomBlackout>(&spec, "RoomBlackout", &example),
+            _ => {}
+        }
+    }
+}
+
+async fn send_example(app: &Router, path: &str, method: &str, example: &Value) -> TestResponse {
+    let method = Method::from_bytes(method.to_uppercase().as_bytes()).unwrap();
+
+    let body = request(method, &concrete(path), Some(&every_permission()))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(example.to_string()))
+        .unwrap();
+
+    send(app, body).await
+}
+
+#[tokio::test]
+async fn request_examples_are_accepted_by_their_routes() {
+    let app = app(VARS);
+
+    for (path, method, example) in request_examples(&spec()) {
+        let response = send_example(&app, &path, &method, &example).await;
+
+        assert!(
+            ![StatusCode::BAD_REQUEST, StatusCode::UNSUPPORTED_MEDIA_TYPE, StatusCode::UNPROCESSABLE_ENTITY].contains(&response.status),
+            "{} {} rejected its example with {}: {}",
+            method,
+            path,
+            response.status,
+            response.text
+        );
+    }
+}
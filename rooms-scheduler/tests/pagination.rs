@@ -0,0 +1,72 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{TestResponse, activity, admin_token, app, post_json, room};
+use serde_json::{Value, json};
+
+/// Five activities that fit in the one room and two that do not fit at all.
+fn request() -> Value {
+    let mut activities: Vec<Value> = (1..=5).map(|id| activity(id, 10, &[id])).collect();
+    activities.extend((6..=7).map(|id| activity(id, 100, &[0])));
+
+    json!({ "rooms": [room(1, "A-101", 40)], "activities": activities, "allow_partial": true })
+}
+
+async fn page(query: &str) -> TestResponse {
+    post_json(&app(&[]), &format!("/api/v1/rooms/schedule{}", query), Some(&admin_token()), &request()).await
+}
+
+fn ids(items: &Value) -> Vec<u64> {
+    items.as_array().unwrap().iter().map(|item| item["id"].as_u64().unwrap()).collect()
+}
+
+#[tokio::test]
+async fn pages_slice_both_lists_and_report_the_totals() {
+    let first = page("?page=1&page_size=2").await;
+    let last = page("?page=3&page_size=2").await;
+
+    assert_eq!(first.status, StatusCode::OK);
+    assert_eq!(ids(&first.body["data"]["scheduled_activities"]), vec![1, 2]);
+    let mut unscheduled = ids(&first.body["data"]["unscheduled_activities"]);
+    unscheduled.sort();
+    assert_eq!(unscheduled, vec![6, 7]);
+    assert_eq!(ids(&last.body["data"]["scheduled_activities"]), vec![5]);
+    assert!(last.body["data"]["unscheduled_activities"].as_array().unwrap().is_empty());
+
+    assert_eq!(
+        last.body["data"]["pagination"],
+        json!({ "page": 3, "page_size": 2, "scheduled_total": 5, "unscheduled_total": 2 })
+    );
+    assert_eq!(last.body["data"]["stats"]["scheduled"], 5);
+}
+
+#[tokio::test]
+async fn page_past_the_end_is_empty() {
+    let response = page("?page=9&page_size=2").await;
+
+    assert!(response.body["data"]["scheduled_activities"].as_array().unwrap().is_empty());
+    assert_eq!(response.body["data"]["pagination"]["scheduled_total"], 5);
+}
+
+#[tokio::test]
+async fn page_size_alone_starts_at_the_first_page() {
+    let response = page("?page_size=5").await;
+
+    assert_eq!(response.body["data"]["pagination"]["page"], 1);
+    assert_eq!(ids(&response.body["data"]["scheduled_activities"]), vec![1, 2, 3, 4, 5]);
+}
+
+#[tokio::test]
+async fn unpaginated_response_has_everything_and_no_metadata() {
+    let response = page("").await;
+
+    assert_eq!(response.body["data"]["scheduled_activities"].as_array().unwrap().len(), 5);
+    assert!(response.body["data"].get("pagination").is_none());
+}
+
+#[tokio::test]
+async fn zero_page_or_size_is_400() {
+    assert_eq!(page("?page=0").await.status, StatusCode::BAD_REQUEST);
+    assert_eq!(page("?page_size=0").await.status, StatusCode::BAD_REQUEST);
+    assert_eq!(page("?page=first").await.status, StatusCode::BAD_REQUEST);
+}
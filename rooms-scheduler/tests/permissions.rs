@@ -0,0 +1,56 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{app, post_json, small_request, token};
+use serde_json::json;
+
+async fn schedule_as_teacher_with(permissions: &[&str]) -> StatusCode {
+    let token = token(json!({ "rol": "teacher", "permissions": permissions }));
+
+    post_json(&app(&[]), "/api/v1/rooms/schedule", Some(&token), &small_request()).await.status
+}
+
+#[tokio::test]
+async fn token_with_the_required_permission_is_allowed() {
+    assert_eq!(schedule_as_teacher_with(&["schedule:write"]).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn resource_wide_grants_cover_every_action() {
+    for granted in ["schedule", "schedule:*", "*"] {
+        assert_eq!(schedule_as_teacher_with(&[granted]).await, StatusCode::OK, "{}", granted);
+    }
+}
+
+#[tokio::test]
+async fn token_without_the_required_permission_is_forbidden() {
+    assert_eq!(schedule_as_teacher_with(&[]).await, StatusCode::FORBIDDEN);
+    assert_eq!(schedule_as_teacher_with(&["schedule:read", "rooms:write"]).await, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn role_permissions_count_without_a_permissions_claim() {
+    let token = token(json!({ "rol": "administrator", "permissions": [] }));
+
+    let response = post_json(&app(&[]), "/api/v1/rooms", Some(&token), &json!({ "id": 7, "name": "C-7", "capacity": 20 })).await;
+
+    assert_eq!(response.status, StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn administrators_schedule_without_a_permissions_claim() {
+    let token = token(json!({ "rol": "administrator", "permissions": [] }));
+
+    let response = post_json(&app(&[]), "/api/v1/rooms/schedule", Some(&token), &small_request()).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn other_roles_need_the_permission_to_schedule() {
+    let token = token(json!({ "rol": "teacher", "permissions": [] }));
+
+    let response = post_json(&app(&[]), "/api/v1/rooms/schedule", Some(&token), &small_request()).await;
+
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,161 @@
+//! Schedules kept in Postgres. These need a server the tests may create
+//! tables in: set `TEST_DATABASE_URL` and run with `--ignored`.
+
+mod common;
+
+use std::{
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    process::{Child, Command, Stdio},
+    thread,
+    time::Duration,
+};
+
+use axum::http::StatusCode;
+use common::{admin_token, app, get, post_json, small_request, token};
+use serde_json::{Value, json};
+
+const SCHEDULE: &str = "/api/v1/rooms/schedule";
+
+fn database_url() -> String {
+    std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL names the Postgres server to test against")
+}
+
+fn app_with_database() -> axum::Router {
+    app(&[("PERSIST_SCHEDULES", "true"), ("DATABASE_URL", &database_url())])
+}
+
+/// A token for a user no other test schedules as.
+fn fresh_user() -> String {
+    token(json!({ "user_id": rand::random::<u32>() as i64 + 1_000_000 }))
+}
+
+#[tokio::test]
+#[ignore = "needs a Postgres server; set TEST_DATABASE_URL"]
+async fn stored_schedule_is_read_back_from_the_database() {
+    let app = app_with_database();
+    let created = post_json(&app, SCHEDULE, Some(&admin_token()), &small_request()).await;
+    let schedule_id = created.body["data"]["schedule_id"].as_str().unwrap();
+
+    let stored = get(&app, &format!("{}/{}", SCHEDULE, schedule_id), Some(&admin_token())).await;
+
+    assert_eq!(stored.status, StatusCode::OK);
+    assert_eq!(stored.body["data"]["schedule_id"], schedule_id);
+    assert_eq!(stored.body["data"]["user_id"], 1);
+    assert_eq!(stored.body["data"]["request"]["rooms"][1]["name"], "B-201");
+    assert_eq!(stored.body["data"]["scheduled_activities"], created.body["data"]["scheduled_activities"]);
+    assert_eq!(stored.body["data"]["unscheduled_activities"], json!([]));
+}
+
+#[tokio::test]
+#[ignore = "needs a Postgres server; set TEST_DATABASE_URL"]
+async fn schedules_of_other_users_and_unknown_ids_are_not_found() {
+    let app = app_with_database();
+    let created = post_json(&app, SCHEDULE, Some(&admin_token()), &small_request()).await;
+    let schedule_id = created.body["data"]["schedule_id"].as_str().unwrap();
+
+    let other_user = get(&app, &format!("{}/{}", SCHEDULE, schedule_id), Some(&fresh_user())).await;
+    let unknown = get(&app, &format!("{}/{}", SCHEDULE, "00000000-0000-4000-8000-000000000000"), Some(&admin_token())).await;
+    let not_a_uuid = get(&app, &format!("{}/missing", SCHEDULE), Some(&admin_token())).await;
+
+    assert_eq!(other_user.status, StatusCode::NOT_FOUND);
+    assert_eq!(unknown.status, StatusCode::NOT_FOUND);
+    assert_eq!(not_a_uuid.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+#[ignore = "needs a Postgres server; set TEST_DATABASE_URL"]
+async fn a_users_schedules_are_listed_oldest_first() {
+    let app = app_with_database();
+    let user = fresh_user();
+
+    let mut created = Vec::new();
+    for _ in 0..3 {
+        let response = post_json(&app, SCHEDULE, Some(&user), &small_request()).await;
+        created.push(response.body["data"]["schedule_id"].clone());
+    }
+
+    let listed = post_json(&app, "/api/v1/graphql", Some(&user), &json!({ "query": "{ schedules { schedule_id } }" })).await;
+    let listed: Vec<Value> = listed.body["data"]["schedules"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|schedule| schedule["schedule_id"].clone())
+        .collect();
+
+    assert_eq!(listed, created);
+}
+
+#[tokio::test]
+#[ignore = "needs a Postgres server; set TEST_DATABASE_URL"]
+async fn readiness_checks_the_database() {
+    let response = get(&app_with_database(), "/readyz", None).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["data"]["checks"]["schedule_store"]["status"], "ok");
+}
+
+/// The service binary on a free port, keeping schedules in the test database.
+fn start_service() -> (Child, u16) {
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rooms-scheduler"))
+        .env_remove("JWT_SECRET")
+        .env_remove("CONFIG_FILE")
+        .env("PORT", port.to_string())
+        .env("PERSIST_SCHEDULES", "true")
+        .env("DATABASE_URL", database_url())
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    for _ in 0..100 {
+        if TcpStream::connect(("127.0.0.1", port)).is_ok() {
+            return (child, port);
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    child.kill().unwrap();
+    child.wait().unwrap();
+    panic!("the service did not start listening");
+}
+
+/// Sends one HTTP/1.0 request and returns the status and JSON body.
+fn call(port: u16, method: &str, path: &str, body: Option<&Value>) -> (u16, Value) {
+    let body = body.map(Value::to_string).unwrap_or_default();
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    write!(
+        stream,
+        "{} {} HTTP/1.0\r\nHost: 127.0.0.1\r\nAuthorization: Bearer {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        method,
+        path,
+        admin_token(),
+        body.len(),
+        body
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+
+    (head.split_whitespace().nth(1).unwrap().parse().unwrap(), serde_json::from_str(body).unwrap())
+}
+
+#[test]
+#[ignore = "needs a Postgres server; set TEST_DATABASE_URL"]
+fn schedules_survive_a_restart() {
+    let (mut first, port) = start_service();
+    let (status, created) = call(port, "POST", SCHEDULE, Some(&small_request()));
+    first.kill().unwrap();
+    first.wait().unwrap();
+    assert_eq!(status, 200);
+
+    let schedule_id = created["data"]["schedule_id"].as_str().unwrap();
+    let (mut second, port) = start_service();
+    let (status, stored) = call(port, "GET", &format!("{}/{}", SCHEDULE, schedule_id), None);
+    second.kill().unwrap();
+    second.wait().unwrap();
+
+    assert_eq!(status, 200);
+    assert_eq!(stored["data"]["scheduled_activities"], created["data"]["scheduled_activities"]);
+}
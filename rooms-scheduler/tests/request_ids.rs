@@ -0,0 +1,54 @@
+mod common;
+
+use axum::{body::Body, http::{Method, StatusCode}};
+use common::{admin_token, app, get, post_json, request, send, small_request};
+
+fn is_uuid_v4(id: &str) -> bool {
+    let groups: Vec<&str> = id.split('-').collect();
+
+    groups.iter().map(|group| group.len()).eq([8, 4, 4, 4, 12])
+        && id.chars().all(|ch| ch == '-' || ch.is_ascii_hexdigit())
+        && groups[2].starts_with('4')
+}
+
+#[tokio::test]
+async fn generated_id_is_returned() {
+    let response = post_json(&app(&[]), "/api/v1/rooms/schedule", Some(&admin_token()), &small_request()).await;
+
+    let id = response.header("x-request-id").unwrap();
+    assert!(is_uuid_v4(id), "{}", id);
+}
+
+#[tokio::test]
+async fn each_request_gets_its_own_id() {
+    let app = app(&[]);
+    let first = get(&app, "/healthz", None).await;
+    let second = get(&app, "/healthz", None).await;
+
+    assert_ne!(first.header("x-request-id"), second.header("x-request-id"));
+}
+
+#[tokio::test]
+async fn provided_id_is_preserved() {
+    let request = request(Method::GET, "/healthz", None)
+        .header("x-request-id", "checkout-42")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = send(&app(&[]), request).await;
+
+    assert_eq!(response.header("x-request-id"), Some("checkout-42"));
+}
+
+#[tokio::test]
+async fn rejected_requests_carry_the_id_too() {
+    let request = request(Method::POST, "/api/v1/rooms/schedule", None)
+        .header("x-request-id", "unauthenticated-1")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = send(&app(&[]), request).await;
+
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(response.header("x-request-id"), Some("unauthenticated-1"));
+}
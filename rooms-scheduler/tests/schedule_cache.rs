@@ -0,0 +1,44 @@
+mod common;
+
+use axum::{Router, http::StatusCode};
+use common::{activity, admin_token, app, get, post_json, room, small_request};
+use serde_json::{Value, json};
+
+/// Scheduler runs so far, from the solve-duration histogram.
+async fn solve_count(app: &Router) -> u64 {
+    let metrics = get(app, "/metrics", None).await.text;
+
+    metrics
+        .lines()
+        .find_map(|line| line.strip_prefix("rooms_scheduler_solve_duration_seconds_count "))
+        .map_or(0, |count| count.parse().unwrap())
+}
+
+async fn schedule(app: &Router, body: &Value) {
+    let response = post_json(app, "/api/v1/rooms/schedule", Some(&admin_token()), body).await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.text);
+}
+
+// A single test, since the run counter is shared by the whole process
+#[tokio::test]
+async fn identical_requests_run_the_scheduler_once() {
+    let app = app(&[("SCHEDULE_CACHE_TTL_SECONDS", "300")]);
+    let before = solve_count(&app).await;
+
+    schedule(&app, &small_request()).await;
+    assert_eq!(solve_count(&app).await, before + 1);
+
+    schedule(&app, &small_request()).await;
+    assert_eq!(solve_count(&app).await, before + 1);
+
+    // Reordered rooms and activities normalize to the same request
+    let mut reordered = small_request();
+    reordered["rooms"].as_array_mut().unwrap().reverse();
+    reordered["activities"].as_array_mut().unwrap().reverse();
+    schedule(&app, &reordered).await;
+    assert_eq!(solve_count(&app).await, before + 1);
+
+    let other = json!({ "rooms": [room(1, "A-101", 40)], "activities": [activity(1, 12, &[0])] });
+    schedule(&app, &other).await;
+    assert_eq!(solve_count(&app).await, before + 2);
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{admin_token, app, get, post_json, small_request, token};
+use serde_json::json;
+
+const SCHEDULE: &str = "/api/v1/rooms/schedule";
+
+/// Schedules are kept in memory for the life of the process.
+fn vars() -> [(&'static str, &'static str); 1] {
+    [("PERSIST_SCHEDULES", "true")]
+}
+
+#[tokio::test]
+async fn schedule_id_is_in_the_body_and_the_header() {
+    let response = post_json(&app(&vars()), SCHEDULE, Some(&admin_token()), &small_request()).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+
+    let schedule_id = response.body["data"]["schedule_id"].as_str().unwrap();
+    assert_eq!(response.header("x-schedule-id"), Some(schedule_id));
+}
+
+#[tokio::test]
+async fn stored_schedule_is_returned_with_its_request() {
+    let app = app(&vars());
+    let created = post_json(&app, SCHEDULE, Some(&admin_token()), &small_request()).await;
+    let schedule_id = created.body["data"]["schedule_id"].as_str().unwrap();
+
+    let stored = get(&app, &format!("{}/{}", SCHEDULE, schedule_id), Some(&admin_token())).await;
+
+    assert_eq!(stored.status, StatusCode::OK);
+    assert_eq!(stored.body["data"]["schedule_id"], schedule_id);
+    assert_eq!(stored.body["data"]["user_id"], 1);
+    assert_eq!(stored.body["data"]["request"]["rooms"][1]["name"], "B-201");
+    assert_eq!(stored.body["data"]["request"]["activities"].as_array().unwrap().len(), 2);
+    assert_eq!(stored.body["data"]["scheduled_activities"], created.body["data"]["scheduled_activities"]);
+}
+
+#[tokio::test]
+async fn schedules_of_other_users_are_not_found() {
+    let app = app(&vars());
+    let created = post_json(&app, SCHEDULE, Some(&admin_token()), &small_request()).await;
+    let schedule_id = created.body["data"]["schedule_id"].as_str().unwrap();
+
+    let other_user = token(json!({ "user_id": 2, "sub": "other@example.edu" }));
+    let response = get(&app, &format!("{}/{}", SCHEDULE, schedule_id), Some(&other_user)).await;
+
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn unknown_schedule_is_404() {
+    let response = get(&app(&vars()), &format!("{}/missing", SCHEDULE), Some(&admin_token())).await;
+
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,27 @@
+mod common;
+
+use common::{activity, admin_token, app, post_json, room, small_request};
+use serde_json::json;
+
+const SCHEDULE: &str = "/api/v1/rooms/schedule";
+
+#[tokio::test]
+async fn run_duration_is_reported() {
+    let response = post_json(&app(&[]), SCHEDULE, Some(&admin_token()), &small_request()).await;
+
+    let duration_ms = &response.body["data"]["duration_ms"];
+    assert!(duration_ms.is_u64(), "{}", duration_ms);
+    assert_eq!(&response.body["data"]["stats"]["duration_ms"], duration_ms);
+}
+
+#[tokio::test]
+async fn every_placement_is_explained() {
+    let body = json!({ "rooms": [room(1, "A-101", 40), room(2, "B-201", 80)], "activities": [activity(1, 35, &[0]), activity(2, 60, &[1])] });
+    let response = post_json(&app(&[]), SCHEDULE, Some(&admin_token()), &body).await;
+
+    let scheduled = response.body["data"]["scheduled_activities"].as_array().unwrap();
+    let reason = |id: u32| &scheduled.iter().find(|a| a["id"] == id).unwrap()["assignment_reason"];
+
+    assert_eq!(reason(1), "best_score");
+    assert_eq!(reason(2), "only_fitting_room");
+}
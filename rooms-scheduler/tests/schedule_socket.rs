@@ -0,0 +1,129 @@
+mod common;
+
+use std::net::SocketAddr;
+
+use common::{admin_token, app, small_request};
+use serde_json::{Value, json};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+/// Upgrades only work over a real connection, so the router is served on a
+/// local port.
+async fn serve() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let service = app(&[]).into_make_service_with_connect_info::<SocketAddr>();
+
+    tokio::spawn(async move { axum::serve(listener, service).await.unwrap() });
+
+    address
+}
+
+/// Opens `/api/v1/rooms/schedule/ws`, returning the status line and the
+/// connection when one was kept.
+async fn connect(token: Option<&str>) -> (String, TcpStream) {
+    let address = serve().await;
+    let mut stream = TcpStream::connect(address).await.unwrap();
+
+    let authorization = token.map(|token| format!("Authorization: Bearer {}\r\n", token)).unwrap_or_default();
+    let handshake = format!(
+        "GET /api/v1/rooms/schedule/ws HTTP/1.1\r\nHost: {}\r\nConnection: Upgrade\r\nUpgrade: websocket\r\n\
+         Sec-WebSocket-Version: 13\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n{}\r\n",
+        address, authorization
+    );
+    stream.write_all(handshake.as_bytes()).await.unwrap();
+
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        head.push(stream.read_u8().await.unwrap());
+    }
+
+    let head = String::from_utf8(head).unwrap();
+    (head, stream)
+}
+
+/// Sends `text` as one masked frame, as clients must.
+async fn send_text(stream: &mut TcpStream, text: &str) {
+    let mask = [0x12, 0x34, 0x56, 0x78];
+    let mut frame = vec![0x81];
+
+    match text.len() {
+        length if length < 126 => frame.push(0x80 | length as u8),
+        length => {
+            frame.push(0x80 | 126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+    }
+
+    frame.extend_from_slice(&mask);
+    frame.extend(text.bytes().enumerate().map(|(index, byte)| byte ^ mask[index % 4]));
+    stream.write_all(&frame).await.unwrap();
+}
+
+/// Every text message until the server closes, parsed as JSON.
+async fn messages(stream: &mut TcpStream) -> Vec<Value> {
+    let mut messages = Vec::new();
+
+    loop {
+        let opcode = stream.read_u8().await.unwrap() & 0x0F;
+        let length = match stream.read_u8().await.unwrap() & 0x7F {
+            126 => stream.read_u16().await.unwrap() as usize,
+            127 => stream.read_u64().await.unwrap() as usize,
+            length => length as usize,
+        };
+
+        let mut payload = vec![0; length];
+        stream.read_exact(&mut payload).await.unwrap();
+
+        match opcode {
+            0x1 => messages.push(serde_json::from_slice(&payload).unwrap()),
+            0x8 => return messages,
+            opcode => panic!("unexpected opcode {}", opcode),
+        }
+    }
+}
+
+#[tokio::test]
+async fn progress_frames_precede_the_result() {
+    let (head, mut stream) = connect(Some(&admin_token())).await;
+    assert!(head.starts_with("HTTP/1.1 101"), "{}", head);
+    assert!(head.contains("s3pPLMBiTxaQ9kYGzzhZRbK+xOo="), "{}", head);
+
+    send_text(&mut stream, &small_request().to_string()).await;
+    let messages = messages(&mut stream).await;
+
+    let (result, progress) = messages.split_last().unwrap();
+    assert!(!progress.is_empty());
+    assert!(progress.iter().all(|message| message["event"] == "progress"));
+
+    let last = &progress[progress.len() - 1]["data"];
+    assert_eq!(last["scheduled_so_far"], 2);
+    assert_eq!(last["unscheduled_so_far"], 0);
+    assert!(last["slot"].is_number());
+
+    assert_eq!(result["event"], "result");
+    assert_eq!(result["data"]["scheduled_activities"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn invalid_request_gets_an_error_message() {
+    let (_, mut stream) = connect(Some(&admin_token())).await;
+
+    let body = json!({ "rooms": [{ "id": 1, "name": "A-101", "capacity": 0 }], "activities": [] });
+    send_text(&mut stream, &body.to_string()).await;
+    let messages = messages(&mut stream).await;
+
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0]["event"], "error");
+    assert_eq!(messages[0]["data"]["success"], false);
+    assert!(messages[0]["data"].to_string().contains("capacity of zero"), "{}", messages[0]);
+}
+
+#[tokio::test]
+async fn handshake_without_a_token_is_401() {
+    let (head, _) = connect(None).await;
+
+    assert!(head.starts_with("HTTP/1.1 401"), "{}", head);
+}
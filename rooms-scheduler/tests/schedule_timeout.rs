@@ -0,0 +1,20 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{activity, admin_token, app, post_json, room};
+use serde_json::json;
+
+const VARS: &[(&str, &str)] = &[("SCHEDULE_TIMEOUT_MS", "1")];
+
+#[tokio::test]
+async fn run_past_the_timeout_is_504() {
+    let rooms: Vec<_> = (1..=200).map(|id| room(id, &format!("R-{}", id), 10 + id % 90)).collect();
+    let activities: Vec<_> = (1..=1500).map(|id| activity(id, 5 + id % 85, &[id % 40, id % 40 + 1])).collect();
+    let body = json!({ "rooms": rooms, "activities": activities, "allow_partial": true, "strategy": "annealing" });
+
+    let response = post_json(&app(VARS), "/api/v1/rooms/schedule", Some(&admin_token()), &body).await;
+
+    assert_eq!(response.status, StatusCode::GATEWAY_TIMEOUT);
+    assert_eq!(response.body["success"], false);
+    assert!(response.body["error"].as_str().unwrap().contains("within 1 ms"), "{}", response.text);
+}
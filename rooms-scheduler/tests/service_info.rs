@@ -0,0 +1,26 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{app, get};
+
+const VARS: &[(&str, &str)] = &[("MAX_BODY_BYTES", "4096"), ("SCHEDULE_TIMEOUT_MS", "2500")];
+
+#[tokio::test]
+async fn info_reflects_the_configuration() {
+    let response = get(&app(VARS), "/api/v1/info", None).await;
+    let info = &response.body["data"];
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(info["max_body_bytes"], 4096);
+    assert_eq!(info["schedule_timeout_ms"], 2500);
+    assert_eq!(info["base_path"], "");
+}
+
+#[tokio::test]
+async fn info_lists_the_compiled_strategies() {
+    let response = get(&app(VARS), "/api/v1/info", None).await;
+    let strategies = response.body["data"]["strategies"].as_array().unwrap();
+
+    assert!(strategies.iter().any(|strategy| strategy == "greedy"));
+    assert_eq!(strategies.iter().any(|strategy| strategy == "exact"), cfg!(feature = "exact"));
+}